@@ -0,0 +1,268 @@
+use crate::assets::{Asset, AssetIndex, AssetKind, Material, Thumbnails};
+use crate::error;
+use crate::history::History;
+use crate::math::Ray;
+use crate::mesh::{MeshId, MeshLibrary};
+use crate::prefab::Prefabs;
+use crate::scene::{Node, Scene};
+use crate::script::Script;
+use crate::selection::Selection;
+use egui::{pos2, vec2, Align2, Color32, Id, Rect, Sense, TextStyle, Ui};
+use glium::backend::Facade;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// Where the browser looks unless told otherwise
+const DEFAULT_ROOT: &str = "assets";
+
+/// Width of a tile, the picture and the name below it
+const TILE_WIDTH: f32 = 72.0;
+const PICTURE_SIZE: f32 = 64.0;
+
+/// Where meshes land when dropped onto nothing, along the ray under the cursor
+const DROP_DISTANCE: f32 = 3.0;
+
+/// Panel listing the assets of a project directory, which can be dragged into the viewport
+pub struct AssetBrowser {
+    index: AssetIndex,
+    thumbnails: Thumbnails,
+    /// directory as typed, scanned when "Scan" is clicked
+    root: String,
+    search: String,
+    /// kinds that are listed
+    kinds: Vec<AssetKind>,
+    dragging: Option<Asset>,
+    /// why the last scan failed
+    error: Option<String>,
+}
+
+impl Default for AssetBrowser {
+    fn default() -> Self {
+        let mut browser = Self {
+            index: AssetIndex::default(),
+            thumbnails: Thumbnails::default(),
+            root: DEFAULT_ROOT.to_string(),
+            search: String::new(),
+            kinds: AssetKind::ALL.to_vec(),
+            dragging: None,
+            error: None,
+        };
+        browser.scan();
+        browser
+    }
+}
+
+impl AssetBrowser {
+    fn scan(&mut self) {
+        match AssetIndex::scan(&self.root) {
+            Ok(index) => {
+                self.index = index;
+                self.error = None;
+            }
+            Err(err) => {
+                self.index = AssetIndex::default();
+                self.error = Some(format!("Could not read {}: {}", self.root, err));
+            }
+        }
+    }
+
+    /// Renders a few more mesh thumbnails, call before the browser is shown
+    pub fn render_thumbnails<F: Facade>(
+        &mut self,
+        facade: &F,
+        painter: &mut egui_glium::Painter,
+        program: &glium::Program,
+        meshes: &mut MeshLibrary,
+    ) -> Result<(), error::Error> {
+        self.thumbnails.render_missing(facade, painter, program, meshes, &self.index)
+    }
+
+    /// Shows the browser, returns an asset that was dropped outside of the editor's windows
+    pub fn show(&mut self, ui: &mut Ui) -> Option<Asset> {
+        ui.horizontal(|ui| {
+            ui.label("Directory");
+            ui.text_edit_singleline(&mut self.root);
+            if ui.button("Scan").clicked() {
+                self.scan();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Search");
+            ui.text_edit_singleline(&mut self.search);
+        });
+        ui.horizontal(|ui| {
+            for &kind in AssetKind::ALL.iter() {
+                let shown = self.kinds.contains(&kind);
+                if ui.selectable_label(shown, format!("{:?}", kind)).clicked() {
+                    if shown {
+                        self.kinds.retain(|&other| other != kind);
+                    } else {
+                        self.kinds.push(kind);
+                    }
+                }
+            }
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(Color32::RED, error);
+        }
+        ui.separator();
+
+        let assets: Vec<Asset> = self.index.search(&self.search, &self.kinds).cloned().collect();
+        egui::ScrollArea::auto_sized().show(ui, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                for asset in &assets {
+                    self.tile(ui, asset);
+                }
+            });
+            if assets.is_empty() {
+                ui.label("No assets found");
+            }
+        });
+
+        let dragged = self.dragging.clone()?;
+        egui::show_tooltip_text(ui.ctx(), Id::new("asset_drag"), format!("Place {}", dragged.name()));
+        if ui.input().pointer.any_released() {
+            self.dragging = None;
+            if !ui.ctx().is_pointer_over_area() {
+                return Some(dragged);
+            }
+        }
+        None
+    }
+
+    fn tile(&mut self, ui: &mut Ui, asset: &Asset) {
+        let (rect, response) = ui.allocate_exact_size(vec2(TILE_WIDTH, PICTURE_SIZE + 16.0), Sense::click_and_drag());
+        let picture = Rect::from_center_size(
+            pos2(rect.center().x, rect.top() + PICTURE_SIZE / 2.0),
+            vec2(PICTURE_SIZE, PICTURE_SIZE),
+        );
+
+        if response.hovered() || self.dragging.as_ref() == Some(asset) {
+            ui.painter().rect_filled(rect, 4.0, ui.visuals().widgets.hovered.bg_fill);
+        }
+        match self.thumbnails.get(&asset.path) {
+            // textures are stored bottom row first, egui expects the top one first
+            Some(texture) => egui::Image::new(texture, picture.size())
+                .uv(Rect::from_min_max(pos2(0.0, 1.0), pos2(1.0, 0.0)))
+                .paint_at(ui, picture),
+            None => {
+                ui.painter().rect_filled(picture.shrink(4.0), 4.0, kind_color(asset.kind));
+                ui.painter().text(
+                    picture.center(),
+                    Align2::CENTER_CENTER,
+                    format!("{:?}", asset.kind),
+                    TextStyle::Small,
+                    Color32::WHITE,
+                );
+            }
+        }
+        let mut name = asset.name();
+        if name.chars().count() > 12 {
+            name = format!("{}…", name.chars().take(11).collect::<String>());
+        }
+        ui.painter().text(
+            pos2(rect.center().x, picture.bottom() + 2.0),
+            Align2::CENTER_TOP,
+            name,
+            TextStyle::Small,
+            ui.visuals().text_color(),
+        );
+
+        if response.drag_started() {
+            self.dragging = Some(asset.clone());
+        }
+        response.on_hover_text(asset.path.display().to_string());
+    }
+}
+
+fn kind_color(kind: AssetKind) -> Color32 {
+    match kind {
+        AssetKind::Mesh => Color32::from_rgb(90, 110, 160),
+        AssetKind::Texture => Color32::from_rgb(150, 100, 60),
+        AssetKind::Material => Color32::from_rgb(140, 70, 120),
+        AssetKind::Prefab => Color32::from_rgb(60, 120, 140),
+        AssetKind::Scene => Color32::from_rgb(70, 130, 80),
+        AssetKind::Script => Color32::from_rgb(120, 120, 60),
+    }
+}
+
+/// Puts `asset` into the scene where it was dropped, `ray` goes through the cursor.
+/// Meshes, prefabs and scenes are added as new nodes, materials and scripts go onto the node under the cursor.
+pub fn place(
+    asset: &Asset,
+    ray: &Ray,
+    scene: &mut Scene,
+    history: &mut History,
+    selection: &mut Selection,
+    meshes: &mut MeshLibrary,
+    prefabs: &mut Prefabs,
+) -> Result<(), Box<dyn Error>> {
+    let hit = scene.raycast(ray, meshes);
+    let distance = match hit {
+        Some((_, distance)) => distance,
+        None => DROP_DISTANCE,
+    };
+    let drop_point = ray.origin + ray.direction * distance;
+    match asset.kind {
+        AssetKind::Mesh => {
+            let id = MeshId::File(asset.path.clone());
+            meshes.load_now(&id)?;
+            let mut node = Node::new(&file_stem(&asset.path));
+            node.mesh = Some(id);
+            node.transform.location = drop_point;
+            selection.set(Some(history.add_node(scene, node)));
+        }
+        AssetKind::Prefab => {
+            let mut nodes = prefabs.load_now(&asset.path)?.instantiate(&asset.path);
+            let root = match nodes.iter_mut().find(|node| node.parent.is_none()) {
+                Some(root) => root,
+                None => return Err("the prefab has no root node".into()),
+            };
+            root.transform.location = drop_point;
+            let root = root.id;
+            let new_ids = history.insert_nodes(scene, &format!("Place {}", file_stem(&asset.path)), nodes);
+            selection.set(Some(new_ids[&root]));
+        }
+        AssetKind::Scene => {
+            let mut placed = Scene::load(&asset.path)?;
+            let ids: HashSet<_> = placed.nodes.iter().map(|node| node.id).collect();
+            for node in &mut placed.nodes {
+                // parents that aren't in the file would be nodes of this scene with the same id
+                if !matches!(node.parent, Some(parent) if ids.contains(&parent)) {
+                    node.parent = None;
+                }
+            }
+            let roots: Vec<_> = placed.nodes.iter().filter(|node| node.parent.is_none()).map(|node| node.id).collect();
+            let label = format!("Place {}", file_stem(&asset.path));
+            let new_ids = history.insert_nodes(scene, &label, placed.nodes);
+            selection.set(None);
+            for root in roots {
+                selection.toggle(new_ids[&root]);
+            }
+        }
+        AssetKind::Material | AssetKind::Script => {
+            let target = match hit.and_then(|(id, _)| scene.node_mut(id)) {
+                Some(node) => node,
+                None => return Err(format!("{:?}s have to be dropped onto a node", asset.kind).into()),
+            };
+            let before = target.clone();
+            if asset.kind == AssetKind::Material {
+                target.color = Material::load(&asset.path)?.color;
+            } else {
+                target.script = Some(ron::from_str::<Script>(&fs::read_to_string(&asset.path)?)?);
+            }
+            history.record_change(&before, target);
+            history.seal();
+        }
+        AssetKind::Texture => return Err("textures can't be used yet".into()),
+    }
+    Ok(())
+}
+
+fn file_stem(path: &Path) -> String {
+    let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    // `crate.scene.ron` is called "crate"
+    name.split('.').next().unwrap_or_default().to_string()
+}
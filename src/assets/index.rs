@@ -0,0 +1,91 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AssetKind {
+    Mesh,
+    Texture,
+    Material,
+    Prefab,
+    Scene,
+    Script,
+}
+
+impl AssetKind {
+    pub const ALL: [AssetKind; 6] = [
+        AssetKind::Mesh,
+        AssetKind::Texture,
+        AssetKind::Material,
+        AssetKind::Prefab,
+        AssetKind::Scene,
+        AssetKind::Script,
+    ];
+
+    /// What kind of asset the file at `path` is, by its extension.
+    /// RON files only count with a second extension saying what's in them, e.g. `crate.material.ron`.
+    pub fn of(path: &Path) -> Option<AssetKind> {
+        let name = path.file_name()?.to_str()?.to_lowercase();
+        let kind = match name.rsplit('.').next()? {
+            "obj" => AssetKind::Mesh,
+            "png" | "jpg" | "jpeg" | "tga" | "bmp" => AssetKind::Texture,
+            "ron" if name.ends_with(".material.ron") => AssetKind::Material,
+            "ron" if name.ends_with(".prefab.ron") => AssetKind::Prefab,
+            "ron" if name.ends_with(".scene.ron") => AssetKind::Scene,
+            "ron" if name.ends_with(".script.ron") => AssetKind::Script,
+            _ => return None,
+        };
+        Some(kind)
+    }
+}
+
+/// A file the asset browser shows
+#[derive(Clone, Debug, PartialEq)]
+pub struct Asset {
+    pub path: PathBuf,
+    pub kind: AssetKind,
+}
+
+impl Asset {
+    pub fn name(&self) -> String {
+        match self.path.file_name() {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => self.path.display().to_string(),
+        }
+    }
+}
+
+/// Every asset below a project directory
+#[derive(Debug, Default)]
+pub struct AssetIndex {
+    /// sorted by path
+    pub assets: Vec<Asset>,
+}
+
+impl AssetIndex {
+    /// Looks through `root` and all directories below it
+    pub fn scan<P: AsRef<Path>>(root: P) -> io::Result<Self> {
+        let mut assets = Vec::new();
+        let mut directories = vec![root.as_ref().to_path_buf()];
+        while let Some(directory) = directories.pop() {
+            for entry in fs::read_dir(&directory)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    directories.push(path);
+                } else if let Some(kind) = AssetKind::of(&path) {
+                    assets.push(Asset { path, kind });
+                }
+            }
+        }
+        assets.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(Self { assets })
+    }
+
+    /// Assets whose name contains `search`, ignoring case, and whose kind is in `kinds`
+    pub fn search<'a>(&'a self, search: &str, kinds: &'a [AssetKind]) -> impl Iterator<Item = &'a Asset> {
+        let search = search.to_lowercase();
+        self.assets
+            .iter()
+            .filter(move |asset| kinds.contains(&asset.kind) && asset.name().to_lowercase().contains(&search))
+    }
+}
@@ -0,0 +1,264 @@
+use crate::error::Error;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often files are checked for changes
+const RELOAD_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Something that can be read from a file by the asset manager
+pub trait Loadable: Send + Sized + 'static {
+    fn load(path: &Path) -> Result<Self, Error>;
+}
+
+/// Reference to an asset of an `Assets` store, cheap to clone.
+/// The asset stays loaded while any handle to it is alive.
+pub struct Handle<T> {
+    path: Arc<PathBuf>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            path: self.path.clone(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.path.hash(state)
+    }
+}
+
+impl<T> std::fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Handle({})", self.path.display())
+    }
+}
+
+enum State<T> {
+    Loading,
+    Loaded(T),
+    Failed(Error),
+}
+
+struct Entry<T> {
+    /// the store's own handle, the asset is unused when it's the only one left
+    handle: Handle<T>,
+    state: State<T>,
+    /// bumped every time the asset is (re)loaded
+    version: u64,
+    /// modification time of the file when it was last read
+    modified: Option<SystemTime>,
+    reloading: bool,
+}
+
+/// Assets of one type, loaded on a worker thread and shared by path.
+/// Assets nobody holds a handle to are dropped, files that change are loaded again.
+pub struct Assets<T: Loadable> {
+    entries: HashMap<PathBuf, Entry<T>>,
+    requests: Sender<PathBuf>,
+    results: Receiver<(PathBuf, Option<SystemTime>, Result<T, Error>)>,
+    last_reload_check: Instant,
+    /// failed loads and reloads the user hasn't been told about yet
+    errors: Vec<Error>,
+}
+
+impl<T: Loadable> Assets<T> {
+    pub fn new() -> Self {
+        let (requests, worker_requests) = mpsc::channel::<PathBuf>();
+        let (worker_results, results) = mpsc::channel();
+        thread::spawn(move || {
+            // ends when the store and with it the sender is dropped
+            for path in worker_requests {
+                let modified = modified(&path);
+                let asset = T::load(&path);
+                if worker_results.send((path, modified, asset)).is_err() {
+                    break;
+                }
+            }
+        });
+        Self {
+            entries: HashMap::new(),
+            requests,
+            results,
+            last_reload_check: Instant::now(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Handle to the asset at `path`, it's loaded in the background the first time
+    pub fn load<P: AsRef<Path>>(&mut self, path: P) -> Handle<T> {
+        let path = path.as_ref();
+        if let Some(entry) = self.entries.get(path) {
+            return entry.handle.clone();
+        }
+        let handle = Handle {
+            path: Arc::new(path.to_path_buf()),
+            marker: PhantomData,
+        };
+        self.entries.insert(path.to_path_buf(), Entry {
+            handle: handle.clone(),
+            state: State::Loading,
+            version: 0,
+            modified: None,
+            reloading: false,
+        });
+        // the worker only stops once `requests` is dropped
+        self.requests.send(path.to_path_buf()).unwrap();
+        handle
+    }
+
+    /// `None` while the asset is still loading or if it couldn't be loaded
+    pub fn get(&self, handle: &Handle<T>) -> Option<&T> {
+        self.get_path(handle.path())
+    }
+
+    pub fn get_path(&self, path: &Path) -> Option<&T> {
+        match &self.entries.get(path)?.state {
+            State::Loaded(asset) => Some(asset),
+            _ => None,
+        }
+    }
+
+    /// Why the asset couldn't be loaded, if it couldn't
+    pub fn error(&self, handle: &Handle<T>) -> Option<&Error> {
+        match &self.entries.get(handle.path())?.state {
+            State::Failed(error) => Some(error),
+            _ => None,
+        }
+    }
+
+    /// Goes up every time the asset is (re)loaded, 0 until it first is
+    pub fn version(&self, handle: &Handle<T>) -> u64 {
+        self.entries.get(handle.path()).map_or(0, |entry| entry.version)
+    }
+
+    pub fn is_loading(&self, handle: &Handle<T>) -> bool {
+        matches!(self.entries.get(handle.path()), Some(Entry { state: State::Loading, .. }))
+    }
+
+    /// Loaded assets with their path and version
+    pub fn iter(&self) -> impl Iterator<Item = (&Path, u64, &T)> {
+        self.entries.iter().filter_map(|(path, entry)| match &entry.state {
+            State::Loaded(asset) => Some((path.as_path(), entry.version, asset)),
+            _ => None,
+        })
+    }
+
+    /// Takes in what the worker finished, drops unused assets and reloads changed files.
+    /// Returns the paths of assets that were (re)loaded or failed to.
+    pub fn update(&mut self) -> Vec<PathBuf> {
+        let mut finished = Vec::new();
+        while let Ok(result) = self.results.try_recv() {
+            finished.push(self.receive(result));
+        }
+
+        self.entries.retain(|_, entry| Arc::strong_count(&entry.handle.path) > 1);
+
+        if self.last_reload_check.elapsed() >= RELOAD_INTERVAL {
+            self.last_reload_check = Instant::now();
+            for (path, entry) in &mut self.entries {
+                let changed = match entry.state {
+                    State::Loading => false,
+                    _ => !entry.reloading && modified(path) != entry.modified,
+                };
+                if changed {
+                    entry.reloading = true;
+                    self.requests.send(path.clone()).unwrap();
+                }
+            }
+        }
+        finished
+    }
+
+    /// Loads and reloads that failed since the last call, they're logged as well
+    pub fn take_errors(&mut self) -> Vec<Error> {
+        std::mem::take(&mut self.errors)
+    }
+
+    /// Blocks until everything that was asked for is loaded (or failed to)
+    pub fn wait(&mut self) {
+        while self.entries.values().any(|entry| matches!(entry.state, State::Loading)) {
+            match self.results.recv() {
+                Ok(result) => {
+                    self.receive(result);
+                }
+                Err(_) => break,
+            }
+        }
+    }
+
+    /// Blocks until the assets at `paths` are loaded (or failed to), the others go on loading in the background
+    pub fn wait_for(&mut self, paths: &[&Path]) {
+        let loading = |entries: &HashMap<PathBuf, Entry<T>>| {
+            paths
+                .iter()
+                .any(|path| matches!(entries.get(*path), Some(Entry { state: State::Loading, .. })))
+        };
+        while loading(&self.entries) {
+            match self.results.recv() {
+                Ok(result) => {
+                    self.receive(result);
+                }
+                Err(_) => break,
+            }
+        }
+    }
+
+    fn receive(&mut self, (path, modified, asset): (PathBuf, Option<SystemTime>, Result<T, Error>)) -> PathBuf {
+        if let Some(entry) = self.entries.get_mut(&path) {
+            entry.modified = modified;
+            match asset {
+                Ok(asset) => {
+                    entry.state = State::Loaded(asset);
+                    entry.version += 1;
+                }
+                // a broken save keeps what was there before
+                Err(error) if entry.reloading && matches!(entry.state, State::Loaded(_)) => {
+                    log::warn!("Could not reload {}", error);
+                    self.errors.push(error);
+                }
+                Err(error) => {
+                    log::error!("Could not load {}", error);
+                    self.errors.push(error.clone());
+                    entry.state = State::Failed(error);
+                }
+            }
+            entry.reloading = false;
+        }
+        path
+    }
+}
+
+impl<T: Loadable> Default for Assets<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    path.metadata().and_then(|metadata| metadata.modified()).ok()
+}
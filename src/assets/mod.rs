@@ -0,0 +1,11 @@
+mod browser;
+mod index;
+mod manager;
+mod material;
+mod thumbnails;
+
+pub use browser::*;
+pub use index::*;
+pub use manager::*;
+pub use material::*;
+pub use thumbnails::*;
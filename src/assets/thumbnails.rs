@@ -0,0 +1,143 @@
+use crate::assets::{AssetIndex, AssetKind, Handle};
+use crate::error::{Error, RenderContext};
+use crate::mesh::{GpuMesh, Mesh, MeshId, MeshLibrary};
+use crate::render::{Instance, Lights};
+use egui::TextureId;
+use glam::f32::{Mat4, Vec3};
+use glium::backend::Facade;
+use glium::framebuffer::{DepthRenderBuffer, SimpleFrameBuffer};
+use glium::texture::{DepthFormat, SrgbTexture2d};
+use glium::{uniform, Surface};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Width and height of a thumbnail in pixels
+pub const THUMBNAIL_SIZE: u32 = 64;
+
+/// Rendering all of a big project at once would stall the editor
+const THUMBNAILS_PER_FRAME: usize = 2;
+
+/// Pictures of the mesh assets, rendered offscreen with the scene's shader
+#[derive(Default)]
+pub struct Thumbnails {
+    textures: HashMap<PathBuf, Thumbnail>,
+    /// meshes that are loaded in the background to be pictured
+    pending: HashMap<PathBuf, Handle<Mesh>>,
+}
+
+struct Thumbnail {
+    /// `None` for meshes that couldn't be loaded or pictured
+    texture: Option<TextureId>,
+    /// version of the mesh it shows, files that are in use are reloaded when they change
+    version: u64,
+}
+
+impl Thumbnails {
+    pub fn get(&self, path: &Path) -> Option<TextureId> {
+        self.textures.get(path).and_then(|thumbnail| thumbnail.texture)
+    }
+
+    /// Renders a few of the mesh thumbnails whose meshes finished loading, and asks for the missing ones.
+    /// Thumbnails that fail to render stay empty, the first failure is returned.
+    pub fn render_missing<F: Facade>(
+        &mut self,
+        facade: &F,
+        painter: &mut egui_glium::Painter,
+        program: &glium::Program,
+        meshes: &mut MeshLibrary,
+        index: &AssetIndex,
+    ) -> Result<(), Error> {
+        for (id, version, _) in meshes.iter() {
+            if let MeshId::File(path) = id {
+                if matches!(self.textures.get(&path), Some(thumbnail) if thumbnail.version != version) {
+                    if let Some(texture) = self.textures.remove(&path).and_then(|thumbnail| thumbnail.texture) {
+                        painter.free_user_texture(texture);
+                    }
+                }
+            }
+        }
+        for asset in &index.assets {
+            if asset.kind == AssetKind::Mesh
+                && !self.textures.contains_key(&asset.path)
+                && !self.pending.contains_key(&asset.path)
+            {
+                self.pending.insert(asset.path.clone(), meshes.request(&asset.path));
+            }
+        }
+
+        let finished: Vec<PathBuf> = self
+            .pending
+            .iter()
+            .filter(|(_, handle)| !meshes.is_loading(handle))
+            .map(|(path, _)| path.clone())
+            .take(THUMBNAILS_PER_FRAME)
+            .collect();
+        let mut result = Ok(());
+        for path in finished {
+            // dropping the handle lets the mesh go unless the scene uses it
+            let handle = self.pending.remove(&path).unwrap();
+            let version = meshes.version(&handle);
+            let texture = match meshes.get_handle(&handle).map(|mesh| render(facade, program, mesh)) {
+                Some(Ok(texture)) => Some(painter.register_glium_texture(texture)),
+                Some(Err(err)) => {
+                    if result.is_ok() {
+                        result = Err(err);
+                    }
+                    None
+                }
+                None => None,
+            };
+            self.textures.insert(path, Thumbnail { texture, version });
+        }
+        result
+    }
+}
+
+/// `mesh` seen from the front, slightly from above and the right, filling the picture
+fn render<F: Facade>(facade: &F, program: &glium::Program, mesh: &Mesh) -> Result<SrgbTexture2d, Error> {
+    let what = "rendering a thumbnail";
+    let texture = SrgbTexture2d::empty(facade, THUMBNAIL_SIZE, THUMBNAIL_SIZE).rendering(what)?;
+    let depth = DepthRenderBuffer::new(facade, DepthFormat::I24, THUMBNAIL_SIZE, THUMBNAIL_SIZE).rendering(what)?;
+    let mut target = SimpleFrameBuffer::with_depth_buffer(facade, &texture, &depth).rendering(what)?;
+    target.clear_color_and_depth((0.0, 0.0, 0.0, 0.0), 1.0);
+
+    let bounds = match mesh.bounds() {
+        Some(bounds) => bounds,
+        None => return Ok(texture),
+    };
+    let center = bounds.center();
+    let radius = (bounds.max - center).length().max(f32::EPSILON);
+    let fov = std::f32::consts::PI / 4.0;
+    // far enough back for the bounding sphere to fit
+    let distance = radius / (fov / 2.0).sin();
+    let eye = center + Vec3::new(0.5, 0.4, -1.0).normalize() * distance;
+    let perspective = Mat4::perspective_rh_gl(fov, 1.0, distance - radius * 1.01, distance + radius * 1.01);
+    let view = Mat4::look_at_rh(eye, center, Vec3::Y);
+
+    let gpu_mesh = GpuMesh::new(facade, mesh)?;
+    let params = glium::DrawParameters {
+        depth: glium::Depth {
+            test: glium::draw_parameters::DepthTest::IfLess,
+            write: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let instance = glium::VertexBuffer::new(facade, &[Instance::new(Mat4::IDENTITY, [0.85, 0.85, 0.85, 1.0])])
+        .rendering(what)?;
+    target
+        .draw(
+            (&gpu_mesh.positions, &gpu_mesh.normals, instance.per_instance().map_err(|_| "the graphics card can't draw instances").rendering(what)?),
+            &gpu_mesh.indices,
+            program,
+            // without lights, the program remembers the ones of the last scene drawn otherwise
+            &Lights::default().uniforms(uniform! {
+                view: view.to_cols_array_2d(),
+                perspective: perspective.to_cols_array_2d(),
+                u_light: [-0.6, 0.8, 0.9f32],
+            }),
+            &params,
+        )
+        .rendering(what)?;
+    Ok(texture)
+}
@@ -0,0 +1,285 @@
+use egui::{CtxRef, CursorIcon, Layout, Order, Pos2, Rect, Sense, Ui, Vec2};
+use glium::glutin::dpi::{PhysicalPosition, PhysicalSize};
+use glium::glutin::window::{Window, WindowBuilder};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// Where the window's position and size are kept between sessions
+pub const GEOMETRY_FILE: &str = "window.ron";
+
+/// Width of the strips along the edges that resize the window, in points
+const RESIZE_MARGIN: f32 = 5.0;
+
+/// The window can't be resized smaller than this, in pixels
+const MIN_SIZE: [i32; 2] = [400, 300];
+
+/// Where the window was and how big, as it's restored on the next start
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub position: [i32; 2],
+    pub size: [u32; 2],
+    pub maximized: bool,
+}
+
+impl WindowGeometry {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let text = fs::read_to_string(path)?;
+        Ok(ron::from_str(&text)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::new())?;
+        fs::write(path, text)?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Edge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Edge {
+    const ALL: [Edge; 8] = [
+        Edge::Left,
+        Edge::Right,
+        Edge::Top,
+        Edge::Bottom,
+        Edge::TopLeft,
+        Edge::TopRight,
+        Edge::BottomLeft,
+        Edge::BottomRight,
+    ];
+
+    /// Which way the edge moves the sides of the window, -1 for the left or top one and 1 for the right or bottom one
+    fn sides(self) -> (i32, i32) {
+        match self {
+            Edge::Left => (-1, 0),
+            Edge::Right => (1, 0),
+            Edge::Top => (0, -1),
+            Edge::Bottom => (0, 1),
+            Edge::TopLeft => (-1, -1),
+            Edge::TopRight => (1, -1),
+            Edge::BottomLeft => (-1, 1),
+            Edge::BottomRight => (1, 1),
+        }
+    }
+
+    /// The strip of `screen` that grabs this edge, corners are squares and the sides go between them
+    fn rect(self, screen: Rect) -> Rect {
+        let span = |side: i32, min: f32, max: f32| match side {
+            -1 => (min, min + RESIZE_MARGIN),
+            1 => (max - RESIZE_MARGIN, max),
+            _ => (min + RESIZE_MARGIN, max - RESIZE_MARGIN),
+        };
+        let (horizontal, vertical) = self.sides();
+        let (left, right) = span(horizontal, screen.min.x, screen.max.x);
+        let (top, bottom) = span(vertical, screen.min.y, screen.max.y);
+        Rect::from_min_max(Pos2::new(left, top), Pos2::new(right, bottom))
+    }
+
+    fn cursor(self) -> CursorIcon {
+        match self.sides() {
+            (0, _) => CursorIcon::ResizeVertical,
+            (_, 0) => CursorIcon::ResizeHorizontal,
+            (horizontal, vertical) if horizontal == vertical => CursorIcon::ResizeNwSe,
+            _ => CursorIcon::ResizeNeSw,
+        }
+    }
+}
+
+/// The window as it was when an edge was grabbed
+struct ResizeDrag {
+    edge: Edge,
+    /// on the screen, in pixels
+    cursor: PhysicalPosition<i32>,
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+}
+
+/// Title bar and resize handles for the undecorated window
+#[derive(Default)]
+pub struct WindowChrome {
+    /// off when the system decorates the window or it's fullscreen, there's nothing to do then
+    pub enabled: bool,
+    /// the last one seen while the window wasn't maximized, `None` until then
+    geometry: Option<WindowGeometry>,
+    /// winit can't tell on every platform, so the buttons keep track
+    maximized: bool,
+    /// the window manager moves the window once it's asked to, until the button is released
+    moving: bool,
+    resize: Option<ResizeDrag>,
+}
+
+impl WindowChrome {
+    /// Picks up the geometry of the last session, if there was one
+    pub fn load(enabled: bool) -> Self {
+        let geometry = match WindowGeometry::load(GEOMETRY_FILE) {
+            Ok(geometry) => Some(geometry),
+            Err(_) if !Path::new(GEOMETRY_FILE).exists() => None,
+            Err(err) => {
+                log::warn!("Could not read {}: {}", GEOMETRY_FILE, err);
+                None
+            }
+        };
+        Self {
+            enabled,
+            geometry,
+            maximized: matches!(geometry, Some(WindowGeometry { maximized: true, .. })),
+            ..Default::default()
+        }
+    }
+
+    /// Opens the window where it was last time
+    pub fn window_builder(&self, builder: WindowBuilder) -> WindowBuilder {
+        match self.geometry {
+            Some(geometry) if self.enabled => builder
+                .with_position(PhysicalPosition::new(geometry.position[0], geometry.position[1]))
+                .with_inner_size(PhysicalSize::new(geometry.size[0], geometry.size[1]))
+                .with_maximized(geometry.maximized),
+            _ => builder,
+        }
+    }
+
+    pub fn save(&self) {
+        if !self.enabled {
+            return;
+        }
+        if let Some(geometry) = self.geometry {
+            let geometry = WindowGeometry {
+                maximized: self.maximized,
+                ..geometry
+            };
+            if let Err(err) = geometry.save(GEOMETRY_FILE) {
+                log::warn!("Could not save {}: {}", GEOMETRY_FILE, err);
+            }
+        }
+    }
+
+    fn toggle_maximized(&mut self, window: &Window) {
+        self.maximized = !self.maximized;
+        window.set_maximized(self.maximized);
+    }
+
+    /// Fills the rest of a horizontal top panel: dragging it moves the window, a double click maximizes it
+    /// and the buttons on the right minimize, maximize and close. Returns `true` when closing.
+    pub fn title_bar(&mut self, ui: &mut Ui, window: &Window) -> bool {
+        let mut close = false;
+        if !self.enabled {
+            return close;
+        }
+        ui.with_layout(Layout::right_to_left(), |ui| {
+            if ui.button("🗙").on_hover_text("Close").clicked() {
+                close = true;
+            }
+            let maximize = if self.maximized { "Restore" } else { "Maximize" };
+            if ui.button("☐").on_hover_text(maximize).clicked() {
+                self.toggle_maximized(window);
+            }
+            if ui.button("⏷").on_hover_text("Minimize").clicked() {
+                window.set_minimized(true);
+            }
+
+            let response = ui.allocate_response(ui.available_size(), Sense::click_and_drag());
+            if response.drag_started() {
+                self.moving = false;
+            }
+            if response.double_clicked() {
+                self.toggle_maximized(window);
+            } else if response.dragged() && !self.moving && !self.maximized && ui.input().pointer.delta() != Vec2::ZERO {
+                self.moving = true;
+                if let Err(err) = window.drag_window() {
+                    log::warn!("Could not move the window: {}", err);
+                }
+            }
+        });
+        close
+    }
+
+    /// Lets the edges and corners be dragged to resize the window and keeps track of where it is, call every frame
+    pub fn update(&mut self, ctx: &CtxRef, window: &Window) {
+        if !self.enabled || self.maximized {
+            self.resize = None;
+            return;
+        }
+        let size = window.inner_size();
+        // a minimized window has no size, and on Windows it's parked far off screen
+        if size.width == 0 || size.height == 0 {
+            self.resize = None;
+            return;
+        }
+        if let Ok(position) = window.outer_position() {
+            self.geometry = Some(WindowGeometry {
+                position: [position.x, position.y],
+                size: [size.width, size.height],
+                maximized: false,
+            });
+        }
+
+        let screen = ctx.input().screen_rect();
+        for &edge in Edge::ALL.iter() {
+            let rect = edge.rect(screen);
+            let mut response = None;
+            egui::Area::new(edge).fixed_pos(rect.min).order(Order::Foreground).show(ctx, |ui| {
+                response = Some(ui.allocate_response(rect.size(), Sense::drag()));
+            });
+            let response = match response {
+                Some(response) => response,
+                None => continue,
+            };
+            if response.hovered() || response.dragged() {
+                ctx.output().cursor_icon = edge.cursor();
+            }
+            if response.drag_started() {
+                self.resize = match (screen_cursor(ctx, window), window.outer_position()) {
+                    (Some(cursor), Ok(position)) => Some(ResizeDrag {
+                        edge,
+                        cursor,
+                        position,
+                        size: window.inner_size(),
+                    }),
+                    _ => None,
+                };
+            }
+            if response.drag_released() {
+                self.resize = None;
+            }
+        }
+
+        if let (Some(drag), Some(cursor)) = (&self.resize, screen_cursor(ctx, window)) {
+            let (horizontal, vertical) = drag.edge.sides();
+            let resize = |side: i32, start: i32, length: u32, moved: i32, min: i32| {
+                let new_length = (length as i32 + side * moved).max(min);
+                // the left and top sides move the window, the other sides stay where they are
+                let new_start = if side < 0 { start + length as i32 - new_length } else { start };
+                (new_start, new_length as u32)
+            };
+            let (x, width) = resize(horizontal, drag.position.x, drag.size.width, cursor.x - drag.cursor.x, MIN_SIZE[0]);
+            let (y, height) = resize(vertical, drag.position.y, drag.size.height, cursor.y - drag.cursor.y, MIN_SIZE[1]);
+            window.set_inner_size(PhysicalSize::new(width, height));
+            if horizontal < 0 || vertical < 0 {
+                window.set_outer_position(PhysicalPosition::new(x, y));
+            }
+        }
+    }
+}
+
+/// Where the pointer is on the screen in pixels, egui only knows where it is in the window
+fn screen_cursor(ctx: &CtxRef, window: &Window) -> Option<PhysicalPosition<i32>> {
+    let pointer = ctx.input().pointer.interact_pos()?;
+    let window = window.outer_position().ok()?;
+    let scale = ctx.pixels_per_point();
+    Some(PhysicalPosition::new(
+        window.x + (pointer.x * scale).round() as i32,
+        window.y + (pointer.y * scale).round() as i32,
+    ))
+}
@@ -0,0 +1,218 @@
+use crate::config;
+use crate::input::Recording;
+use crate::logging;
+use crate::mesh::{GpuMeshes, MeshLibrary};
+use crate::render::{self, Headless, SceneRenderer};
+use crate::scene::Scene;
+use crate::simulation;
+use std::path::PathBuf;
+
+/// Handles subcommands that run without opening a window.
+/// Returns the exit code, or `None` if the editor should start normally.
+pub fn run(args: &[String]) -> Option<i32> {
+    match args.get(1).map(String::as_str) {
+        Some("replay") => {
+            // warnings of the simulation go to stderr, there's no log file for runs without a window
+            logging::init(None, log::LevelFilter::Trace);
+            Some(replay(&args[2..]))
+        }
+        Some("render") => {
+            logging::init(None, log::LevelFilter::Info);
+            Some(render(&args[2..]))
+        }
+        Some("--help") | Some("-h") => {
+            println!("{}\n{}\n{}", config::USAGE, REPLAY_USAGE, RENDER_USAGE);
+            Some(0)
+        }
+        _ => None,
+    }
+}
+
+const REPLAY_USAGE: &str = "usage: game replay <recording.ron> [--expect <scene.ron>] [--out <scene.ron>]";
+
+/// Plays an input recording back headless and checks or stores the resulting scene
+fn replay(args: &[String]) -> i32 {
+    let mut recording_path = None;
+    let mut expect_path = None;
+    let mut out_path = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = match arg.as_str() {
+            "--expect" | "--out" => match args.next() {
+                Some(value) => value,
+                None => {
+                    eprintln!("{} needs a value\n{}", arg, REPLAY_USAGE);
+                    return 2;
+                }
+            },
+            _ if recording_path.is_none() => {
+                recording_path = Some(arg);
+                continue;
+            }
+            _ => {
+                eprintln!("{}", REPLAY_USAGE);
+                return 2;
+            }
+        };
+        match arg.as_str() {
+            "--expect" => expect_path = Some(value),
+            _ => out_path = Some(value),
+        }
+    }
+
+    let recording_path = match recording_path {
+        Some(path) => path,
+        None => {
+            eprintln!("{}", REPLAY_USAGE);
+            return 2;
+        }
+    };
+
+    let recording = match Recording::load(recording_path) {
+        Ok(recording) => recording,
+        Err(err) => {
+            eprintln!("Could not load recording {}: {}", recording_path, err);
+            return 1;
+        }
+    };
+    let frames = recording.frames;
+    let scene = simulation::replay(recording);
+    println!("Replayed {} frames from {}", frames, recording_path);
+
+    if let Some(out_path) = out_path {
+        if let Err(err) = scene.save(out_path) {
+            eprintln!("Could not save scene {}: {}", out_path, err);
+            return 1;
+        }
+    }
+
+    if let Some(expect_path) = expect_path {
+        let expected = match Scene::load(expect_path) {
+            Ok(scene) => scene,
+            Err(err) => {
+                eprintln!("Could not load scene {}: {}", expect_path, err);
+                return 1;
+            }
+        };
+        if scene != expected {
+            eprintln!("Final scene does not match {}", expect_path);
+            eprintln!("{:#?}", scene);
+            return 1;
+        }
+        println!("Final scene matches {}", expect_path);
+    }
+
+    0
+}
+
+const RENDER_USAGE: &str = "usage: game render <scene.ron> [--camera <node>] [--out <png>] [--size <WxH>]
+  --camera <node>          look out of the node with this name instead of the scene's camera
+  --out <png>              where the picture goes, render.png by default
+  --size <WxH>             size of the picture, 1280x720 by default";
+
+/// Draws a scene without a window and saves it as a PNG
+fn render(args: &[String]) -> i32 {
+    let mut scene_path = None;
+    let mut camera = None;
+    let mut out_path = PathBuf::from("render.png");
+    let mut size = (1280, 720);
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = match arg.as_str() {
+            "--camera" | "--out" | "--size" => match args.next() {
+                Some(value) => value,
+                None => {
+                    eprintln!("{} needs a value\n{}", arg, RENDER_USAGE);
+                    return 2;
+                }
+            },
+            _ if scene_path.is_none() => {
+                scene_path = Some(arg);
+                continue;
+            }
+            _ => {
+                eprintln!("{}", RENDER_USAGE);
+                return 2;
+            }
+        };
+        match arg.as_str() {
+            "--camera" => camera = Some(value),
+            "--out" => out_path = PathBuf::from(value),
+            _ => match config::parse_resolution(value) {
+                Ok(resolution) => size = resolution,
+                Err(err) => {
+                    eprintln!("{}", err);
+                    return 2;
+                }
+            },
+        }
+    }
+
+    let scene_path = match scene_path {
+        Some(path) => path,
+        None => {
+            eprintln!("{}", RENDER_USAGE);
+            return 2;
+        }
+    };
+    let scene = match Scene::load(scene_path) {
+        Ok(scene) => scene,
+        Err(err) => {
+            eprintln!("Could not load scene {}: {}", scene_path, err);
+            return 1;
+        }
+    };
+    let view = match camera {
+        Some(name) => match scene.nodes.iter().find(|node| &node.name == name) {
+            Some(node) => render::node_view(&scene, node),
+            None => {
+                eprintln!("There is no node called {} in {}", name, scene_path);
+                return 1;
+            }
+        },
+        None => render::scene_view(&scene),
+    };
+
+    let headless = match Headless::new() {
+        Ok(headless) => headless,
+        Err(err) => {
+            eprintln!("{}", err);
+            return 1;
+        }
+    };
+    let mut meshes = MeshLibrary::new();
+    meshes.track(&scene);
+    meshes.wait();
+    meshes.update();
+    for err in meshes.take_errors() {
+        eprintln!("Could not load {}", err);
+    }
+
+    let mut gpu_meshes = GpuMeshes::default();
+    let rendered = gpu_meshes
+        .sync(&headless, &meshes)
+        .and_then(|()| SceneRenderer::new(&headless))
+        .and_then(|mut renderer| renderer.draw_offscreen(&headless, &scene, &gpu_meshes, view, size.0, size.1))
+        .and_then(|(image, stats)| render::save_png(&image, &out_path).map(|()| stats));
+    match rendered {
+        Ok(stats) => {
+            println!(
+                "Rendered {} at {}x{} to {}, {} nodes drawn with {} triangles and {} out of view",
+                scene_path,
+                size.0,
+                size.1,
+                out_path.display(),
+                stats.drawn,
+                stats.triangles,
+                stats.culled
+            );
+            0
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            1
+        }
+    }
+}
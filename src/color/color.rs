@@ -8,8 +8,6 @@ pub struct Color {
 
 /// in hex: #27292d
 pub const BACKGROUND_COLOR: Color = Color::from_rgb(39, 41, 45);
-/// in hex: #9ba4b5
-pub const FOREGROUND_COLOR: Color = Color::from_rgb(155, 164, 181);
 
 impl Color {
     pub const fn from_rgb(red: i16, green: i16, blue: i16) -> Self {
@@ -0,0 +1,182 @@
+use crate::config::{Config, FullscreenMode, Mode, CONFIG_FILE};
+use log::LevelFilter;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "usage: game [options]
+  --config <file>          settings to use instead of config.toml
+  --resolution <WxH>       window size, e.g. 1920x1080
+  --fullscreen <mode>      windowed, borderless or exclusive
+  --vsync <on|off>
+  --msaa <samples>         0 turns anti-aliasing off
+  --scene <file>           scene to open
+  --log-level <level>      off, error, warn, info, debug or trace
+  --editor, --runtime      start with or without the editor";
+
+/// Settings given on the command line, they win over the config file for this run but aren't saved
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Overrides {
+    pub config: Option<PathBuf>,
+    pub resolution: Option<(u32, u32)>,
+    pub fullscreen: Option<FullscreenMode>,
+    pub vsync: Option<bool>,
+    pub msaa: Option<u16>,
+    pub scene: Option<PathBuf>,
+    pub log_level: Option<LevelFilter>,
+    pub mode: Option<Mode>,
+}
+
+impl Overrides {
+    /// Reads the options after the program name
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut overrides = Self::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+            match arg.as_str() {
+                "--config" => overrides.config = Some(PathBuf::from(value()?)),
+                "--resolution" => overrides.resolution = Some(parse_resolution(value()?)?),
+                "--fullscreen" => {
+                    let text = value()?;
+                    let mode = FullscreenMode::ALL.iter().copied().find(|mode| mode.name() == text);
+                    overrides.fullscreen = Some(mode.ok_or_else(|| format!("unknown fullscreen mode {}", text))?);
+                }
+                "--vsync" => {
+                    overrides.vsync = Some(match value()?.as_str() {
+                        "on" => true,
+                        "off" => false,
+                        text => return Err(format!("expected on or off, got {}", text)),
+                    })
+                }
+                "--msaa" => {
+                    let text = value()?;
+                    overrides.msaa = Some(text.parse().map_err(|_| format!("expected a number of samples, got {}", text))?);
+                }
+                "--scene" => overrides.scene = Some(PathBuf::from(value()?)),
+                "--log-level" => {
+                    let text = value()?;
+                    overrides.log_level = Some(text.parse().map_err(|_| format!("unknown log level {}", text))?);
+                }
+                "--editor" => overrides.mode = Some(Mode::Editor),
+                "--runtime" => overrides.mode = Some(Mode::Runtime),
+                _ => return Err(format!("unknown option {}", arg)),
+            }
+        }
+        Ok(overrides)
+    }
+
+    pub fn config_path(&self) -> PathBuf {
+        self.config.clone().unwrap_or_else(|| PathBuf::from(CONFIG_FILE))
+    }
+
+    pub fn apply(&self, config: &mut Config) {
+        if let Some((width, height)) = self.resolution {
+            config.window.width = width;
+            config.window.height = height;
+        }
+        if let Some(fullscreen) = self.fullscreen {
+            config.window.fullscreen = fullscreen;
+        }
+        if let Some(vsync) = self.vsync {
+            config.graphics.vsync = vsync;
+        }
+        if let Some(msaa) = self.msaa {
+            config.graphics.msaa = msaa;
+        }
+        if let Some(scene) = &self.scene {
+            config.startup.scene = Some(scene.clone());
+        }
+        if let Some(log_level) = self.log_level {
+            config.startup.log_level = log_level;
+        }
+        if let Some(mode) = self.mode {
+            config.startup.mode = mode;
+        }
+    }
+}
+
+/// `1920x1080` as width and height
+pub fn parse_resolution(text: &str) -> Result<(u32, u32), String> {
+    let error = || format!("expected a resolution like 1920x1080, got {}", text);
+    let (width, height) = text.split_once('x').ok_or_else(error)?;
+    let width: u32 = width.parse().map_err(|_| error())?;
+    let height: u32 = height.parse().map_err(|_| error())?;
+    if width == 0 || height == 0 {
+        return Err(error());
+    }
+    Ok((width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Overrides, String> {
+        let args: Vec<String> = args.split_whitespace().map(str::to_string).collect();
+        Overrides::parse(&args)
+    }
+
+    #[test]
+    fn resolutions() {
+        assert_eq!(parse_resolution("1920x1080"), Ok((1920, 1080)));
+        assert_eq!(parse_resolution("1x1"), Ok((1, 1)));
+        for text in ["0x0", "0x1080", "1920x0", "1920", "1920x", "x1080", "1920*1080", "-1x5", "axb", ""] {
+            assert!(parse_resolution(text).is_err(), "{} should be rejected", text);
+        }
+    }
+
+    #[test]
+    fn every_option() {
+        let overrides = parse(
+            "--config other.toml --resolution 800x600 --fullscreen borderless --vsync off --msaa 4 \
+             --scene level.scene.ron --log-level debug --runtime",
+        )
+        .unwrap();
+        assert_eq!(overrides, Overrides {
+            config: Some(PathBuf::from("other.toml")),
+            resolution: Some((800, 600)),
+            fullscreen: Some(FullscreenMode::Borderless),
+            vsync: Some(false),
+            msaa: Some(4),
+            scene: Some(PathBuf::from("level.scene.ron")),
+            log_level: Some(LevelFilter::Debug),
+            mode: Some(Mode::Runtime),
+        });
+        assert_eq!(parse(""), Ok(Overrides::default()));
+        // the last one wins
+        assert_eq!(parse("--runtime --editor").unwrap().mode, Some(Mode::Editor));
+    }
+
+    #[test]
+    fn missing_values() {
+        for option in ["--config", "--resolution", "--fullscreen", "--vsync", "--msaa", "--scene", "--log-level"] {
+            assert_eq!(parse(option), Err(format!("{} needs a value", option)));
+        }
+    }
+
+    #[test]
+    fn bad_values() {
+        for args in [
+            "--resolution 0x0",
+            "--resolution big",
+            "--fullscreen sometimes",
+            "--vsync yes",
+            "--msaa -1",
+            "--msaa many",
+            "--log-level loud",
+            "--verbose",
+            "scene.ron",
+        ] {
+            assert!(parse(args).is_err(), "{} should be rejected", args);
+        }
+    }
+
+    #[test]
+    fn overrides_win_over_the_config() {
+        let mut config = Config::default();
+        config.graphics.msaa = 8;
+        parse("--resolution 640x480 --vsync on").unwrap().apply(&mut config);
+        assert_eq!((config.window.width, config.window.height), (640, 480));
+        assert!(config.graphics.vsync);
+        assert_eq!(config.graphics.msaa, 8);
+    }
+}
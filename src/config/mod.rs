@@ -0,0 +1,7 @@
+mod args;
+mod settings;
+mod window;
+
+pub use args::*;
+pub use settings::*;
+pub use window::*;
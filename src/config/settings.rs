@@ -0,0 +1,184 @@
+use crate::error::Error;
+use glium::glutin;
+use log::LevelFilter;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where the settings are read from unless `--config` says otherwise
+pub const CONFIG_FILE: &str = "config.toml";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FullscreenMode {
+    Windowed,
+    /// a window covering the whole monitor
+    Borderless,
+    /// takes over the monitor with the video mode closest to the resolution
+    Exclusive,
+}
+
+impl FullscreenMode {
+    pub const ALL: [FullscreenMode; 3] = [FullscreenMode::Windowed, FullscreenMode::Borderless, FullscreenMode::Exclusive];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FullscreenMode::Windowed => "windowed",
+            FullscreenMode::Borderless => "borderless",
+            FullscreenMode::Exclusive => "exclusive",
+        }
+    }
+}
+
+/// What the program starts as
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    Editor,
+    /// only the game, without the editor's panels and windows
+    Runtime,
+}
+
+impl Mode {
+    pub fn name(self) -> &'static str {
+        match self {
+            Mode::Editor => "editor",
+            Mode::Runtime => "runtime",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowConfig {
+    pub title: String,
+    /// size of the window's contents in pixels, or of the video mode in exclusive fullscreen
+    pub width: u32,
+    pub height: u32,
+    pub fullscreen: FullscreenMode,
+    /// the system's title bar and borders instead of the editor's own
+    pub decorations: bool,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            title: "Game".to_string(),
+            width: 1280,
+            height: 720,
+            fullscreen: FullscreenMode::Windowed,
+            decorations: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GraphicsConfig {
+    pub vsync: bool,
+    /// samples per pixel for anti-aliasing, 0 turns it off
+    pub msaa: u16,
+    pub srgb: bool,
+    pub hardware_acceleration: bool,
+}
+
+impl Default for GraphicsConfig {
+    fn default() -> Self {
+        Self {
+            vsync: true,
+            msaa: 0,
+            srgb: true,
+            hardware_acceleration: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StartupConfig {
+    pub mode: Mode,
+    /// scene file opened at the start, an empty scene if there is none
+    pub scene: Option<PathBuf>,
+    /// records below this aren't logged at all
+    pub log_level: LevelFilter,
+}
+
+impl Default for StartupConfig {
+    fn default() -> Self {
+        Self {
+            mode: Mode::Editor,
+            scene: None,
+            log_level: LevelFilter::Trace,
+        }
+    }
+}
+
+/// Settings of the engine, kept in a TOML file.
+/// Missing entries fall back to the defaults, so an empty file is fine.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub window: WindowConfig,
+    pub graphics: GraphicsConfig,
+    pub startup: StartupConfig,
+}
+
+impl Config {
+    /// The defaults if there's no file at `path`
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = fs::read_to_string(path).map_err(|err| Error::file(path, err))?;
+        toml::from_str(&text).map_err(|err| Error::file(path, err))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let text = toml::to_string_pretty(self).map_err(|err| Error::file(path, err))?;
+        fs::write(path, text).map_err(|err| Error::file(path, err))
+    }
+
+    pub fn window_builder<T>(&self, event_loop: &glutin::event_loop::EventLoop<T>) -> glutin::window::WindowBuilder {
+        let window = &self.window;
+        let size = glutin::dpi::PhysicalSize::new(window.width, window.height);
+        let fullscreen = match window.fullscreen {
+            FullscreenMode::Windowed => None,
+            FullscreenMode::Borderless => Some(glutin::window::Fullscreen::Borderless(None)),
+            FullscreenMode::Exclusive => {
+                let closest = event_loop.primary_monitor().and_then(|monitor| {
+                    monitor.video_modes().min_by_key(|mode| {
+                        let (width, height) = (mode.size().width as i64, mode.size().height as i64);
+                        ((width - size.width as i64).abs() + (height - size.height as i64).abs(), -(mode.refresh_rate() as i64))
+                    })
+                });
+                match closest {
+                    Some(mode) => Some(glutin::window::Fullscreen::Exclusive(mode)),
+                    None => {
+                        log::warn!("No video mode for exclusive fullscreen, using borderless instead");
+                        Some(glutin::window::Fullscreen::Borderless(None))
+                    }
+                }
+            }
+        };
+        glutin::window::WindowBuilder::new()
+            .with_title(&window.title)
+            .with_inner_size(size)
+            .with_fullscreen(fullscreen)
+            .with_decorations(window.decorations)
+    }
+
+    pub fn context_builder(&self) -> glutin::ContextBuilder<'static, glutin::NotCurrent> {
+        let graphics = &self.graphics;
+        let builder = glutin::ContextBuilder::new()
+            .with_hardware_acceleration(Some(graphics.hardware_acceleration))
+            .with_vsync(graphics.vsync)
+            .with_srgb(graphics.srgb);
+        if graphics.msaa > 0 {
+            builder.with_multisampling(graphics.msaa)
+        } else {
+            builder
+        }
+    }
+}
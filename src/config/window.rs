@@ -0,0 +1,159 @@
+use crate::config::{Config, FullscreenMode, Mode};
+use crate::toasts::Toasts;
+use egui::{ComboBox, CtxRef, DragValue, Grid};
+use log::LevelFilter;
+use std::path::PathBuf;
+
+const LOG_LEVELS: [LevelFilter; 6] = [
+    LevelFilter::Off,
+    LevelFilter::Error,
+    LevelFilter::Warn,
+    LevelFilter::Info,
+    LevelFilter::Debug,
+    LevelFilter::Trace,
+];
+
+const MSAA_SAMPLES: [u16; 4] = [0, 2, 4, 8];
+
+/// Edits the config file. Most settings only take effect on the next start.
+pub struct SettingsWindow {
+    pub open: bool,
+    path: PathBuf,
+    /// as it's being edited
+    config: Config,
+    /// as it is in the file
+    saved: Config,
+}
+
+impl SettingsWindow {
+    /// `config` is the one read from `path`, without the command line options
+    pub fn new(path: PathBuf, config: Config) -> Self {
+        Self {
+            open: false,
+            path,
+            saved: config.clone(),
+            config,
+        }
+    }
+
+    pub fn show(&mut self, ctx: &CtxRef, toasts: &mut Toasts) {
+        let mut open = self.open;
+        egui::Window::new("Settings").open(&mut open).show(ctx, |ui| {
+            let config = &mut self.config;
+            ui.heading("Window");
+            Grid::new("settings_window").show(ui, |ui| {
+                ui.label("Title");
+                ui.text_edit_singleline(&mut config.window.title);
+                ui.end_row();
+
+                ui.label("Resolution");
+                ui.horizontal(|ui| {
+                    ui.add(DragValue::new(&mut config.window.width).clamp_range(1..=16384));
+                    ui.label("x");
+                    ui.add(DragValue::new(&mut config.window.height).clamp_range(1..=16384));
+                });
+                ui.end_row();
+
+                ui.label("Fullscreen");
+                ComboBox::from_id_source("settings_fullscreen")
+                    .selected_text(config.window.fullscreen.name())
+                    .show_ui(ui, |ui| {
+                        for &mode in FullscreenMode::ALL.iter() {
+                            ui.selectable_value(&mut config.window.fullscreen, mode, mode.name());
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("Decorations");
+                ui.checkbox(&mut config.window.decorations, "system title bar");
+                ui.end_row();
+            });
+
+            ui.heading("Graphics");
+            Grid::new("settings_graphics").show(ui, |ui| {
+                ui.label("Vsync");
+                ui.checkbox(&mut config.graphics.vsync, "");
+                ui.end_row();
+
+                ui.label("MSAA");
+                let msaa = match config.graphics.msaa {
+                    0 => "off".to_string(),
+                    samples => format!("{}x", samples),
+                };
+                ComboBox::from_id_source("settings_msaa").selected_text(msaa).show_ui(ui, |ui| {
+                    for &samples in MSAA_SAMPLES.iter() {
+                        let text = if samples == 0 { "off".to_string() } else { format!("{}x", samples) };
+                        ui.selectable_value(&mut config.graphics.msaa, samples, text);
+                    }
+                });
+                ui.end_row();
+
+                ui.label("sRGB");
+                ui.checkbox(&mut config.graphics.srgb, "");
+                ui.end_row();
+
+                ui.label("Hardware acceleration");
+                ui.checkbox(&mut config.graphics.hardware_acceleration, "");
+                ui.end_row();
+            });
+
+            ui.heading("Startup");
+            Grid::new("settings_startup").show(ui, |ui| {
+                ui.label("Mode");
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut config.startup.mode, Mode::Editor, Mode::Editor.name());
+                    ui.radio_value(&mut config.startup.mode, Mode::Runtime, Mode::Runtime.name());
+                });
+                ui.end_row();
+
+                ui.label("Scene");
+                ui.horizontal(|ui| {
+                    let name = match &config.startup.scene {
+                        Some(path) => path.display().to_string(),
+                        None => "empty".to_string(),
+                    };
+                    ui.label(name);
+                    if ui.button("Browse…").clicked() {
+                        if let Some(path) = tinyfiledialogs::open_file_dialog("Start scene", "", None) {
+                            config.startup.scene = Some(PathBuf::from(path));
+                        }
+                    }
+                    if config.startup.scene.is_some() && ui.button("Clear").clicked() {
+                        config.startup.scene = None;
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Log level");
+                ComboBox::from_id_source("settings_log_level")
+                    .selected_text(config.startup.log_level.as_str())
+                    .show_ui(ui, |ui| {
+                        for &level in LOG_LEVELS.iter() {
+                            ui.selectable_value(&mut config.startup.log_level, level, level.as_str());
+                        }
+                    });
+                ui.end_row();
+            });
+
+            ui.separator();
+            ui.label("Changes take effect on the next start, options on the command line win over them.");
+            ui.horizontal(|ui| {
+                let changed = self.config != self.saved;
+                if ui.add(egui::Button::new("Save").enabled(changed)).clicked() {
+                    match self.config.save(&self.path) {
+                        Ok(()) => {
+                            // the only one that can change right away
+                            log::set_max_level(self.config.startup.log_level);
+                            self.saved = self.config.clone();
+                        }
+                        Err(err) => toasts.error(err),
+                    }
+                }
+                if ui.add(egui::Button::new("Revert").enabled(changed)).clicked() {
+                    self.config = self.saved.clone();
+                }
+            });
+        });
+        self.open = open;
+    }
+}
@@ -0,0 +1,5 @@
+mod registry;
+mod window;
+
+pub use registry::*;
+pub use window::*;
@@ -0,0 +1,209 @@
+use crate::history::History;
+use crate::scene::Scene;
+use crate::selection::Selection;
+use crate::simulation::Simulation;
+use glam::f32::Vec3;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// What commands get to work on
+pub struct Context<'a> {
+    pub scene: &'a mut Scene,
+    pub history: &'a mut History,
+    pub selection: &'a mut Selection,
+    pub simulation: &'a mut Simulation,
+}
+
+/// Runs a command with its arguments, returns what to print
+pub type CommandFn = Box<dyn Fn(&mut Context, &[&str]) -> Result<String, String>>;
+
+/// Value of a variable, it keeps its type when set from the console
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Float(f32),
+}
+
+impl Value {
+    /// Parses `text` as the same type as `self`
+    fn parse_like(self, text: &str) -> Result<Value, String> {
+        match self {
+            Value::Bool(_) => match text {
+                "1" | "true" | "on" => Ok(Value::Bool(true)),
+                "0" | "false" | "off" => Ok(Value::Bool(false)),
+                _ => Err(format!("expected 0 or 1, got {}", text)),
+            },
+            Value::Float(_) => text
+                .parse()
+                .map(Value::Float)
+                .map_err(|_| format!("expected a number, got {}", text)),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Bool(value) => write!(f, "{}", *value as u8),
+            Value::Float(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+enum Kind {
+    Command(CommandFn),
+    Var(Value),
+}
+
+struct Entry {
+    help: &'static str,
+    kind: Kind,
+}
+
+/// Commands and variables the subsystems offer to the console, by name.
+/// Typing a variable's name shows it, adding a value sets it.
+#[derive(Default)]
+pub struct Registry {
+    entries: BTreeMap<String, Entry>,
+}
+
+impl Registry {
+    pub fn command(
+        &mut self,
+        name: &str,
+        help: &'static str,
+        run: impl Fn(&mut Context, &[&str]) -> Result<String, String> + 'static,
+    ) {
+        self.entries.insert(name.to_string(), Entry {
+            help,
+            kind: Kind::Command(Box::new(run)),
+        });
+    }
+
+    pub fn var(&mut self, name: &str, help: &'static str, value: Value) {
+        self.entries.insert(name.to_string(), Entry { help, kind: Kind::Var(value) });
+    }
+
+    pub fn get(&self, name: &str) -> Option<Value> {
+        match self.entries.get(name)?.kind {
+            Kind::Var(value) => Some(value),
+            Kind::Command(_) => None,
+        }
+    }
+
+    /// `false` for variables that don't exist or aren't on/off
+    pub fn bool(&self, name: &str) -> bool {
+        matches!(self.get(name), Some(Value::Bool(true)))
+    }
+
+    /// `None` for variables that don't exist or aren't numbers
+    pub fn float(&self, name: &str) -> Option<f32> {
+        match self.get(name)? {
+            Value::Float(value) => Some(value),
+            Value::Bool(_) => None,
+        }
+    }
+
+    /// Sets a variable from the text typed for it
+    pub fn set(&mut self, name: &str, text: &str) -> Result<(), String> {
+        match self.entries.get_mut(name).map(|entry| &mut entry.kind) {
+            Some(Kind::Var(value)) => {
+                *value = value.parse_like(text)?;
+                Ok(())
+            }
+            _ => Err(format!("{} is not a variable", name)),
+        }
+    }
+
+    /// Names starting with `prefix`, sorted
+    pub fn complete<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = &'a str> {
+        self.entries.keys().map(String::as_str).filter(move |name| name.starts_with(prefix))
+    }
+
+    /// Every name with its help text, sorted
+    pub fn help(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries.iter().map(|(name, entry)| (name.as_str(), entry.help))
+    }
+
+    /// Runs a command or shows or sets a variable, `args` are the words after the name
+    pub fn execute(&mut self, context: &mut Context, name: &str, args: &[&str]) -> Result<String, String> {
+        let entry = match self.entries.get(name) {
+            Some(entry) => entry,
+            None => return Err(format!("unknown command {}", name)),
+        };
+        match (&entry.kind, args) {
+            (Kind::Command(run), args) => run(context, args),
+            (Kind::Var(value), []) => Ok(format!("{} is {}", name, value)),
+            (Kind::Var(_), [text]) => {
+                self.set(name, text)?;
+                Ok(String::new())
+            }
+            (Kind::Var(_), _) => Err(format!("usage: {} <value>", name)),
+        }
+    }
+}
+
+/// Reads `x y z` from the arguments of a command
+pub fn parse_vec3(args: &[&str]) -> Result<Vec3, String> {
+    match args {
+        [x, y, z] => {
+            let parse = |text: &str| text.parse::<f32>().map_err(|_| format!("expected a number, got {}", text));
+            Ok(Vec3::new(parse(x)?, parse(y)?, parse(z)?))
+        }
+        _ => Err("expected x y z".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_keep_their_type() {
+        let flag = Value::Bool(false);
+        for text in ["1", "true", "on"] {
+            assert_eq!(flag.parse_like(text), Ok(Value::Bool(true)));
+        }
+        for text in ["0", "false", "off"] {
+            assert_eq!(flag.parse_like(text), Ok(Value::Bool(false)));
+        }
+        assert!(flag.parse_like("2").is_err());
+        assert!(flag.parse_like("").is_err());
+
+        let number = Value::Float(1.0);
+        assert_eq!(number.parse_like("0.5"), Ok(Value::Float(0.5)));
+        assert_eq!(number.parse_like("-3"), Ok(Value::Float(-3.0)));
+        assert!(number.parse_like("on").is_err());
+        assert!(number.parse_like("").is_err());
+    }
+
+    #[test]
+    fn set_checks_the_type() {
+        let mut registry = Registry::default();
+        registry.var("wireframe", "", Value::Bool(false));
+        registry.var("timescale", "", Value::Float(1.0));
+        registry.command("step", "", |_, _| Ok(String::new()));
+
+        registry.set("wireframe", "on").unwrap();
+        assert!(registry.bool("wireframe"));
+        registry.set("timescale", "0.25").unwrap();
+        assert_eq!(registry.float("timescale"), Some(0.25));
+
+        assert!(registry.set("timescale", "fast").is_err());
+        assert_eq!(registry.float("timescale"), Some(0.25));
+        assert!(registry.set("step", "1").is_err());
+        assert!(registry.set("missing", "1").is_err());
+        assert_eq!(registry.float("wireframe"), None);
+    }
+
+    #[test]
+    fn complete_lists_names_in_order() {
+        let mut registry = Registry::default();
+        for name in ["step", "stress", "spawn", "timescale"] {
+            registry.command(name, "", |_, _| Ok(String::new()));
+        }
+        assert_eq!(registry.complete("st").collect::<Vec<_>>(), ["step", "stress"]);
+        assert_eq!(registry.complete("").count(), 4);
+        assert_eq!(registry.complete("x").count(), 0);
+    }
+}
@@ -0,0 +1,356 @@
+use crate::console::{Context, Registry};
+use egui::{Color32, Id, Key, TextStyle};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+
+/// Run when the editor starts, if it's there
+pub const STARTUP_FILE: &str = "autoexec.cfg";
+
+/// Oldest output lines are dropped beyond this
+const MAX_OUTPUT: usize = 500;
+
+/// Files running `exec` on themselves would never end
+const MAX_EXEC_DEPTH: usize = 8;
+
+/// Handled by the console itself rather than the registry
+const BUILTINS: [(&str, &str); 3] = [
+    ("clear", "clears the console"),
+    ("exec", "exec <file>: runs the lines of a file as commands, # starts a comment"),
+    ("help", "help [name]: lists all commands and variables, or explains one"),
+];
+
+enum Line {
+    Input(String),
+    Output(String),
+    Error(String),
+}
+
+/// In-game console, toggled with the key left of 1, running commands of the `Registry`
+#[derive(Default)]
+pub struct Console {
+    pub registry: Registry,
+    pub open: bool,
+    input: String,
+    output: VecDeque<Line>,
+    /// lines that were entered, oldest first
+    history: Vec<String>,
+    /// entry of `history` shown while going through it with the arrow keys
+    history_position: Option<usize>,
+    /// the input should get the keyboard next frame
+    focus: bool,
+}
+
+impl Console {
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.focus = self.open;
+    }
+
+    /// Runs one line as typed into the console
+    pub fn execute(&mut self, context: &mut Context, line: &str) {
+        self.execute_nested(context, line, 0);
+    }
+
+    /// Runs every line of the file at `path`
+    pub fn exec_file<P: AsRef<Path>>(&mut self, context: &mut Context, path: P) {
+        self.exec_file_nested(context, path.as_ref(), 0);
+    }
+
+    fn execute_nested(&mut self, context: &mut Context, line: &str, depth: usize) {
+        let words = split(line);
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        let (name, args) = match words.split_first() {
+            Some((name, args)) => (*name, args),
+            None => return,
+        };
+        match (name, args) {
+            ("clear", _) => self.output.clear(),
+            ("exec", [path]) if depth < MAX_EXEC_DEPTH => self.exec_file_nested(context, Path::new(path), depth + 1),
+            ("exec", [_]) => self.print(Line::Error("exec is nested too deep".to_string())),
+            ("exec", _) => self.print(Line::Error("usage: exec <file>".to_string())),
+            ("help", []) => {
+                let mut lines: Vec<(&str, &str)> = BUILTINS.to_vec();
+                lines.extend(self.registry.help());
+                lines.sort_unstable();
+                let lines: Vec<String> = lines.iter().map(|(name, help)| format!("{}  {}", name, help)).collect();
+                for line in lines {
+                    self.print(Line::Output(line));
+                }
+            }
+            ("help", [name]) => {
+                let help = BUILTINS
+                    .iter()
+                    .copied()
+                    .chain(self.registry.help())
+                    .find(|(other, _)| other == name)
+                    .map(|(_, help)| help.to_string());
+                match help {
+                    Some(help) => self.print(Line::Output(help)),
+                    None => self.print(Line::Error(format!("unknown command {}", name))),
+                }
+            }
+            _ => match self.registry.execute(context, name, args) {
+                Ok(output) if output.is_empty() => {}
+                Ok(output) => self.print(Line::Output(output)),
+                Err(error) => self.print(Line::Error(error)),
+            },
+        }
+    }
+
+    fn exec_file_nested(&mut self, context: &mut Context, path: &Path, depth: usize) {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) => {
+                self.print(Line::Error(format!("Could not read {}: {}", path.display(), err)));
+                return;
+            }
+        };
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default();
+            self.execute_nested(context, line, depth);
+        }
+    }
+
+    fn print(&mut self, line: Line) {
+        if self.output.len() == MAX_OUTPUT {
+            self.output.pop_front();
+        }
+        self.output.push_back(line);
+    }
+
+    pub fn show(&mut self, ctx: &egui::CtxRef, context: &mut Context) {
+        if !self.open {
+            return;
+        }
+        // the key that opened the console types itself into the input as well
+        self.input.retain(|c| c != '`');
+
+        let mut open = self.open;
+        let mut close = false;
+        egui::Window::new("Console")
+            .open(&mut open)
+            .default_size(egui::vec2(500.0, 250.0))
+            .show(ctx, |ui| {
+                let input_height = ui.fonts()[TextStyle::Monospace].row_height() + 16.0;
+                egui::ScrollArea::from_max_height(ui.available_size().y - input_height).show(ui, |ui| {
+                    for line in &self.output {
+                        let (text, color) = match line {
+                            Line::Input(text) => (format!("> {}", text), ui.visuals().weak_text_color()),
+                            Line::Output(text) => (text.clone(), ui.visuals().text_color()),
+                            Line::Error(text) => (text.clone(), Color32::from_rgb(230, 90, 80)),
+                        };
+                        ui.add(egui::Label::new(text).text_style(TextStyle::Monospace).text_color(color));
+                    }
+                    ui.scroll_to_cursor(egui::Align::BOTTOM);
+                });
+
+                let id = Id::new("console_input");
+                let had_focus = ui.memory().has_focus(id);
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.input)
+                        .id(id)
+                        .text_style(TextStyle::Monospace)
+                        .desired_width(f32::INFINITY)
+                        .lock_focus(true),
+                );
+                if self.focus {
+                    response.request_focus();
+                    self.focus = false;
+                }
+                // the toggle key only reaches the console as text while its input has focus
+                if self.input.contains('`') {
+                    self.input.retain(|c| c != '`');
+                    close = had_focus;
+                }
+
+                let (enter, tab, up, down) = {
+                    let input = ui.input();
+                    (
+                        input.key_pressed(Key::Enter),
+                        input.key_pressed(Key::Tab),
+                        input.key_pressed(Key::ArrowUp),
+                        input.key_pressed(Key::ArrowDown),
+                    )
+                };
+                if response.lost_focus() && enter {
+                    let line = std::mem::take(&mut self.input);
+                    if !line.trim().is_empty() {
+                        self.print(Line::Input(line.clone()));
+                        if self.history.last() != Some(&line) {
+                            self.history.push(line.clone());
+                        }
+                        self.execute(context, &line);
+                    }
+                    self.history_position = None;
+                    self.focus = true;
+                } else if response.has_focus() && tab {
+                    self.complete();
+                    // puts the cursor after the completion
+                    ui.memory().id_data.remove(&id);
+                } else if response.has_focus() && (up || down) {
+                    self.browse_history(up);
+                    ui.memory().id_data.remove(&id);
+                }
+            });
+        self.open = open && !close;
+    }
+
+    /// Completes the name being typed as far as all matches agree, lists them if there are several
+    fn complete(&mut self) {
+        if self.input.contains(' ') {
+            return;
+        }
+        let mut matches: Vec<&str> = BUILTINS
+            .iter()
+            .map(|(name, _)| *name)
+            .chain(self.registry.complete(&self.input))
+            .filter(|name| name.starts_with(self.input.as_str()))
+            .collect();
+        matches.sort_unstable();
+        let common = match matches.split_first() {
+            Some((first, rest)) => rest.iter().fold(first.to_string(), |common, name| {
+                common.chars().zip(name.chars()).take_while(|(a, b)| a == b).map(|(a, _)| a).collect()
+            }),
+            None => return,
+        };
+        let lines: Vec<String> = if matches.len() > 1 {
+            matches.iter().map(|name| name.to_string()).collect()
+        } else {
+            Vec::new()
+        };
+
+        self.input = if lines.is_empty() { format!("{} ", common) } else { common };
+        for line in lines {
+            self.print(Line::Output(line));
+        }
+    }
+
+    /// Steps to an older line with `older`, or to a newer one and finally an empty input
+    fn browse_history(&mut self, older: bool) {
+        if self.history.is_empty() {
+            return;
+        }
+        self.history_position = match (self.history_position, older) {
+            (None, true) => Some(self.history.len() - 1),
+            (None, false) => None,
+            (Some(position), true) => Some(position.saturating_sub(1)),
+            (Some(position), false) if position + 1 < self.history.len() => Some(position + 1),
+            (Some(_), false) => None,
+        };
+        self.input = match self.history_position {
+            Some(position) => self.history[position].clone(),
+            None => String::new(),
+        };
+    }
+}
+
+/// Words of a command line, double quotes keep spaces in one word
+fn split(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quoted = false;
+    let mut started = false;
+    for c in line.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                started = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if started {
+                    words.push(std::mem::take(&mut word));
+                    started = false;
+                }
+            }
+            c => {
+                word.push(c);
+                started = true;
+            }
+        }
+    }
+    if started {
+        words.push(word);
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::console::Value;
+
+    fn outputs(console: &Console) -> Vec<&str> {
+        console
+            .output
+            .iter()
+            .filter_map(|line| match line {
+                Line::Output(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn split_words_and_quotes() {
+        assert_eq!(split("spawn teapot 1 2 3"), ["spawn", "teapot", "1", "2", "3"]);
+        assert_eq!(split("  step \t 4  "), ["step", "4"]);
+        assert_eq!(split(r#"exec "my scripts/start.cfg""#), ["exec", "my scripts/start.cfg"]);
+        assert_eq!(split(r#"name """#), ["name", ""]);
+        assert_eq!(split(r#"a"b c"d"#), ["ab cd"]);
+        assert!(split("   ").is_empty());
+    }
+
+    #[test]
+    fn complete_as_far_as_the_matches_agree() {
+        let mut console = Console::default();
+        console.registry.var("timescale", "", Value::Float(1.0));
+        console.registry.command("step", "", |_, _| Ok(String::new()));
+        console.registry.command("stress", "", |_, _| Ok(String::new()));
+
+        console.input = "ti".to_string();
+        console.complete();
+        assert_eq!(console.input, "timescale ");
+        assert!(outputs(&console).is_empty());
+
+        console.input = "s".to_string();
+        console.complete();
+        assert_eq!(console.input, "st");
+        assert_eq!(outputs(&console), ["step", "stress"]);
+
+        // builtins complete as well
+        console.input = "ex".to_string();
+        console.complete();
+        assert_eq!(console.input, "exec ");
+
+        // only the name is completed, not its arguments
+        console.input = "step 1".to_string();
+        console.complete();
+        assert_eq!(console.input, "step 1");
+
+        console.input = "zzz".to_string();
+        console.complete();
+        assert_eq!(console.input, "zzz");
+    }
+
+    #[test]
+    fn browse_history_back_and_forth() {
+        let mut console = Console::default();
+        console.browse_history(true);
+        assert_eq!(console.input, "");
+
+        console.history = vec!["first".to_string(), "second".to_string()];
+        console.browse_history(true);
+        assert_eq!(console.input, "second");
+        console.browse_history(true);
+        assert_eq!(console.input, "first");
+        // stays at the oldest
+        console.browse_history(true);
+        assert_eq!(console.input, "first");
+        console.browse_history(false);
+        assert_eq!(console.input, "second");
+        console.browse_history(false);
+        assert_eq!(console.input, "");
+        assert_eq!(console.history_position, None);
+    }
+}
@@ -0,0 +1,326 @@
+use crate::input::{Action, ActionMap, InputState};
+use crate::inspect::{self, inspect_enum, inspect_fields};
+use crate::mesh::MeshLibrary;
+use crate::physics::{self, WorldShape};
+use crate::scene::{Scene, Transform};
+use glam::f32::{Quat, Vec3};
+use serde::{Deserialize, Serialize};
+
+/// How many times per step overlaps are pushed out, corners need more than one
+const RESOLVE_ITERATIONS: usize = 4;
+/// How far below the feet ground still counts as being stood on
+const GROUND_PROBE: f32 = 0.05;
+/// Steepest `max_slope` in degrees, at 90 walls would count as ground
+const MAX_SLOPE: f32 = 89.0;
+/// Pushing out of ground this steep or steeper goes no further than for this slope, instead of off towards infinity
+const MIN_GROUND_NORMAL_Y: f32 = 0.1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CameraMode {
+    /// the editor camera stays in control
+    None,
+    FirstPerson,
+    /// `CharacterController::camera_distance` behind the character
+    ThirdPerson,
+}
+
+/// Walks a node around with the movement actions, standing on and sliding along colliders.
+/// The node's location is at the character's feet, it faces along its Z axis turned flat.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CharacterController {
+    pub walk_speed: f32,
+    /// speed while `Action::Sprint` is held
+    pub sprint_speed: f32,
+    /// upwards speed at the start of a jump
+    pub jump_speed: f32,
+    /// steepest slope in degrees that can be walked up, anything steeper is a wall
+    pub max_slope: f32,
+    /// highest ledge that is walked onto without jumping
+    pub step_height: f32,
+    /// the character is a capsule standing on the node's location
+    pub radius: f32,
+    pub height: f32,
+    pub camera: CameraMode,
+    pub eye_height: f32,
+    pub camera_distance: f32,
+
+    pub velocity: Vec3,
+    pub grounded: bool,
+    /// whether jump was already held last step, so holding it jumps only once
+    jump_held: bool,
+}
+
+impl Default for CharacterController {
+    fn default() -> Self {
+        Self {
+            walk_speed: 2.0,
+            sprint_speed: 4.0,
+            jump_speed: 4.5,
+            max_slope: 45.0,
+            step_height: 0.3,
+            radius: 0.3,
+            height: 1.8,
+            camera: CameraMode::None,
+            eye_height: 1.6,
+            camera_distance: 3.0,
+            velocity: Vec3::ZERO,
+            grounded: false,
+            jump_held: false,
+        }
+    }
+}
+
+inspect_enum!(CameraMode { None, FirstPerson, ThirdPerson });
+
+inspect_fields!(CharacterController {
+    walk_speed: inspect::non_negative,
+    sprint_speed: inspect::non_negative,
+    jump_speed: inspect::non_negative,
+    max_slope: slope,
+    step_height: inspect::non_negative,
+    radius: inspect::positive,
+    height: inspect::positive,
+    camera,
+    eye_height,
+    camera_distance: inspect::positive,
+    grounded: inspect::read_only,
+});
+
+/// `max_slope` from flat up to `MAX_SLOPE`
+fn slope(ui: &mut egui::Ui, value: &mut f32) -> bool {
+    ui.add(egui::DragValue::new(value).speed(1.0).suffix("°").clamp_range(0.0..=MAX_SLOPE)).changed()
+}
+
+impl CharacterController {
+    /// The capsule when the feet are at `location`
+    pub fn shape(&self, location: Vec3) -> WorldShape {
+        let radius = self.radius.min(self.height * 0.5);
+        WorldShape::Capsule {
+            a: location + Vec3::Y * radius,
+            b: location + Vec3::Y * (self.height - radius),
+            radius,
+        }
+    }
+
+    /// Horizontal direction the character looks at when it has `rotation`
+    pub fn forward(rotation: Quat) -> Vec3 {
+        let flat = |direction: Vec3| Vec3::new(direction.x, 0.0, direction.z).try_normalize();
+        let forward = rotation * Vec3::Z;
+        // tipped over to face straight up or down, the way it faced before is where the feet or head point
+        flat(forward).or_else(|| flat(rotation * Vec3::Y * -forward.y.signum())).unwrap_or(Vec3::Z)
+    }
+
+    /// Eye position and view direction of the camera following the character, if it has one
+    pub fn camera(&self, location: Vec3, rotation: Quat) -> Option<(Vec3, Vec3)> {
+        let forward = Self::forward(rotation);
+        let eye = location + Vec3::Y * self.eye_height;
+        match self.camera {
+            CameraMode::None => None,
+            CameraMode::FirstPerson => Some((eye, forward)),
+            CameraMode::ThirdPerson => {
+                let direction = (forward - Vec3::Y * 0.3).normalize();
+                Some((eye - direction * self.camera_distance, direction))
+            }
+        }
+    }
+
+    fn is_walkable(&self, normal: Vec3) -> bool {
+        // scene files can hold any angle, not only what the inspector allows
+        normal.y >= self.max_slope.clamp(0.0, MAX_SLOPE).to_radians().cos()
+    }
+}
+
+/// Moves every node with a `CharacterController` by one step.
+/// Characters collide in world space, also when they're below a moved or rotated parent.
+pub fn update(scene: &mut Scene, actions: &ActionMap, input: &InputState, meshes: &MeshLibrary, dt: f32) {
+    let gravity = scene.gravity;
+    // placing mesh colliders is expensive, so it's done once and only the characters are moved along
+    let mut colliders = physics::world_shapes(scene, meshes);
+
+    for index in 0..scene.nodes.len() {
+        let mut controller = match &scene.nodes[index].controller {
+            Some(controller) => controller.clone(),
+            None => continue,
+        };
+        let own = colliders.iter().position(|collider| collider.node == index);
+        let obstacles: Vec<&WorldShape> = colliders
+            .iter()
+            .filter(|collider| collider.node != index && !collider.trigger)
+            .filter(|collider| !matches!(own, Some(own) if !colliders[own].interacts_with(collider)))
+            .map(|collider| &collider.shape)
+            .collect();
+        let parent = scene.parent_transform(&scene.nodes[index]);
+        let transform = parent.child_to_world(&scene.nodes[index].transform);
+
+        let value = |action| actions.value(action, input);
+        let forward = CharacterController::forward(transform.rotation);
+        let right = Vec3::new(forward.z, 0.0, -forward.x);
+        let mut wish = forward * (value(Action::MoveForward) - value(Action::MoveBackward))
+            + right * (value(Action::MoveRight) - value(Action::MoveLeft));
+        if wish.length_squared() > 1.0 {
+            wish = wish.normalize();
+        }
+        let speed = if value(Action::Sprint) >= 0.5 {
+            controller.sprint_speed
+        } else {
+            controller.walk_speed
+        };
+        controller.velocity.x = wish.x * speed;
+        controller.velocity.z = wish.z * speed;
+
+        let jump = value(Action::Jump) >= 0.5;
+        if controller.grounded && jump && !controller.jump_held {
+            controller.velocity.y = controller.jump_speed;
+            controller.grounded = false;
+        } else if !controller.grounded {
+            controller.velocity += gravity * dt;
+        } else {
+            controller.velocity.y = 0.0;
+        }
+        controller.jump_held = jump;
+
+        let start = transform.location;
+        let mut location = start + controller.velocity * dt;
+        // only walk up ledges, jumping characters don't climb them
+        let can_step = controller.grounded && controller.velocity.y <= 0.0;
+        let mut hit = resolve(&controller, &mut location, &obstacles, can_step);
+
+        if hit.ceiling && controller.velocity.y > 0.0 {
+            controller.velocity.y = 0.0;
+        }
+        if hit.blocked {
+            // slide along the wall instead of running into it
+            let moved = (location - start) / dt;
+            controller.velocity.x = moved.x;
+            controller.velocity.z = moved.z;
+        }
+
+        if !hit.grounded && controller.velocity.y <= 0.0 {
+            let mut probe = location - Vec3::Y * GROUND_PROBE;
+            if resolve(&controller, &mut probe, &obstacles, false).grounded {
+                // keep walking down slopes instead of bouncing off them
+                location = probe;
+                hit.grounded = true;
+            }
+        }
+        if hit.grounded && controller.velocity.y < 0.0 {
+            controller.velocity.y = 0.0;
+        }
+        controller.grounded = hit.grounded;
+
+        let node = &mut scene.nodes[index];
+        node.transform.location = parent.world_to_child(&Transform { location, ..transform }).location;
+        node.controller = Some(controller);
+        if let Some(own) = own {
+            if let Some(moved) = physics::placed_collider(scene, meshes, index) {
+                colliders[own] = moved;
+            }
+        }
+    }
+}
+
+/// What the character touched while being pushed out of the obstacles
+#[derive(Default)]
+struct Hit {
+    /// a wall or a slope that is too steep
+    blocked: bool,
+    /// walkable ground below
+    grounded: bool,
+    ceiling: bool,
+}
+
+/// Pushes the character at `location` out of all `obstacles`.
+/// With `can_step` obstacles lower than `step_height` are stepped onto instead.
+fn resolve(controller: &CharacterController, location: &mut Vec3, obstacles: &[&WorldShape], can_step: bool) -> Hit {
+    let mut hit = Hit::default();
+
+    for _ in 0..RESOLVE_ITERATIONS {
+        let shape = controller.shape(*location);
+        let mut touched = false;
+        for obstacle in obstacles {
+            let (normal, depth) = match physics::collide(obstacle, &shape) {
+                Some(contact) => contact,
+                None => continue,
+            };
+            touched = true;
+
+            // the rounded bottom of the capsule makes edges look like steep slopes,
+            // so ledges are recognized by their height instead
+            let top = obstacle.bounds().max.y;
+            let ledge = top > location.y && top - location.y <= controller.step_height;
+
+            if controller.is_walkable(normal) {
+                hit.grounded = true;
+                // stand on slopes without sliding down, push straight up
+                *location += Vec3::Y * (depth / normal.y.max(MIN_GROUND_NORMAL_Y));
+            } else if can_step && ledge && normal.y > 0.0 {
+                hit.grounded = true;
+                location.y = top;
+            } else {
+                hit.blocked = true;
+                // walls only push sideways so steep slopes can't be climbed
+                let sideways = Vec3::new(normal.x, 0.0, normal.z);
+                if sideways.length_squared() > f32::EPSILON {
+                    let sideways = sideways.normalize();
+                    *location += sideways * (depth / sideways.dot(normal).max(0.1));
+                } else {
+                    *location += normal * depth;
+                }
+                if normal.y < -0.5 {
+                    hit.ceiling = true;
+                }
+            }
+        }
+        if !touched {
+            break;
+        }
+    }
+
+    hit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::{Collider, Shape};
+    use crate::scene::Node;
+
+    #[test]
+    fn walls_are_never_ground() {
+        let controller = CharacterController {
+            max_slope: 120.0,
+            ..CharacterController::default()
+        };
+        assert!(controller.is_walkable(Vec3::Y));
+        assert!(!controller.is_walkable(Vec3::X));
+        assert!(!controller.is_walkable(Vec3::new(1.0, -0.5, 0.0).normalize()));
+    }
+
+    #[test]
+    fn characters_below_a_parent_collide_where_they_are() {
+        let mut scene = Scene {
+            nodes: Vec::new(),
+            ..Scene::default()
+        };
+        let mut platform = Node::new("platform");
+        platform.transform.location = Vec3::new(5.0, -0.5, 0.0);
+        platform.collider = Some(Collider::new(Shape::Box { half_extents: Vec3::splat(0.5) }));
+        scene.add(platform);
+        let mut parent = Node::new("parent");
+        parent.transform.location = Vec3::new(5.0, 0.0, 0.0);
+        let parent = scene.add(parent);
+        let mut character = Node::new("character");
+        character.parent = Some(parent);
+        character.controller = Some(CharacterController::default());
+        let character = scene.add(character);
+
+        let meshes = MeshLibrary::new();
+        for _ in 0..10 {
+            update(&mut scene, &ActionMap::default(), &InputState::default(), &meshes, 1.0 / 60.0);
+        }
+        // standing on the platform below its parent, not falling past it as if it were at the origin
+        let node = scene.node(character).unwrap();
+        assert!(node.controller.as_ref().unwrap().grounded);
+        assert!(node.transform.location.length() < 0.01, "{}", node.transform.location);
+    }
+}
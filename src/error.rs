@@ -0,0 +1,59 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Something that went wrong in the engine, with what it was doing at the time.
+/// Only a window that can't be opened or shaders that don't compile end the editor,
+/// everything else is shown to the user and the editor carries on.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// the window or its OpenGL context couldn't be created
+    Window(String),
+    /// there's no OpenGL context to render into without a window
+    Headless(String),
+    Shader { name: &'static str, error: String },
+    /// creating a buffer or texture, drawing or showing a frame failed
+    Render { what: String, error: String },
+    /// a file couldn't be read or written, e.g. an asset or a recording
+    File { path: PathBuf, error: String },
+    /// the controllers or other input devices can't be reached
+    #[cfg_attr(not(feature = "gamepad"), allow(dead_code))]
+    Input(String),
+}
+
+impl Error {
+    pub fn file(path: &Path, error: impl fmt::Display) -> Self {
+        Error::File {
+            path: path.to_path_buf(),
+            error: error.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Window(error) => write!(f, "Could not open the window: {}", error),
+            Error::Headless(error) => write!(f, "Could not render without a window: {}", error),
+            Error::Shader { name, error } => write!(f, "The {} shader does not compile: {}", name, error),
+            Error::Render { what, error } => write!(f, "Error while {}: {}", what, error),
+            Error::File { path, error } => write!(f, "{}: {}", path.display(), error),
+            Error::Input(error) => write!(f, "Input unavailable: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Says what was being rendered when a glium call fails
+pub trait RenderContext<T> {
+    fn rendering(self, what: &str) -> Result<T, Error>;
+}
+
+impl<T, E: fmt::Display> RenderContext<T> for Result<T, E> {
+    fn rendering(self, what: &str) -> Result<T, Error> {
+        self.map_err(|error| Error::Render {
+            what: what.to_string(),
+            error: error.to_string(),
+        })
+    }
+}
@@ -0,0 +1,414 @@
+use crate::math::{Ray, Viewport};
+use crate::scene::Transform;
+use glam::f32::{Quat, Vec2, Vec3};
+
+/// How close the cursor has to be to a handle to grab it, in pixels
+const GRAB_DISTANCE: f32 = 8.0;
+/// Length of the axes as a share of the distance to the camera, so the gizmo keeps its size on screen
+const SCREEN_SIZE: f32 = 0.15;
+const RING_SEGMENTS: usize = 48;
+/// How far the cursor has to move to the right to double the size with the uniform scale handle
+const UNIFORM_SCALE_PIXELS: f32 = 100.0;
+
+const AXIS_COLORS: [[f32; 4]; 3] = [[1.0, 0.2, 0.2, 1.0], [0.2, 1.0, 0.2, 1.0], [0.3, 0.4, 1.0, 1.0]];
+const UNIFORM_COLOR: [f32; 4] = [0.9, 0.9, 0.9, 1.0];
+const ACTIVE_COLOR: [f32; 4] = [1.0, 0.9, 0.1, 1.0];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GizmoMode {
+    Translate,
+    Rotate,
+    Scale,
+}
+
+/// Whether the handles follow the world axes or the rotation of the node
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GizmoSpace {
+    World,
+    Local,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Handle {
+    /// moves along, rotates around or scales along one axis
+    Axis(usize),
+    /// moves within the plane this axis is the normal of
+    Plane(usize),
+    /// scales along all axes at once
+    Uniform,
+}
+
+/// A colored line in world space
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Line {
+    pub from: Vec3,
+    pub to: Vec3,
+    pub color: [f32; 4],
+}
+
+/// Outline of a handle, used for drawing it and for finding it under the cursor
+struct Outline {
+    handle: Handle,
+    points: Vec<Vec3>,
+    closed: bool,
+    /// grabbed anywhere inside, not only on the outline
+    filled: bool,
+}
+
+struct Drag {
+    handle: Handle,
+    /// the transform before the drag started
+    start: Transform,
+    axes: [Vec3; 3],
+    cursor: Vec2,
+    /// where the handle was grabbed in the world
+    grab: Vec3,
+}
+
+/// Handles drawn over the selected node to move, rotate and scale it with the mouse
+pub struct Gizmo {
+    pub mode: GizmoMode,
+    pub space: GizmoSpace,
+    pub snap: bool,
+    /// distance moves snap to
+    pub translate_step: f32,
+    /// angle rotations snap to, in degrees
+    pub rotate_step: f32,
+    /// scale factors snap to multiples of this
+    pub scale_step: f32,
+    hovered: Option<Handle>,
+    drag: Option<Drag>,
+}
+
+impl Default for Gizmo {
+    fn default() -> Self {
+        Self {
+            mode: GizmoMode::Translate,
+            space: GizmoSpace::World,
+            snap: false,
+            translate_step: 0.25,
+            rotate_step: 15.0,
+            scale_step: 0.1,
+            hovered: None,
+            drag: None,
+        }
+    }
+}
+
+impl Gizmo {
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// Highlights the handle under `cursor`
+    pub fn hover(&mut self, transform: &Transform, viewport: &Viewport, cursor: Vec2) {
+        if self.drag.is_none() {
+            self.hovered = self.handle_at(transform, viewport, cursor);
+        }
+    }
+
+    /// Grabs the handle under `cursor`, returns whether there was one
+    pub fn begin(&mut self, transform: &Transform, viewport: &Viewport, cursor: Vec2) -> bool {
+        let handle = match self.handle_at(transform, viewport, cursor) {
+            Some(handle) => handle,
+            None => return false,
+        };
+        let axes = self.axes(transform);
+        let center = transform.location;
+        let ray = viewport.ray(cursor);
+
+        let grab = match (self.mode, handle) {
+            (GizmoMode::Scale, Handle::Uniform) => Some(center),
+            (GizmoMode::Rotate, Handle::Axis(axis)) | (_, Handle::Plane(axis)) => plane_hit(center, axes[axis], &ray),
+            (_, Handle::Axis(axis)) => line_param(center, axes[axis], &ray).map(|param| center + axes[axis] * param),
+            (_, Handle::Uniform) => None,
+        };
+        match grab {
+            Some(grab) => {
+                self.drag = Some(Drag {
+                    handle,
+                    start: *transform,
+                    axes,
+                    cursor,
+                    grab,
+                });
+                self.hovered = Some(handle);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves the grabbed handle to `cursor`, changing `transform` to follow it
+    pub fn drag(&self, transform: &mut Transform, viewport: &Viewport, cursor: Vec2) {
+        let drag = match &self.drag {
+            Some(drag) => drag,
+            None => return,
+        };
+        let start = &drag.start;
+        let center = start.location;
+        let ray = viewport.ray(cursor);
+
+        match (self.mode, drag.handle) {
+            (GizmoMode::Translate, Handle::Axis(axis)) => {
+                let direction = drag.axes[axis];
+                if let Some(param) = line_param(center, direction, &ray) {
+                    let moved = param - (drag.grab - center).dot(direction);
+                    transform.location = center + direction * self.snapped(moved, self.translate_step);
+                }
+            }
+            (GizmoMode::Translate, Handle::Plane(normal)) => {
+                if let Some(hit) = plane_hit(center, drag.axes[normal], &ray) {
+                    let moved = hit - drag.grab;
+                    let mut location = center;
+                    for axis in other_axes(normal) {
+                        let direction = drag.axes[axis];
+                        location += direction * self.snapped(moved.dot(direction), self.translate_step);
+                    }
+                    transform.location = location;
+                }
+            }
+            (GizmoMode::Rotate, Handle::Axis(axis)) => {
+                let direction = drag.axes[axis];
+                if let Some(hit) = plane_hit(center, direction, &ray) {
+                    let from = drag.grab - center;
+                    let to = hit - center;
+                    let angle = direction.dot(from.cross(to)).atan2(from.dot(to));
+                    let angle = self.snapped(angle.to_degrees(), self.rotate_step).to_radians();
+                    // rotating around the axis in world space works for local axes as well
+                    transform.rotation = (Quat::from_axis_angle(direction, angle) * start.rotation).normalize();
+                }
+            }
+            (GizmoMode::Scale, Handle::Axis(axis)) => {
+                let direction = drag.axes[axis];
+                let grabbed = (drag.grab - center).dot(direction);
+                if let Some(param) = line_param(center, direction, &ray) {
+                    if grabbed.abs() > f32::EPSILON {
+                        transform.size[axis] = start.size[axis] * self.snapped_factor(param / grabbed);
+                    }
+                }
+            }
+            (GizmoMode::Scale, Handle::Uniform) => {
+                let factor = 1.0 + (cursor.x - drag.cursor.x) / UNIFORM_SCALE_PIXELS;
+                transform.size = start.size * self.snapped_factor(factor.max(0.0));
+            }
+            _ => {}
+        }
+    }
+
+    pub fn end(&mut self) {
+        self.drag = None;
+    }
+
+    /// Lines to draw the gizmo of a node at `transform` with
+    pub fn lines(&self, transform: &Transform, viewport: &Viewport) -> Vec<Line> {
+        let active = match &self.drag {
+            Some(drag) => Some(drag.handle),
+            None => self.hovered,
+        };
+
+        let mut lines = Vec::new();
+        for outline in self.outlines(transform, viewport) {
+            let color = match outline.handle {
+                handle if Some(handle) == active => ACTIVE_COLOR,
+                Handle::Axis(axis) | Handle::Plane(axis) => AXIS_COLORS[axis],
+                Handle::Uniform => UNIFORM_COLOR,
+            };
+            for (from, to) in segments(&outline.points, outline.closed) {
+                lines.push(Line { from, to, color });
+            }
+        }
+        lines
+    }
+
+    /// Directions of the handles. Scaling always follows the world axes
+    /// since the size is applied after the rotation.
+    fn axes(&self, transform: &Transform) -> [Vec3; 3] {
+        match (self.mode, self.space) {
+            (GizmoMode::Scale, _) | (_, GizmoSpace::World) => [Vec3::X, Vec3::Y, Vec3::Z],
+            (_, GizmoSpace::Local) => {
+                let rotation = transform.rotation;
+                [rotation * Vec3::X, rotation * Vec3::Y, rotation * Vec3::Z]
+            }
+        }
+    }
+
+    fn outlines(&self, transform: &Transform, viewport: &Viewport) -> Vec<Outline> {
+        let center = transform.location;
+        let size = (viewport.eye() - center).length() * SCREEN_SIZE;
+        // while dragging the handles stay where they were grabbed
+        let axes = match &self.drag {
+            Some(drag) => drag.axes,
+            None => self.axes(transform),
+        };
+
+        let mut outlines = Vec::new();
+        match self.mode {
+            GizmoMode::Translate => {
+                for (axis, &direction) in axes.iter().enumerate() {
+                    outlines.push(Outline {
+                        handle: Handle::Axis(axis),
+                        points: vec![center, center + direction * size],
+                        closed: false,
+                        filled: false,
+                    });
+                }
+                for normal in 0..3 {
+                    let [u, v] = other_axes(normal);
+                    let corner = |a: f32, b: f32| center + (axes[u] * a + axes[v] * b) * size;
+                    outlines.push(Outline {
+                        handle: Handle::Plane(normal),
+                        points: vec![corner(0.25, 0.25), corner(0.45, 0.25), corner(0.45, 0.45), corner(0.25, 0.45)],
+                        closed: true,
+                        filled: true,
+                    });
+                }
+            }
+            GizmoMode::Rotate => {
+                for axis in 0..3 {
+                    let [u, v] = other_axes(axis);
+                    let points = (0..RING_SEGMENTS)
+                        .map(|segment| {
+                            let angle = segment as f32 / RING_SEGMENTS as f32 * std::f32::consts::TAU;
+                            center + (axes[u] * angle.cos() + axes[v] * angle.sin()) * size
+                        })
+                        .collect();
+                    outlines.push(Outline {
+                        handle: Handle::Axis(axis),
+                        points,
+                        closed: true,
+                        filled: false,
+                    });
+                }
+            }
+            GizmoMode::Scale => {
+                // faces the camera so it can always be grabbed, comes first to win over the axes at the center
+                let camera = viewport.view.inverse();
+                let right = camera.x_axis.truncate() * size * 0.1;
+                let up = camera.y_axis.truncate() * size * 0.1;
+                outlines.push(Outline {
+                    handle: Handle::Uniform,
+                    points: vec![center - right - up, center + right - up, center + right + up, center - right + up],
+                    closed: true,
+                    filled: true,
+                });
+
+                for (axis, &direction) in axes.iter().enumerate() {
+                    let [u, v] = other_axes(axis);
+                    let end = center + direction * size;
+                    let corner = |a: f32, b: f32| end + (axes[u] * a + axes[v] * b) * size * 0.05;
+                    outlines.push(Outline {
+                        handle: Handle::Axis(axis),
+                        points: vec![center, end],
+                        closed: false,
+                        filled: false,
+                    });
+                    outlines.push(Outline {
+                        handle: Handle::Axis(axis),
+                        points: vec![corner(-1.0, -1.0), corner(1.0, -1.0), corner(1.0, 1.0), corner(-1.0, 1.0)],
+                        closed: true,
+                        filled: true,
+                    });
+                }
+            }
+        }
+        outlines
+    }
+
+    /// The handle closest to `cursor`, if any is close enough
+    fn handle_at(&self, transform: &Transform, viewport: &Viewport, cursor: Vec2) -> Option<Handle> {
+        let mut closest: Option<(Handle, f32)> = None;
+        for outline in self.outlines(transform, viewport) {
+            let points: Option<Vec<Vec2>> = outline.points.iter().map(|&point| viewport.project(point)).collect();
+            let points = match points {
+                Some(points) => points,
+                None => continue,
+            };
+
+            let mut distance = segments(&points, outline.closed)
+                .map(|(from, to)| distance_to_segment(cursor, from, to))
+                .fold(f32::INFINITY, f32::min);
+            if outline.filled && inside_polygon(cursor, &points) {
+                distance = 0.0;
+            }
+
+            let closer = match closest {
+                Some((_, best)) => distance < best,
+                None => true,
+            };
+            if distance <= GRAB_DISTANCE && closer {
+                closest = Some((outline.handle, distance));
+            }
+        }
+        closest.map(|(handle, _)| handle)
+    }
+
+    fn snapped(&self, value: f32, step: f32) -> f32 {
+        if self.snap && step > 0.0 {
+            (value / step).round() * step
+        } else {
+            value
+        }
+    }
+
+    fn snapped_factor(&self, factor: f32) -> f32 {
+        1.0 + self.snapped(factor - 1.0, self.scale_step)
+    }
+}
+
+/// The two axes that aren't `axis`
+fn other_axes(axis: usize) -> [usize; 2] {
+    [(axis + 1) % 3, (axis + 2) % 3]
+}
+
+/// Pairs of neighbouring points, with `closed` the last one connects back to the first
+fn segments<T: Copy>(points: &[T], closed: bool) -> impl Iterator<Item = (T, T)> + '_ {
+    let count = if closed { points.len() } else { points.len().saturating_sub(1) };
+    (0..count).map(move |index| (points[index], points[(index + 1) % points.len()]))
+}
+
+/// Where on the line through `origin` along `direction` the ray passes closest,
+/// as multiple of `direction`. `None` if the two are parallel.
+fn line_param(origin: Vec3, direction: Vec3, ray: &Ray) -> Option<f32> {
+    let offset = origin - ray.origin;
+    let a = direction.dot(direction);
+    let b = direction.dot(ray.direction);
+    let c = ray.direction.dot(ray.direction);
+    let denominator = a * c - b * b;
+    if denominator <= a * c * 1e-6 {
+        return None;
+    }
+    Some((b * ray.direction.dot(offset) - c * direction.dot(offset)) / denominator)
+}
+
+/// Where the ray hits the plane through `point`, `None` if it runs parallel or away from it
+fn plane_hit(point: Vec3, normal: Vec3, ray: &Ray) -> Option<Vec3> {
+    let speed = ray.direction.dot(normal);
+    if speed.abs() <= ray.direction.length() * 1e-6 {
+        return None;
+    }
+    let distance = (point - ray.origin).dot(normal) / speed;
+    if distance < 0.0 {
+        return None;
+    }
+    Some(ray.origin + ray.direction * distance)
+}
+
+fn distance_to_segment(point: Vec2, from: Vec2, to: Vec2) -> f32 {
+    let along = to - from;
+    let length_squared = along.length_squared();
+    let t = if length_squared > f32::EPSILON {
+        ((point - from).dot(along) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    point.distance(from + along * t)
+}
+
+/// Whether `point` is inside the convex polygon, no matter which way it is wound
+fn inside_polygon(point: Vec2, polygon: &[Vec2]) -> bool {
+    let sides: Vec<f32> = segments(polygon, true)
+        .map(|(from, to)| (to - from).perp_dot(point - from))
+        .collect();
+    sides.iter().all(|&side| side >= 0.0) || sides.iter().all(|&side| side <= 0.0)
+}
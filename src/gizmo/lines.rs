@@ -0,0 +1,93 @@
+use crate::error::{Error, RenderContext};
+use crate::gizmo::Line;
+use crate::math::Viewport;
+use glium::backend::Facade;
+use glium::{implement_vertex, uniform, Surface};
+
+#[derive(Copy, Clone)]
+struct LineVertex {
+    position: [f32; 3],
+    color: [f32; 4],
+}
+
+implement_vertex!(LineVertex, position, color);
+
+const VERTEX_SHADER: &str = r#"
+    #version 330
+
+    in vec3 position;
+    in vec4 color;
+
+    out vec4 v_color;
+
+    uniform mat4 perspective;
+    uniform mat4 view;
+
+    void main() {
+        v_color = color;
+        gl_Position = perspective * view * vec4(position, 1.0);
+    }
+"#;
+
+const FRAGMENT_SHADER: &str = r#"
+    #version 330
+
+    in vec4 v_color;
+    out vec4 color;
+
+    void main() {
+        color = v_color;
+    }
+"#;
+
+/// Draws colored lines on top of the scene, used for the gizmos
+pub struct LineRenderer {
+    program: glium::Program,
+}
+
+impl LineRenderer {
+    pub fn new<F: Facade>(facade: &F) -> Result<Self, Error> {
+        let program =
+            glium::Program::from_source(facade, VERTEX_SHADER, FRAGMENT_SHADER, None).map_err(|err| Error::Shader {
+                name: "line",
+                error: err.to_string(),
+            })?;
+        Ok(Self { program })
+    }
+
+    pub fn draw<F: Facade, S: Surface>(
+        &self,
+        facade: &F,
+        target: &mut S,
+        lines: &[Line],
+        viewport: &Viewport,
+    ) -> Result<(), Error> {
+        if lines.is_empty() {
+            return Ok(());
+        }
+        let mut vertices = Vec::with_capacity(lines.len() * 2);
+        for line in lines {
+            vertices.push(LineVertex { position: line.from.to_array(), color: line.color });
+            vertices.push(LineVertex { position: line.to.to_array(), color: line.color });
+        }
+        let vertices = glium::VertexBuffer::new(facade, &vertices).rendering("uploading the gizmo lines")?;
+
+        // no depth test, the handles have to stay visible inside the node
+        let params = glium::DrawParameters {
+            line_width: Some(2.0),
+            ..Default::default()
+        };
+        target
+            .draw(
+                &vertices,
+                glium::index::NoIndices(glium::index::PrimitiveType::LinesList),
+                &self.program,
+                &uniform! {
+                    perspective: viewport.perspective.to_cols_array_2d(),
+                    view: viewport.view.to_cols_array_2d(),
+                },
+                &params,
+            )
+            .rendering("drawing the gizmo lines")
+    }
+}
@@ -0,0 +1,5 @@
+mod handles;
+mod lines;
+
+pub use handles::*;
+pub use lines::*;
@@ -0,0 +1,537 @@
+use crate::console::Registry;
+use crate::prefab;
+use crate::scene::{Node, NodeId, Scene};
+use std::collections::{HashMap, HashSet};
+
+/// Oldest entries are forgotten beyond this
+const MAX_ENTRIES: usize = 200;
+
+/// An edit of the scene that can be undone and done again
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    /// properties of a node changed, e.g. its transform, color or mesh.
+    /// Only those that differ between `before` and `after` are put back, the rest stay as the simulation left them.
+    Change { id: NodeId, before: Box<Node>, after: Box<Node> },
+    /// `index` is where the node goes, the node is found by its id when it's taken out again
+    Add { index: usize, node: Box<Node> },
+    /// `index` is where the node goes back to, it's found by its id when it's removed
+    Remove { index: usize, node: Box<Node> },
+    /// several commands that are undone together, e.g. deleting a node with its children
+    Group(Vec<Command>),
+}
+
+impl Command {
+    fn apply(&self, scene: &mut Scene) {
+        match self {
+            Command::Change { id, before, after } => {
+                if let Some(node) = scene.node_mut(*id) {
+                    restore(node, before, after);
+                }
+            }
+            Command::Add { index, node } => insert(scene, *index, node),
+            Command::Remove { node, .. } => remove(scene, node.id),
+            Command::Group(commands) => match batch(commands) {
+                Some((Batch::Add, nodes)) => insert_all(scene, nodes),
+                Some((Batch::Remove, nodes)) => remove_all(scene, nodes),
+                None => {
+                    for command in commands {
+                        command.apply(scene);
+                    }
+                }
+            },
+        }
+    }
+
+    fn revert(&self, scene: &mut Scene) {
+        match self {
+            Command::Change { id, before, after } => {
+                if let Some(node) = scene.node_mut(*id) {
+                    restore(node, after, before);
+                }
+            }
+            Command::Add { node, .. } => remove(scene, node.id),
+            Command::Remove { index, node } => insert(scene, *index, node),
+            Command::Group(commands) => match batch(commands) {
+                Some((Batch::Add, nodes)) => remove_all(scene, nodes),
+                Some((Batch::Remove, nodes)) => insert_all(scene, nodes),
+                None => {
+                    for command in commands.iter().rev() {
+                        command.revert(scene);
+                    }
+                }
+            },
+        }
+    }
+}
+
+/// Copies the listed fields from `to` where they differ from `from`
+macro_rules! restore_fields {
+    ($target:expr, $from:expr, $to:expr, [$($field:ident),* $(,)?]) => {{
+        $(
+            if $from.$field != $to.$field {
+                $target.$field = $to.$field.clone();
+            }
+        )*
+    }};
+}
+
+/// Changes the properties of `node` that differ between `from` and `to` to their values in `to`.
+/// Velocities and the like that weren't part of the edit are kept, the simulation went on since.
+fn restore(node: &mut Node, from: &Node, to: &Node) {
+    restore_fields!(node, from, to, [name, parent, visible, locked, color, mesh, collider, script, light, prefab]);
+    restore_fields!(node.transform, from.transform, to.transform, [location, rotation, size]);
+    match (&mut node.body, &from.body, &to.body) {
+        (Some(body), Some(from), Some(to)) => {
+            restore_fields!(body, from, to, [kind, mass, velocity, restitution, friction, gravity_scale]);
+        }
+        (body, from, to) if from != to => *body = to.clone(),
+        _ => {}
+    }
+    match (&mut node.controller, &from.controller, &to.controller) {
+        (Some(controller), Some(from), Some(to)) => restore_fields!(controller, from, to, [
+            walk_speed,
+            sprint_speed,
+            jump_speed,
+            max_slope,
+            step_height,
+            radius,
+            height,
+            camera,
+            eye_height,
+            camera_distance,
+            velocity,
+            grounded,
+        ]),
+        (controller, from, to) if from != to => *controller = to.clone(),
+        _ => {}
+    }
+}
+
+/// Puts `node` at `index`, or at the end if the scene got shorter in the meantime
+fn insert(scene: &mut Scene, index: usize, node: &Node) {
+    let index = index.min(scene.nodes.len());
+    scene.nodes.insert(index, node.clone());
+}
+
+fn remove(scene: &mut Scene, id: NodeId) {
+    if let Some(index) = scene.index_of(id) {
+        scene.nodes.remove(index);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Batch {
+    Add,
+    Remove,
+}
+
+/// The nodes of a group that only adds or only removes nodes, with where they go.
+/// Thousands of them at once are put in or taken out in one pass instead of one by one.
+fn batch(commands: &[Command]) -> Option<(Batch, Vec<(usize, &Node)>)> {
+    let kind = match commands.first()? {
+        Command::Add { .. } => Batch::Add,
+        Command::Remove { .. } => Batch::Remove,
+        _ => return None,
+    };
+    commands
+        .iter()
+        .map(|command| match (kind, command) {
+            (Batch::Add, Command::Add { index, node }) | (Batch::Remove, Command::Remove { index, node }) => {
+                Some((*index, node.as_ref()))
+            }
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()
+        .map(|nodes| (kind, nodes))
+}
+
+/// Same as inserting the nodes one after another from the lowest index up
+fn insert_all(scene: &mut Scene, mut nodes: Vec<(usize, &Node)>) {
+    nodes.sort_by_key(|&(index, _)| index);
+    let mut old = std::mem::take(&mut scene.nodes).into_iter();
+    for (index, node) in nodes {
+        while scene.nodes.len() < index {
+            match old.next() {
+                Some(next) => scene.nodes.push(next),
+                None => break,
+            }
+        }
+        scene.nodes.push(node.clone());
+    }
+    scene.nodes.extend(old);
+}
+
+fn remove_all(scene: &mut Scene, nodes: Vec<(usize, &Node)>) {
+    let ids: HashSet<NodeId> = nodes.iter().map(|(_, node)| node.id).collect();
+    scene.nodes.retain(|node| !ids.contains(&node.id));
+}
+
+struct Entry {
+    label: String,
+    command: Command,
+    /// further changes of the same kind are still merged into this entry
+    open: bool,
+}
+
+/// Edits of the scene in the order they were made, for undo and redo
+#[derive(Default)]
+pub struct History {
+    entries: Vec<Entry>,
+    /// number of entries that are applied, the ones after it can be redone
+    position: usize,
+}
+
+impl History {
+    /// Records `command`, which has already been applied to the scene.
+    /// Anything that could be redone is dropped.
+    pub fn push(&mut self, label: &str, command: Command) {
+        self.entries.truncate(self.position);
+
+        if let Some(last) = self.entries.last_mut() {
+            if last.open && last.label == label {
+                if let (Command::Change { id, after, .. }, Command::Change { id: new_id, after: new_after, .. }) =
+                    (&mut last.command, &command)
+                {
+                    if id == new_id {
+                        *after = new_after.clone();
+                        return;
+                    }
+                }
+            }
+            last.open = false;
+        }
+
+        self.entries.push(Entry {
+            label: label.to_string(),
+            command,
+            open: true,
+        });
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+        self.position = self.entries.len();
+    }
+
+    /// Records that a node changed from `before` to `after`, if it did.
+    /// Consecutive changes of the same property are merged until `seal` is called.
+    /// Properties changed on a prefab instance become overrides of it.
+    pub fn record_change(&mut self, before: &Node, after: &mut Node) {
+        prefab::mark_overrides(before, after);
+        if let Some(label) = change_label(before, after) {
+            self.push(
+                label,
+                Command::Change {
+                    id: after.id,
+                    before: Box::new(before.clone()),
+                    after: Box::new(after.clone()),
+                },
+            );
+        }
+    }
+
+    /// Ends merging into the last entry, e.g. when a slider is released
+    pub fn seal(&mut self) {
+        if let Some(last) = self.entries.last_mut() {
+            last.open = false;
+        }
+    }
+
+    /// Applies `command` to `scene` and records it as its own entry
+    pub fn execute(&mut self, scene: &mut Scene, label: &str, command: Command) {
+        command.apply(scene);
+        self.push(label, command);
+        self.seal();
+    }
+
+    /// Adds `node` to the end of the scene and returns its id
+    pub fn add_node(&mut self, scene: &mut Scene, mut node: Node) -> NodeId {
+        let id = scene.next_id();
+        node.id = id;
+        self.execute(scene, &format!("Add {}", node.name), Command::Add {
+            index: scene.nodes.len(),
+            node: Box::new(node),
+        });
+        id
+    }
+
+    /// Copies the nodes with all their children, returns the ids of the copies of `ids`
+    pub fn duplicate_nodes(&mut self, scene: &mut Scene, ids: &[NodeId]) -> Vec<NodeId> {
+        let copied = scene.subtrees(ids);
+        let selected: HashSet<NodeId> = ids.iter().copied().collect();
+        // children of nodes that are copied anyway would be copied twice
+        let roots: Vec<NodeId> = scene
+            .nodes
+            .iter()
+            .filter(|node| selected.contains(&node.id))
+            .filter(|node| !node.parent.is_some_and(|parent| copied.contains(&parent)))
+            .map(|node| node.id)
+            .collect();
+        let renamed: HashSet<NodeId> = roots.iter().copied().collect();
+
+        let mut nodes = Vec::new();
+        for node in scene.nodes.iter().filter(|node| copied.contains(&node.id)) {
+            let mut node = node.clone();
+            if renamed.contains(&node.id) {
+                node.name = format!("{} copy", node.name);
+            }
+            nodes.push(node);
+        }
+
+        let label = match roots.as_slice() {
+            [] => return Vec::new(),
+            [id] => format!("Duplicate {}", scene.node(*id).unwrap().name),
+            _ => format!("Duplicate {} nodes", roots.len()),
+        };
+        let new_ids = self.insert_nodes(scene, &label, nodes);
+        roots.iter().map(|root| new_ids[root]).collect()
+    }
+
+    /// Adds `nodes` to the end of the scene with fresh ids, as one entry.
+    /// Parents among `nodes` are pointed at the new ids, others are kept.
+    /// Returns the new id of each old one.
+    pub fn insert_nodes(&mut self, scene: &mut Scene, label: &str, nodes: Vec<Node>) -> HashMap<NodeId, NodeId> {
+        // all ids first, a parent can come after its children
+        let new_ids: HashMap<NodeId, NodeId> = nodes
+            .iter()
+            .zip(scene.next_id().0..)
+            .map(|(node, id)| (node.id, NodeId(id)))
+            .collect();
+
+        let mut commands = Vec::new();
+        for mut node in nodes {
+            node.id = new_ids[&node.id];
+            node.parent = node.parent.map(|parent| new_ids.get(&parent).copied().unwrap_or(parent));
+            commands.push(Command::Add {
+                index: scene.nodes.len() + commands.len(),
+                node: Box::new(node),
+            });
+        }
+        if !commands.is_empty() {
+            self.execute(scene, label, Command::Group(commands));
+        }
+        new_ids
+    }
+
+    /// Deletes the nodes with all their children
+    pub fn remove_nodes(&mut self, scene: &mut Scene, ids: &[NodeId]) {
+        let removed = scene.subtrees(ids);
+        // removing from the back keeps the indices of the ones before valid
+        let commands: Vec<Command> = scene
+            .nodes
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, node)| removed.contains(&node.id))
+            .map(|(index, node)| Command::Remove {
+                index,
+                node: Box::new(node.clone()),
+            })
+            .collect();
+
+        let label = match commands.as_slice() {
+            [] => return,
+            [Command::Remove { node, .. }] => format!("Delete {}", node.name),
+            _ => format!("Delete {} nodes", commands.len()),
+        };
+        self.execute(scene, &label, Command::Group(commands));
+    }
+
+    /// Forgets all entries, for when the whole scene is replaced and they no longer apply to it
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.position = 0;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.position > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.position < self.entries.len()
+    }
+
+    pub fn undo(&mut self, scene: &mut Scene) {
+        if self.can_undo() {
+            self.position -= 1;
+            self.entries[self.position].command.revert(scene);
+            self.seal();
+        }
+    }
+
+    pub fn redo(&mut self, scene: &mut Scene) {
+        if self.can_redo() {
+            self.entries[self.position].command.apply(scene);
+            self.position += 1;
+        }
+    }
+
+    /// Undoes or redoes until exactly `position` entries are applied
+    pub fn jump_to(&mut self, position: usize, scene: &mut Scene) {
+        while self.position > position {
+            self.undo(scene);
+        }
+        while self.position < position.min(self.entries.len()) {
+            self.redo(scene);
+        }
+    }
+
+    /// Labels of all entries, oldest first
+    pub fn labels(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|entry| entry.label.as_str())
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+/// Console commands for undo and redo
+pub fn register_commands(registry: &mut Registry) {
+    registry.command("undo", "takes back the last edit of the scene", |context, _| {
+        context.history.undo(context.scene);
+        Ok(String::new())
+    });
+    registry.command("redo", "does the last undone edit again", |context, _| {
+        context.history.redo(context.scene);
+        Ok(String::new())
+    });
+}
+
+/// What the history calls a change of a node, `None` if nothing changed
+fn change_label(before: &Node, after: &Node) -> Option<&'static str> {
+    if before == after {
+        return None;
+    }
+    Some(if before.parent != after.parent {
+        "Reparent"
+    } else if before.name != after.name {
+        "Rename"
+    } else if before.visible != after.visible {
+        "Visibility"
+    } else if before.locked != after.locked {
+        "Lock"
+    } else if before.transform.location != after.transform.location {
+        "Move"
+    } else if before.transform.rotation != after.transform.rotation {
+        "Rotate"
+    } else if before.transform.size != after.transform.size {
+        "Scale"
+    } else if before.color != after.color {
+        "Color"
+    } else if before.mesh != after.mesh {
+        "Mesh"
+    } else if before.body != after.body || before.collider != after.collider {
+        "Physics"
+    } else if before.controller != after.controller {
+        "Character"
+    } else if before.script != after.script {
+        "Script"
+    } else if before.light != after.light {
+        "Light"
+    } else if before.prefab != after.prefab {
+        "Prefab"
+    } else {
+        "Edit"
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::RigidBody;
+    use glam::f32::Vec3;
+
+    fn scene_with(names: &[&str]) -> (Scene, History) {
+        let mut scene = Scene {
+            nodes: Vec::new(),
+            ..Scene::default()
+        };
+        let mut history = History::default();
+        for name in names {
+            history.add_node(&mut scene, Node::new(name));
+        }
+        (scene, history)
+    }
+
+    fn names(scene: &Scene) -> Vec<&str> {
+        scene.nodes.iter().map(|node| node.name.as_str()).collect()
+    }
+
+    #[test]
+    fn undo_and_redo_add_and_remove() {
+        let (mut scene, mut history) = scene_with(&["a", "b", "c"]);
+        let b = scene.nodes[1].id;
+        history.remove_nodes(&mut scene, &[b]);
+        assert_eq!(names(&scene), ["a", "c"]);
+        history.undo(&mut scene);
+        assert_eq!(names(&scene), ["a", "b", "c"]);
+        history.undo(&mut scene);
+        assert_eq!(names(&scene), ["a", "b"]);
+        history.redo(&mut scene);
+        history.redo(&mut scene);
+        assert_eq!(names(&scene), ["a", "c"]);
+    }
+
+    #[test]
+    fn remove_and_duplicate_with_children() {
+        let (mut scene, mut history) = scene_with(&["a", "b", "c", "d"]);
+        let ids: Vec<NodeId> = scene.nodes.iter().map(|node| node.id).collect();
+        // d is below b which is below a, c stays on its own in between
+        scene.nodes[1].parent = Some(ids[0]);
+        scene.nodes[3].parent = Some(ids[1]);
+
+        let copies = history.duplicate_nodes(&mut scene, &[ids[0], ids[1]]);
+        assert_eq!(names(&scene), ["a", "b", "c", "d", "a copy", "b", "d"]);
+        assert_eq!(copies.len(), 1);
+        history.undo(&mut scene);
+
+        history.remove_nodes(&mut scene, &[ids[0]]);
+        assert_eq!(names(&scene), ["c"]);
+        history.undo(&mut scene);
+        assert_eq!(names(&scene), ["a", "b", "c", "d"]);
+        assert_eq!(scene.nodes[3].parent, Some(ids[1]));
+        history.redo(&mut scene);
+        assert_eq!(names(&scene), ["c"]);
+    }
+
+    #[test]
+    fn undo_after_the_scene_was_replaced() {
+        let (mut scene, mut history) = scene_with(&["a", "b", "c"]);
+        let c = scene.nodes[2].id;
+        history.remove_nodes(&mut scene, &[c]);
+
+        // like a replay starting from a scene of its own
+        scene = scene_with(&["x"]).0;
+        history.clear();
+        assert!(!history.can_undo() && !history.can_redo());
+        history.undo(&mut scene);
+        assert_eq!(names(&scene), ["x"]);
+    }
+
+    #[test]
+    fn undo_keeps_what_the_simulation_changed() {
+        let (mut scene, mut history) = scene_with(&["a"]);
+        scene.nodes[0].body = Some(RigidBody::default());
+
+        let before = scene.nodes[0].clone();
+        scene.nodes[0].color = [255, 0, 0, 255];
+        let mut after = scene.nodes[0].clone();
+        history.record_change(&before, &mut after);
+        scene.nodes[0] = after;
+
+        // the simulation keeps running in the editor
+        let node = &mut scene.nodes[0];
+        node.transform.location = Vec3::new(0.0, -3.0, 0.0);
+        node.body.as_mut().unwrap().velocity = Vec3::new(0.0, -5.0, 0.0);
+
+        history.undo(&mut scene);
+        let node = &scene.nodes[0];
+        assert_eq!(node.color, [255, 255, 255, 255]);
+        assert_eq!(node.transform.location, Vec3::new(0.0, -3.0, 0.0));
+        assert_eq!(node.body.as_ref().unwrap().velocity, Vec3::new(0.0, -5.0, 0.0));
+
+        history.redo(&mut scene);
+        assert_eq!(scene.nodes[0].color, [255, 0, 0, 255]);
+        assert_eq!(scene.nodes[0].transform.location, Vec3::new(0.0, -3.0, 0.0));
+    }
+}
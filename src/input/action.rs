@@ -0,0 +1,218 @@
+use crate::input::{GamepadAxis, GamepadButton, GamepadState, InputState, Key};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// What the player wants to do, independent of the device they use for it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    MoveForward,
+    MoveBackward,
+    MoveLeft,
+    MoveRight,
+    Jump,
+    Sprint,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Binding {
+    Key(Key),
+    Button(GamepadButton),
+    /// only the positive half of an axis, e.g. pushing a stick up
+    AxisPositive(GamepadAxis),
+    /// only the negative half of an axis, e.g. pushing a stick down
+    AxisNegative(GamepadAxis),
+}
+
+/// Translates keys, buttons and axes into `Action`s
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ActionMap {
+    bindings: HashMap<Action, Vec<Binding>>,
+    /// sticks closer to the center than this count as released
+    pub stick_deadzone: f32,
+    /// triggers pressed less than this count as released
+    pub trigger_deadzone: f32,
+}
+
+impl Default for ActionMap {
+    fn default() -> Self {
+        let mut map = Self {
+            bindings: HashMap::new(),
+            stick_deadzone: 0.2,
+            trigger_deadzone: 0.1,
+        };
+
+        map.bind(Action::MoveForward, Binding::Key(Key::W));
+        map.bind(Action::MoveBackward, Binding::Key(Key::S));
+        map.bind(Action::MoveLeft, Binding::Key(Key::A));
+        map.bind(Action::MoveRight, Binding::Key(Key::D));
+        map.bind(Action::Jump, Binding::Key(Key::Space));
+        map.bind(Action::Sprint, Binding::Key(Key::LShift));
+
+        map.bind(Action::MoveForward, Binding::AxisPositive(GamepadAxis::LeftStickY));
+        map.bind(Action::MoveBackward, Binding::AxisNegative(GamepadAxis::LeftStickY));
+        map.bind(Action::MoveLeft, Binding::AxisNegative(GamepadAxis::LeftStickX));
+        map.bind(Action::MoveRight, Binding::AxisPositive(GamepadAxis::LeftStickX));
+        map.bind(Action::Jump, Binding::Button(GamepadButton::South));
+        map.bind(Action::Sprint, Binding::Button(GamepadButton::LeftStick));
+        map.bind(Action::Sprint, Binding::AxisPositive(GamepadAxis::LeftTrigger));
+
+        map
+    }
+}
+
+impl ActionMap {
+    pub fn bind(&mut self, action: Action, binding: Binding) {
+        let bindings = self.bindings.entry(action).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    pub fn bindings(&self, action: Action) -> &[Binding] {
+        self.bindings.get(&action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// How strongly `action` is requested, from 0 to 1.
+    /// If several bindings are active the strongest one wins.
+    pub fn value(&self, action: Action, input: &InputState) -> f32 {
+        let mut value: f32 = 0.0;
+        for &binding in self.bindings(action) {
+            let binding_value = match binding {
+                Binding::Key(key) => input.is_pressed(key) as u8 as f32,
+                Binding::Button(button) => input
+                    .gamepads()
+                    .any(|(_, gamepad)| gamepad.buttons.contains(&button)) as u8 as f32,
+                Binding::AxisPositive(axis) => input
+                    .gamepads()
+                    .map(|(_, gamepad)| self.axis(gamepad, axis).max(0.0))
+                    .fold(0.0, f32::max),
+                Binding::AxisNegative(axis) => input
+                    .gamepads()
+                    .map(|(_, gamepad)| (-self.axis(gamepad, axis)).max(0.0))
+                    .fold(0.0, f32::max),
+            };
+            value = value.max(binding_value);
+        }
+        value.min(1.0)
+    }
+
+    /// Value of `axis` with the deadzone applied and the remaining range
+    /// stretched back to the full -1 to 1, so there is no jump at the edge.
+    /// Sticks use a radial deadzone, so diagonals behave the same as straight pushes.
+    pub fn axis(&self, gamepad: &GamepadState, axis: GamepadAxis) -> f32 {
+        let value = gamepad.axis(axis);
+        match axis.partner() {
+            Some(partner) => {
+                let length = value.hypot(gamepad.axis(partner));
+                if length <= self.stick_deadzone {
+                    return 0.0;
+                }
+                let scaled = ((length - self.stick_deadzone) / (1.0 - self.stick_deadzone)).min(1.0);
+                value / length * scaled
+            }
+            None => {
+                if value <= self.trigger_deadzone {
+                    return 0.0;
+                }
+                ((value - self.trigger_deadzone) / (1.0 - self.trigger_deadzone)).min(1.0)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{GamepadEvent, GamepadId, InputEvent};
+
+    const PAD: GamepadId = GamepadId(0);
+
+    fn gamepad(axes: &[(GamepadAxis, f32)]) -> GamepadState {
+        GamepadState {
+            axes: axes.iter().copied().collect(),
+            ..GamepadState::default()
+        }
+    }
+
+    fn input(events: &[InputEvent]) -> InputState {
+        let mut input = InputState::default();
+        input.apply(&InputEvent::Gamepad(GamepadEvent::Connected(PAD)));
+        for event in events {
+            input.apply(event);
+        }
+        input
+    }
+
+    fn assert_near(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-5, "expected {}, got {}", expected, actual);
+    }
+
+    #[test]
+    fn stick_deadzone_is_radial() {
+        let map = ActionMap::default();
+        // each axis alone is inside the deadzone, together they're outside of it
+        let diagonal = gamepad(&[(GamepadAxis::LeftStickX, 0.15), (GamepadAxis::LeftStickY, 0.15)]);
+        assert!(map.axis(&diagonal, GamepadAxis::LeftStickX) > 0.0);
+        assert!(map.axis(&diagonal, GamepadAxis::LeftStickY) > 0.0);
+
+        let inside = gamepad(&[(GamepadAxis::LeftStickX, 0.1), (GamepadAxis::LeftStickY, -0.1)]);
+        assert_eq!(map.axis(&inside, GamepadAxis::LeftStickX), 0.0);
+        assert_eq!(map.axis(&inside, GamepadAxis::LeftStickY), 0.0);
+    }
+
+    #[test]
+    fn stick_range_is_stretched() {
+        let map = ActionMap::default();
+        let halfway = (1.0 + map.stick_deadzone) / 2.0;
+        assert_near(map.axis(&gamepad(&[(GamepadAxis::LeftStickX, halfway)]), GamepadAxis::LeftStickX), 0.5);
+        assert_near(map.axis(&gamepad(&[(GamepadAxis::LeftStickX, -1.0)]), GamepadAxis::LeftStickX), -1.0);
+        // the direction is kept when the stick is pushed past the edge diagonally
+        let corner = gamepad(&[(GamepadAxis::RightStickX, 1.0), (GamepadAxis::RightStickY, 1.0)]);
+        assert_near(map.axis(&corner, GamepadAxis::RightStickX), std::f32::consts::FRAC_1_SQRT_2);
+    }
+
+    #[test]
+    fn trigger_deadzone() {
+        let map = ActionMap::default();
+        let trigger = |value| map.axis(&gamepad(&[(GamepadAxis::LeftTrigger, value)]), GamepadAxis::LeftTrigger);
+        assert_eq!(trigger(map.trigger_deadzone), 0.0);
+        assert_near(trigger((1.0 + map.trigger_deadzone) / 2.0), 0.5);
+        assert_near(trigger(1.0), 1.0);
+        // the partner of a stick has no say over a trigger
+        let both = gamepad(&[(GamepadAxis::LeftTrigger, 0.05), (GamepadAxis::LeftStickX, 1.0)]);
+        assert_eq!(map.axis(&both, GamepadAxis::LeftTrigger), 0.0);
+    }
+
+    #[test]
+    fn bind_adds_each_binding_once() {
+        let mut map = ActionMap::default();
+        let before = map.bindings(Action::Jump).len();
+        map.bind(Action::Jump, Binding::Key(Key::J));
+        map.bind(Action::Jump, Binding::Key(Key::J));
+        assert_eq!(map.bindings(Action::Jump).len(), before + 1);
+        assert!(map.bindings(Action::Jump).contains(&Binding::Key(Key::J)));
+    }
+
+    #[test]
+    fn strongest_binding_wins() {
+        let map = ActionMap::default();
+        let pushed = input(&[InputEvent::Gamepad(GamepadEvent::AxisChanged(PAD, GamepadAxis::LeftStickY, 0.6))]);
+        let stick = map.value(Action::MoveForward, &pushed);
+        assert!(stick > 0.0 && stick < 1.0);
+        assert_eq!(map.value(Action::MoveBackward, &pushed), 0.0);
+
+        let mut both = pushed.clone();
+        both.apply(&InputEvent::KeyPressed(Key::W));
+        assert_eq!(map.value(Action::MoveForward, &both), 1.0);
+    }
+
+    #[test]
+    fn buttons_and_axes_both_trigger_an_action() {
+        let map = ActionMap::default();
+        let button = input(&[InputEvent::Gamepad(GamepadEvent::ButtonPressed(PAD, GamepadButton::LeftStick))]);
+        assert_eq!(map.value(Action::Sprint, &button), 1.0);
+        let trigger = input(&[InputEvent::Gamepad(GamepadEvent::AxisChanged(PAD, GamepadAxis::LeftTrigger, 1.0))]);
+        assert_eq!(map.value(Action::Sprint, &trigger), 1.0);
+        assert_eq!(map.value(Action::Jump, &trigger), 0.0);
+    }
+}
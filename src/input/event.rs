@@ -0,0 +1,92 @@
+use crate::input::{GamepadAxis, GamepadButton, GamepadEvent, GamepadId};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+pub use glium::glutin::event::VirtualKeyCode as Key;
+
+/// Everything the simulation reacts to. Kept small and serializable so a
+/// stream of these can be written to disk and replayed.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum InputEvent {
+    KeyPressed(Key),
+    KeyReleased(Key),
+    Gamepad(GamepadEvent),
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct GamepadState {
+    pub buttons: HashSet<GamepadButton>,
+    /// raw values as reported by the device, without any deadzone applied
+    pub axes: HashMap<GamepadAxis, f32>,
+}
+
+impl GamepadState {
+    pub fn axis(&self, axis: GamepadAxis) -> f32 {
+        self.axes.get(&axis).copied().unwrap_or(0.0)
+    }
+}
+
+/// Which keys are currently held down and what the connected gamepads are doing
+#[derive(Clone, Debug, Default)]
+pub struct InputState {
+    pressed: HashSet<Key>,
+    gamepads: HashMap<GamepadId, GamepadState>,
+}
+
+impl InputState {
+    pub fn apply(&mut self, event: &InputEvent) {
+        match *event {
+            InputEvent::KeyPressed(key) => {
+                self.pressed.insert(key);
+            }
+            InputEvent::KeyReleased(key) => {
+                self.pressed.remove(&key);
+            }
+            InputEvent::Gamepad(GamepadEvent::Connected(id)) => {
+                self.gamepads.insert(id, GamepadState::default());
+            }
+            InputEvent::Gamepad(GamepadEvent::Disconnected(id)) => {
+                self.gamepads.remove(&id);
+            }
+            // events that arrive after a gamepad was unplugged don't bring it back
+            InputEvent::Gamepad(GamepadEvent::ButtonPressed(id, button)) => {
+                if let Some(gamepad) = self.gamepads.get_mut(&id) {
+                    gamepad.buttons.insert(button);
+                }
+            }
+            InputEvent::Gamepad(GamepadEvent::ButtonReleased(id, button)) => {
+                if let Some(gamepad) = self.gamepads.get_mut(&id) {
+                    gamepad.buttons.remove(&button);
+                }
+            }
+            InputEvent::Gamepad(GamepadEvent::AxisChanged(id, axis, value)) => {
+                if let Some(gamepad) = self.gamepads.get_mut(&id) {
+                    gamepad.axes.insert(axis, value);
+                }
+            }
+        }
+    }
+
+    pub fn is_pressed(&self, key: Key) -> bool {
+        self.pressed.contains(&key)
+    }
+
+    pub fn gamepads(&self) -> impl Iterator<Item = (&GamepadId, &GamepadState)> {
+        self.gamepads.iter()
+    }
+
+    /// Events that would bring an empty state into this one
+    pub fn to_events(&self) -> Vec<InputEvent> {
+        let mut events: Vec<InputEvent> = self.pressed.iter().map(|&key| InputEvent::KeyPressed(key)).collect();
+        for (&id, gamepad) in &self.gamepads {
+            events.push(InputEvent::Gamepad(GamepadEvent::Connected(id)));
+            for &button in &gamepad.buttons {
+                events.push(InputEvent::Gamepad(GamepadEvent::ButtonPressed(id, button)));
+            }
+            for (&axis, &value) in &gamepad.axes {
+                events.push(InputEvent::Gamepad(GamepadEvent::AxisChanged(id, axis, value)));
+            }
+        }
+        events
+    }
+}
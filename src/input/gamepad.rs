@@ -0,0 +1,285 @@
+use crate::error::Error;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GamepadId(pub u32);
+
+/// Buttons named by their position, like on a xbox controller `South` is A
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    Select,
+    Start,
+    Mode,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+/// Sticks go from -1 to 1 (up and right are positive), triggers from 0 to 1
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+impl GamepadAxis {
+    /// The other axis of the same stick, `None` for triggers
+    pub fn partner(self) -> Option<GamepadAxis> {
+        match self {
+            GamepadAxis::LeftStickX => Some(GamepadAxis::LeftStickY),
+            GamepadAxis::LeftStickY => Some(GamepadAxis::LeftStickX),
+            GamepadAxis::RightStickX => Some(GamepadAxis::RightStickY),
+            GamepadAxis::RightStickY => Some(GamepadAxis::RightStickX),
+            GamepadAxis::LeftTrigger | GamepadAxis::RightTrigger => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GamepadEvent {
+    Connected(GamepadId),
+    Disconnected(GamepadId),
+    ButtonPressed(GamepadId, GamepadButton),
+    ButtonReleased(GamepadId, GamepadButton),
+    AxisChanged(GamepadId, GamepadAxis, f32),
+}
+
+/// Source of gamepad events, polled once per frame
+pub trait GamepadBackend {
+    fn poll(&mut self) -> Vec<GamepadEvent>;
+}
+
+/// Gamepads that only exist in software. Whatever is done to them
+/// comes out of `poll` like it would from real hardware.
+#[derive(Default)]
+pub struct VirtualGamepads {
+    connected: Vec<GamepadId>,
+    events: Vec<GamepadEvent>,
+}
+
+impl VirtualGamepads {
+    /// Plugs in a new gamepad. Ids are handed out from the top
+    /// so they don't clash with real devices.
+    pub fn connect(&mut self) -> GamepadId {
+        let mut id = GamepadId(u32::MAX);
+        while self.connected.contains(&id) {
+            id.0 -= 1;
+        }
+        self.connected.push(id);
+        self.events.push(GamepadEvent::Connected(id));
+        id
+    }
+
+    pub fn disconnect(&mut self, id: GamepadId) {
+        if let Some(index) = self.connected.iter().position(|&connected| connected == id) {
+            self.connected.remove(index);
+            self.events.push(GamepadEvent::Disconnected(id));
+        }
+    }
+
+    pub fn press(&mut self, id: GamepadId, button: GamepadButton) {
+        self.events.push(GamepadEvent::ButtonPressed(id, button));
+    }
+
+    pub fn release(&mut self, id: GamepadId, button: GamepadButton) {
+        self.events.push(GamepadEvent::ButtonReleased(id, button));
+    }
+
+    pub fn set_axis(&mut self, id: GamepadId, axis: GamepadAxis, value: f32) {
+        self.events.push(GamepadEvent::AxisChanged(id, axis, value));
+    }
+}
+
+impl GamepadBackend for VirtualGamepads {
+    fn poll(&mut self) -> Vec<GamepadEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+#[cfg(feature = "gamepad")]
+pub struct GilrsBackend {
+    gilrs: gilrs::Gilrs,
+    /// gamepads that were already plugged in when the backend started
+    connected: Vec<GamepadEvent>,
+}
+
+#[cfg(feature = "gamepad")]
+impl GilrsBackend {
+    pub fn new() -> Result<Self, String> {
+        // the error can hold a whole `Gilrs`, no need to carry that around
+        let gilrs = gilrs::Gilrs::new().map_err(|err| err.to_string())?;
+        let connected = gilrs
+            .gamepads()
+            .map(|(id, _)| GamepadEvent::Connected(Self::id(id)))
+            .collect();
+        Ok(Self { gilrs, connected })
+    }
+
+    fn id(id: gilrs::GamepadId) -> GamepadId {
+        let index: usize = id.into();
+        GamepadId(index as u32)
+    }
+
+    fn button(button: gilrs::Button) -> Option<GamepadButton> {
+        use gilrs::Button;
+        Some(match button {
+            Button::South => GamepadButton::South,
+            Button::East => GamepadButton::East,
+            Button::North => GamepadButton::North,
+            Button::West => GamepadButton::West,
+            Button::LeftTrigger => GamepadButton::LeftBumper,
+            Button::RightTrigger => GamepadButton::RightBumper,
+            Button::Select => GamepadButton::Select,
+            Button::Start => GamepadButton::Start,
+            Button::Mode => GamepadButton::Mode,
+            Button::LeftThumb => GamepadButton::LeftStick,
+            Button::RightThumb => GamepadButton::RightStick,
+            Button::DPadUp => GamepadButton::DPadUp,
+            Button::DPadDown => GamepadButton::DPadDown,
+            Button::DPadLeft => GamepadButton::DPadLeft,
+            Button::DPadRight => GamepadButton::DPadRight,
+            _ => return None,
+        })
+    }
+
+    fn axis(axis: gilrs::Axis) -> Option<GamepadAxis> {
+        use gilrs::Axis;
+        Some(match axis {
+            Axis::LeftStickX => GamepadAxis::LeftStickX,
+            Axis::LeftStickY => GamepadAxis::LeftStickY,
+            Axis::RightStickX => GamepadAxis::RightStickX,
+            Axis::RightStickY => GamepadAxis::RightStickY,
+            Axis::LeftZ => GamepadAxis::LeftTrigger,
+            Axis::RightZ => GamepadAxis::RightTrigger,
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(feature = "gamepad")]
+impl GamepadBackend for GilrsBackend {
+    fn poll(&mut self) -> Vec<GamepadEvent> {
+        use gilrs::{Button, EventType};
+
+        let mut events = std::mem::take(&mut self.connected);
+        while let Some(gilrs::Event { id, event, .. }) = self.gilrs.next_event() {
+            let id = Self::id(id);
+            let event = match event {
+                EventType::Connected => GamepadEvent::Connected(id),
+                EventType::Disconnected => GamepadEvent::Disconnected(id),
+                // Most controllers report the analog triggers as buttons with a value
+                EventType::ButtonChanged(Button::LeftTrigger2, value, _) => {
+                    GamepadEvent::AxisChanged(id, GamepadAxis::LeftTrigger, value)
+                }
+                EventType::ButtonChanged(Button::RightTrigger2, value, _) => {
+                    GamepadEvent::AxisChanged(id, GamepadAxis::RightTrigger, value)
+                }
+                EventType::ButtonPressed(button, _) => match Self::button(button) {
+                    Some(button) => GamepadEvent::ButtonPressed(id, button),
+                    None => continue,
+                },
+                EventType::ButtonReleased(button, _) => match Self::button(button) {
+                    Some(button) => GamepadEvent::ButtonReleased(id, button),
+                    None => continue,
+                },
+                EventType::AxisChanged(axis, value, _) => match Self::axis(axis) {
+                    Some(axis) => GamepadEvent::AxisChanged(id, axis, value),
+                    None => continue,
+                },
+                _ => continue,
+            };
+            events.push(event);
+        }
+        events
+    }
+}
+
+/// The backend for real controllers, one without any devices when gamepad support is disabled.
+/// Fails when the controllers can't be reached, callers can fall back to `VirtualGamepads`.
+pub fn default_gamepad_backend() -> Result<Box<dyn GamepadBackend>, Error> {
+    #[cfg(feature = "gamepad")]
+    return match GilrsBackend::new() {
+        Ok(backend) => Ok(Box::new(backend)),
+        Err(err) => Err(Error::Input(format!("no gamepads: {}", err))),
+    };
+
+    #[cfg(not(feature = "gamepad"))]
+    Ok(Box::new(VirtualGamepads::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{InputEvent, InputState};
+
+    fn apply(input: &mut InputState, gamepads: &mut VirtualGamepads) {
+        for event in gamepads.poll() {
+            input.apply(&InputEvent::Gamepad(event));
+        }
+    }
+
+    #[test]
+    fn connect_and_disconnect() {
+        let mut gamepads = VirtualGamepads::default();
+        let first = gamepads.connect();
+        let second = gamepads.connect();
+        assert_ne!(first, second);
+        assert_eq!(gamepads.poll(), [GamepadEvent::Connected(first), GamepadEvent::Connected(second)]);
+
+        gamepads.disconnect(first);
+        // twice is once too often, it's not there anymore
+        gamepads.disconnect(first);
+        assert_eq!(gamepads.poll(), [GamepadEvent::Disconnected(first)]);
+        assert!(gamepads.poll().is_empty());
+
+        // the free id is handed out again
+        assert_eq!(gamepads.connect(), first);
+    }
+
+    #[test]
+    fn input_follows_virtual_gamepads() {
+        let mut gamepads = VirtualGamepads::default();
+        let mut input = InputState::default();
+        let id = gamepads.connect();
+        gamepads.press(id, GamepadButton::South);
+        gamepads.set_axis(id, GamepadAxis::LeftStickX, 0.5);
+        apply(&mut input, &mut gamepads);
+
+        let (_, state) = input.gamepads().next().unwrap();
+        assert!(state.buttons.contains(&GamepadButton::South));
+        assert_eq!(state.axis(GamepadAxis::LeftStickX), 0.5);
+
+        gamepads.release(id, GamepadButton::South);
+        apply(&mut input, &mut gamepads);
+        assert!(input.gamepads().all(|(_, state)| state.buttons.is_empty()));
+
+        gamepads.disconnect(id);
+        apply(&mut input, &mut gamepads);
+        assert_eq!(input.gamepads().count(), 0);
+    }
+
+    #[test]
+    fn late_events_dont_reconnect() {
+        let mut gamepads = VirtualGamepads::default();
+        let mut input = InputState::default();
+        let id = gamepads.connect();
+        gamepads.disconnect(id);
+        gamepads.press(id, GamepadButton::South);
+        gamepads.set_axis(id, GamepadAxis::LeftStickY, 1.0);
+        apply(&mut input, &mut gamepads);
+        assert_eq!(input.gamepads().count(), 0);
+    }
+}
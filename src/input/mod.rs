@@ -0,0 +1,9 @@
+mod action;
+mod event;
+mod gamepad;
+mod recording;
+
+pub use action::*;
+pub use event::*;
+pub use gamepad::*;
+pub use recording::*;
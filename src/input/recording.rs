@@ -0,0 +1,81 @@
+use crate::input::InputEvent;
+use crate::scene::Scene;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    /// simulation step the event was applied in, counted from the start of the recording
+    pub frame: u64,
+    pub event: InputEvent,
+}
+
+/// An input stream together with everything needed to reproduce it:
+/// the timestep it was recorded with and the scene it started from.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Recording {
+    pub timestep: f32,
+    pub scene: Scene,
+    pub events: Vec<RecordedEvent>,
+    /// total number of simulation steps covered
+    pub frames: u64,
+}
+
+impl Recording {
+    pub fn new(timestep: f32, scene: Scene) -> Self {
+        Self {
+            timestep,
+            scene,
+            events: Vec::new(),
+            frames: 0,
+        }
+    }
+
+    pub fn record(&mut self, frame: u64, event: InputEvent) {
+        self.events.push(RecordedEvent { frame, event });
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let text = fs::read_to_string(path)?;
+        let mut recording: Self = ron::from_str(&text)?;
+        recording.scene.assign_missing_ids();
+        Ok(recording)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::new())?;
+        fs::write(path, text)?;
+        Ok(())
+    }
+}
+
+/// Feeds the events of a `Recording` back frame by frame
+pub struct Playback {
+    recording: Recording,
+    next: usize,
+}
+
+impl Playback {
+    pub fn new(recording: Recording) -> Self {
+        Self { recording, next: 0 }
+    }
+
+    /// Returns the events recorded for `frame`. Frames have to be requested in order.
+    pub fn events_for(&mut self, frame: u64) -> Vec<InputEvent> {
+        let mut events = Vec::new();
+        while let Some(recorded) = self.recording.events.get(self.next) {
+            if recorded.frame > frame {
+                break;
+            }
+            events.push(recorded.event);
+            self.next += 1;
+        }
+        events
+    }
+
+    pub fn is_finished(&self, frame: u64) -> bool {
+        frame >= self.recording.frames
+    }
+}
@@ -0,0 +1,242 @@
+use crate::scene;
+use egui::{DragValue, Ui};
+use glam::f32::{Quat, Vec3};
+use glam::EulerRot;
+use std::fmt::Debug;
+
+/// Drag speed of numbers without a natural scale
+const SPEED: f32 = 0.01;
+
+/// Values that can be edited in the inspector.
+/// Structs and unit enums get an implementation from `inspect_fields!` and `inspect_enum!`.
+pub trait Inspect {
+    /// Shows widgets for `self` named `label`, returns whether anything was edited
+    fn inspect(&mut self, ui: &mut Ui, label: &str) -> bool;
+}
+
+/// Structs whose fields can be shown without the section around them, e.g. in a window of their own
+pub trait InspectFields {
+    fn inspect_fields(&mut self, ui: &mut Ui) -> bool;
+}
+
+impl Inspect for f32 {
+    fn inspect(&mut self, ui: &mut Ui, label: &str) -> bool {
+        row(ui, label, |ui| ui.add(DragValue::new(self).speed(SPEED)).changed())
+    }
+}
+
+impl Inspect for bool {
+    fn inspect(&mut self, ui: &mut Ui, label: &str) -> bool {
+        ui.checkbox(self, label).changed()
+    }
+}
+
+impl Inspect for String {
+    fn inspect(&mut self, ui: &mut Ui, label: &str) -> bool {
+        row(ui, label, |ui| ui.text_edit_singleline(self).changed())
+    }
+}
+
+impl Inspect for Vec3 {
+    fn inspect(&mut self, ui: &mut Ui, label: &str) -> bool {
+        row(ui, label, |ui| vector(ui, self))
+    }
+}
+
+/// A checkbox adds and removes the value, e.g. a component of a node
+impl<T: Inspect + Default> Inspect for Option<T> {
+    fn inspect(&mut self, ui: &mut Ui, label: &str) -> bool {
+        ui.horizontal(|ui| {
+            let mut enabled = self.is_some();
+            let toggled = ui.checkbox(&mut enabled, "").changed();
+            if toggled {
+                *self = if enabled { Some(T::default()) } else { None };
+            }
+            match self {
+                Some(value) => value.inspect(ui, label) || toggled,
+                None => {
+                    ui.label(label);
+                    toggled
+                }
+            }
+        })
+        .inner
+    }
+}
+
+/// A label followed by the widgets `add` puts on the same line
+pub fn row(ui: &mut Ui, label: &str, add: impl FnOnce(&mut Ui) -> bool) -> bool {
+    ui.horizontal(|ui| {
+        ui.label(label);
+        add(ui)
+    })
+    .inner
+}
+
+/// Field name as shown in the inspector, `walk_speed` becomes "walk speed"
+pub fn field_label(name: &str) -> String {
+    name.replace('_', " ")
+}
+
+fn vector(ui: &mut Ui, value: &mut Vec3) -> bool {
+    let mut changed = false;
+    for axis in 0..3 {
+        changed |= ui.add(DragValue::new(&mut value[axis]).speed(SPEED)).changed();
+    }
+    changed
+}
+
+// Widgets for fields that shouldn't be shown like any other value of their type,
+// used as `field: widget` in `inspect_fields!`
+
+/// Premultiplied srgba, as in `Node::color`
+pub fn color(ui: &mut Ui, value: &mut [u8; 4]) -> bool {
+    let before = *value;
+    ui.color_edit_button_srgba_premultiplied(value);
+    *value != before
+}
+
+/// Orders the Euler angles of a rotation can be shown in, the first is what older scenes stored
+const EULER_ORDERS: [(EulerRot, &str); 6] = [
+    (EulerRot::XYZ, "XYZ"),
+    (EulerRot::XZY, "XZY"),
+    (EulerRot::YXZ, "YXZ"),
+    (EulerRot::YZX, "YZX"),
+    (EulerRot::ZXY, "ZXY"),
+    (EulerRot::ZYX, "ZYX"),
+];
+
+/// What a rotation field remembers between frames
+#[derive(Clone, Default)]
+struct EulerState {
+    /// index into `EULER_ORDERS`
+    order: usize,
+    /// the angles last shown and the rotation they were shown for
+    angles: [f32; 3],
+    rotation: Quat,
+}
+
+/// Rotation edited as Euler angles in degrees, in an order picked next to them.
+/// Angles that were typed in stay as they are while the rotation doesn't change,
+/// even if converting back would give different ones, e.g. 180° instead of -180°.
+pub fn euler_degrees(ui: &mut Ui, value: &mut Quat) -> bool {
+    let id = ui.make_persistent_id("euler_degrees");
+    let mut state: EulerState = ui.memory().id_data_temp.get_or_default::<EulerState>(id).clone();
+
+    let order = state.order;
+    egui::ComboBox::from_id_source(id)
+        .width(50.0)
+        .selected_text(EULER_ORDERS[state.order].1)
+        .show_ui(ui, |ui| {
+            for (index, (_, name)) in EULER_ORDERS.iter().enumerate() {
+                ui.selectable_value(&mut state.order, index, *name);
+            }
+        });
+    let (rotation_order, name) = EULER_ORDERS[state.order];
+    if state.rotation != *value || state.order != order {
+        let (a, b, c) = scene::euler_angles(*value, rotation_order);
+        state.angles = [a.to_degrees(), b.to_degrees(), c.to_degrees()];
+        state.rotation = *value;
+    }
+
+    let mut changed = false;
+    for (angle, axis) in state.angles.iter_mut().zip(name.chars()) {
+        let drag = DragValue::new(angle).speed(1.0).prefix(format!("{} ", axis)).suffix("°");
+        changed |= ui.add(drag).changed();
+    }
+    if changed {
+        let [a, b, c] = state.angles;
+        *value = Quat::from_euler(rotation_order, a.to_radians(), b.to_radians(), c.to_radians());
+        state.rotation = *value;
+    }
+
+    ui.memory().id_data_temp.insert(id, state);
+    changed
+}
+
+/// A checkbox for each of the first 8 bits, e.g. collision layers
+pub fn layers(ui: &mut Ui, value: &mut u32) -> bool {
+    let mut changed = false;
+    for layer in 0..8 {
+        let mut set = *value & (1 << layer) != 0;
+        if ui.checkbox(&mut set, "").on_hover_text(format!("layer {}", layer + 1)).changed() {
+            *value ^= 1 << layer;
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Numbers that must stay above 0, e.g. a mass or a size
+pub fn positive(ui: &mut Ui, value: &mut f32) -> bool {
+    ui.add(DragValue::new(value).speed(SPEED).clamp_range(0.001..=f32::MAX)).changed()
+}
+
+pub fn non_negative(ui: &mut Ui, value: &mut f32) -> bool {
+    ui.add(DragValue::new(value).speed(SPEED).clamp_range(0.0..=f32::MAX)).changed()
+}
+
+/// Numbers from 0 to 1, e.g. bounciness
+pub fn fraction(ui: &mut Ui, value: &mut f32) -> bool {
+    ui.add(DragValue::new(value).speed(SPEED).clamp_range(0.0..=1.0)).changed()
+}
+
+/// State the simulation keeps up to date, shown but not editable
+pub fn read_only<T: Debug>(ui: &mut Ui, value: &mut T) -> bool {
+    ui.label(format!("{:?}", value));
+    false
+}
+
+/// Implements `InspectFields` for a struct with a widget per listed field,
+/// and `Inspect` as a collapsible section of them.
+/// Fields are shown as `Inspect` shows their type, or with `field: widget` by
+/// calling `widget(ui, &mut field) -> bool` next to the field's name.
+/// Fields that aren't listed are left out.
+macro_rules! inspect_fields {
+    (@field $self:ident, $ui:ident, $field:ident) => {
+        $crate::inspect::Inspect::inspect(&mut $self.$field, $ui, &$crate::inspect::field_label(stringify!($field)))
+    };
+    (@field $self:ident, $ui:ident, $field:ident, $widget:expr) => {
+        $crate::inspect::row($ui, &$crate::inspect::field_label(stringify!($field)), |ui| {
+            $widget(ui, &mut $self.$field)
+        })
+    };
+    ($type:ty { $($field:ident $(: $widget:expr)?),* $(,)? }) => {
+        impl $crate::inspect::InspectFields for $type {
+            fn inspect_fields(&mut self, ui: &mut egui::Ui) -> bool {
+                let mut changed = false;
+                $(changed |= $crate::inspect::inspect_fields!(@field self, ui, $field $(, $widget)?);)*
+                changed
+            }
+        }
+
+        impl $crate::inspect::Inspect for $type {
+            fn inspect(&mut self, ui: &mut egui::Ui, label: &str) -> bool {
+                egui::CollapsingHeader::new(label)
+                    .show(ui, |ui| $crate::inspect::InspectFields::inspect_fields(self, ui))
+                    .body_returned
+                    .unwrap_or(false)
+            }
+        }
+    };
+}
+
+/// Implements `Inspect` for an enum without fields as a combo box of the listed variants
+macro_rules! inspect_enum {
+    ($type:ident { $($variant:ident),* $(,)? }) => {
+        impl $crate::inspect::Inspect for $type {
+            fn inspect(&mut self, ui: &mut egui::Ui, label: &str) -> bool {
+                let before = *self;
+                egui::ComboBox::from_label(label)
+                    .selected_text(format!("{:?}", self))
+                    .show_ui(ui, |ui| {
+                        $(ui.selectable_value(self, $type::$variant, stringify!($variant));)*
+                    });
+                *self != before
+            }
+        }
+    };
+}
+
+pub(crate) use inspect_enum;
+pub(crate) use inspect_fields;
@@ -0,0 +1,177 @@
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Where the editor writes its log
+pub const LOG_FILE: &str = "logs/game.log";
+
+/// Oldest records are dropped from the viewer beyond this
+const MAX_RECORDS: usize = 5000;
+
+/// A log file is moved aside once it grows beyond this
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Log files kept besides the current one, `game.1.log` is the newest of them
+const KEEP_FILES: usize = 3;
+
+/// Records at least this important are also written to stderr
+const ECHO_LEVEL: Level = Level::Info;
+
+/// Name of the crate in the targets of its records, dependencies only get to log warnings
+const CRATE: &str = env!("CARGO_PKG_NAME");
+
+/// A message that was logged
+#[derive(Clone, Debug)]
+pub struct LogRecord {
+    /// seconds since the logger started
+    pub time: f32,
+    pub level: Level,
+    /// module the record comes from, e.g. `assets::manager`
+    pub target: String,
+    pub message: String,
+}
+
+/// The latest records, shared between the logger and the log viewer
+#[derive(Default)]
+pub struct LogBuffer {
+    pub records: VecDeque<LogRecord>,
+}
+
+pub type SharedLog = Arc<Mutex<LogBuffer>>;
+
+/// Log file that starts over in a new file when it gets too big
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+}
+
+impl LogFile {
+    /// Starts a new file at `path`, the one of the last run is kept as an older one
+    fn create(path: &Path) -> io::Result<Self> {
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        if path.exists() {
+            rotate(path)?;
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            file: File::create(path)?,
+            size: 0,
+        })
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        if self.size > MAX_FILE_SIZE {
+            *self = LogFile::create(&self.path)?;
+        }
+        writeln!(self.file, "{}", line)?;
+        self.size += line.len() as u64 + 1;
+        Ok(())
+    }
+}
+
+/// Moves `game.log` to `game.1.log`, `game.1.log` to `game.2.log` and so on, dropping the oldest
+fn rotate(path: &Path) -> io::Result<()> {
+    for number in (1..KEEP_FILES).rev() {
+        let older = numbered(path, number);
+        if older.exists() {
+            fs::rename(&older, numbered(path, number + 1))?;
+        }
+    }
+    fs::rename(path, numbered(path, 1))
+}
+
+fn numbered(path: &Path, number: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    match path.extension() {
+        Some(extension) => path.with_file_name(format!("{}.{}.{}", stem, number, extension.to_string_lossy())),
+        None => path.with_file_name(format!("{}.{}", stem, number)),
+    }
+}
+
+/// Module path within this crate, `None` for other crates
+fn own_module(target: &str) -> Option<&str> {
+    match target.strip_prefix(CRATE)? {
+        "" => Some("main"),
+        rest => rest.strip_prefix("::"),
+    }
+}
+
+struct Logger {
+    start: Instant,
+    buffer: SharedLog,
+    file: Option<Mutex<LogFile>>,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        own_module(metadata.target()).is_some() || metadata.level() <= Level::Warn
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let target = record.target();
+        let record = LogRecord {
+            time: self.start.elapsed().as_secs_f32(),
+            level: record.level(),
+            target: own_module(target).unwrap_or(target).to_string(),
+            message: record.args().to_string(),
+        };
+
+        let line = format!("[{:9.3}] {:5} {}: {}", record.time, record.level, record.target, record.message);
+        if record.level <= ECHO_LEVEL {
+            eprintln!("{}", line);
+        }
+        if let Some(file) = &self.file {
+            if let Err(err) = file.lock().unwrap().write_line(&line) {
+                eprintln!("Could not write to the log file: {}", err);
+            }
+        }
+
+        let mut buffer = self.buffer.lock().unwrap();
+        if buffer.records.len() == MAX_RECORDS {
+            buffer.records.pop_front();
+        }
+        buffer.records.push_back(record);
+    }
+
+    fn flush(&self) {
+        if let Some(file) = &self.file {
+            let _ = file.lock().unwrap().file.flush();
+        }
+    }
+}
+
+/// Installs the logger for the `log` macros and returns the records for the viewer.
+/// Records also go to the file at `path`, if there is one. Those below `max_level` are dropped.
+pub fn init(path: Option<&Path>, max_level: LevelFilter) -> SharedLog {
+    let buffer = SharedLog::default();
+    let mut file_error = None;
+    let file = path.and_then(|path| match LogFile::create(path) {
+        Ok(file) => Some(Mutex::new(file)),
+        Err(err) => {
+            file_error = Some(format!("Could not create log file {}: {}", path.display(), err));
+            None
+        }
+    });
+    let logger = Logger {
+        start: Instant::now(),
+        buffer: buffer.clone(),
+        file,
+    };
+    if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        log::set_max_level(max_level);
+    }
+    if let Some(error) = file_error {
+        log::warn!("{}", error);
+    }
+    buffer
+}
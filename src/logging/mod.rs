@@ -0,0 +1,5 @@
+mod logger;
+mod viewer;
+
+pub use logger::*;
+pub use viewer::*;
@@ -0,0 +1,95 @@
+use crate::logging::{LogRecord, SharedLog};
+use egui::{Color32, TextStyle, Ui};
+use log::Level;
+use std::collections::{BTreeSet, HashSet};
+
+const LEVELS: [Level; 5] = [Level::Error, Level::Warn, Level::Info, Level::Debug, Level::Trace];
+
+/// Panel listing the log records, filtered by level, module and text
+pub struct LogViewer {
+    /// levels that are listed
+    levels: HashSet<Level>,
+    /// modules that are hidden
+    hidden: HashSet<String>,
+    search: String,
+}
+
+impl Default for LogViewer {
+    fn default() -> Self {
+        Self {
+            // traces are mostly input, they'd drown out the rest
+            levels: LEVELS.iter().copied().filter(|&level| level != Level::Trace).collect(),
+            hidden: HashSet::new(),
+            search: String::new(),
+        }
+    }
+}
+
+impl LogViewer {
+    pub fn show(&mut self, ui: &mut Ui, log: &SharedLog) {
+        let mut log = log.lock().unwrap();
+
+        ui.horizontal(|ui| {
+            for &level in LEVELS.iter() {
+                let shown = self.levels.contains(&level);
+                if ui.selectable_label(shown, level.as_str()).clicked() {
+                    if shown {
+                        self.levels.remove(&level);
+                    } else {
+                        self.levels.insert(level);
+                    }
+                }
+            }
+            if ui.button("Clear").clicked() {
+                log.records.clear();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Search");
+            ui.text_edit_singleline(&mut self.search);
+        });
+
+        let modules: BTreeSet<&str> = log.records.iter().map(|record| record.target.as_str()).collect();
+        egui::CollapsingHeader::new("Modules").default_open(false).show(ui, |ui| {
+            for module in modules {
+                let mut shown = !self.hidden.contains(module);
+                if ui.checkbox(&mut shown, module).changed() {
+                    if shown {
+                        self.hidden.remove(module);
+                    } else {
+                        self.hidden.insert(module.to_string());
+                    }
+                }
+            }
+        });
+        ui.separator();
+
+        let search = self.search.to_lowercase();
+        let records: Vec<&LogRecord> = log
+            .records
+            .iter()
+            .filter(|record| {
+                self.levels.contains(&record.level)
+                    && !self.hidden.contains(&record.target)
+                    && record.message.to_lowercase().contains(&search)
+            })
+            .collect();
+        let row_height = ui.fonts()[TextStyle::Monospace].row_height();
+        egui::ScrollArea::auto_sized().show_rows(ui, row_height, records.len(), |ui, rows| {
+            for record in &records[rows] {
+                let text = format!("{:9.3} {:5} {}: {}", record.time, record.level, record.target, record.message);
+                ui.add(egui::Label::new(text).text_style(TextStyle::Monospace).text_color(level_color(ui, record.level)));
+            }
+        });
+    }
+}
+
+/// Errors red and warnings yellow, the rest less prominent
+pub fn level_color(ui: &Ui, level: Level) -> Color32 {
+    match level {
+        Level::Error => Color32::from_rgb(230, 90, 80),
+        Level::Warn => Color32::from_rgb(220, 180, 70),
+        Level::Info => ui.visuals().text_color(),
+        Level::Debug | Level::Trace => ui.visuals().weak_text_color(),
+    }
+}
@@ -1,327 +1,506 @@
+mod assets;
+mod chrome;
+mod cli;
 mod color;
+mod config;
+mod console;
+mod controller;
+mod error;
+mod gizmo;
+mod history;
+mod input;
+mod inspect;
+mod logging;
+mod math;
+mod mesh;
+mod outliner;
+mod physics;
+mod prefab;
+mod render;
+mod scene;
+mod script;
+mod selection;
+mod simulation;
 mod teapot;
+mod toasts;
 
-use color::BACKGROUND_COLOR;
+use assets::AssetBrowser;
+use chrome::WindowChrome;
+use config::{Config, FullscreenMode, Mode, Overrides, SettingsWindow};
+use console::{Console, Value};
 use egui::{vec2, Slider};
-use glium::implement_vertex;
-use glium::uniform;
+use error::{Error, RenderContext};
+use gizmo::{Gizmo, GizmoMode, GizmoSpace, LineRenderer};
+use glam::f32::Vec2;
+use history::History;
+use input::{GamepadAxis, GamepadBackend, GamepadButton, GamepadEvent, InputEvent, Recording, VirtualGamepads};
+use inspect::InspectFields;
+use logging::LogViewer;
+use math::Viewport;
+use mesh::GpuMeshes;
+use outliner::Outliner;
+use prefab::Prefabs;
+use render::{Capture, RenderStats, SceneRenderer};
+use scene::{Node, Scene};
+use selection::Selection;
+use simulation::Simulation;
 use std::time::Instant;
-use glium::glutin::event::ElementState;
+use toasts::Toasts;
 
 extern crate glium;
 
 fn main() {
-    use glium::{glutin, Surface};
+    use glium::glutin;
+
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(code) = cli::run(&args) {
+        std::process::exit(code);
+    }
+
+    let overrides = match Overrides::parse(&args[1..]) {
+        Ok(overrides) => overrides,
+        Err(err) => {
+            eprintln!("{}\n{}", err, config::USAGE);
+            std::process::exit(2);
+        }
+    };
+    let config_path = overrides.config_path();
+    let file_config = Config::load(&config_path);
+    let mut config = file_config.clone().unwrap_or_default();
+    overrides.apply(&mut config);
+    let editor = config.startup.mode == Mode::Editor;
+
+    let log = logging::init(Some(std::path::Path::new(logging::LOG_FILE)), config.startup.log_level);
+    let mut log_viewer = LogViewer::default();
+    let mut toasts = Toasts::default();
+    if let Err(err) = &file_config {
+        toasts.warn(format!("Using the default settings, {}", err));
+    }
+    let mut settings = SettingsWindow::new(config_path, file_config.unwrap_or_default());
 
     let event_loop = glutin::event_loop::EventLoop::new();
-    let window_builder = glutin::window::WindowBuilder::new().with_title("Game").with_decorations(false);
-    let context_builder = glutin::ContextBuilder::new()
-        .with_hardware_acceleration(Some(true))
-        .with_vsync(true)
-        .with_srgb(true);
+    let windowed = !config.window.decorations && config.window.fullscreen == FullscreenMode::Windowed;
+    let mut chrome = WindowChrome::load(windowed);
+    // a resolution on the command line wins over the size the window had last time
+    let window_builder = if overrides.resolution.is_some() {
+        config.window_builder(&event_loop)
+    } else {
+        chrome.window_builder(config.window_builder(&event_loop))
+    };
+    let context_builder = config.context_builder();
 
-    let display = glium::Display::new(window_builder, context_builder, &event_loop).unwrap();
+    let display = match glium::Display::new(window_builder, context_builder, &event_loop) {
+        Ok(display) => display,
+        Err(err) => exit_with(Error::Window(err.to_string())),
+    };
 
     let mut egui = egui_glium::EguiGlium::new(&display);
 
-    let mut name = "Hi!";
     let mut code = String::from("Hello");
 
-    let mut gui_is_active = true;
-
+    let mut gui_is_active = editor;
 
-    let positions = glium::VertexBuffer::new(&display, &teapot::VERTICES).unwrap();
-    let normals = glium::VertexBuffer::new(&display, &teapot::NORMALS).unwrap();
-    let indices = glium::IndexBuffer::new(&display, glium::index::PrimitiveType::TrianglesList,
-                                      &teapot::INDICES).unwrap();
-    let vertex_shader_src = r#"
-        #version 330
 
-        in vec3 position;
-        in vec3 normal;
+    let mut renderer = SceneRenderer::new(&display).unwrap_or_else(|err| exit_with(err));
 
-        out vec3 v_normal;
-
-        uniform mat4 perspective;
-
-        uniform mat4 view;
-        uniform mat4 model;
-
-        void main() {
-            mat4 modelview = view * model;
-            v_normal = transpose(inverse(mat3(modelview))) * normal;  
-            gl_Position = perspective * modelview * vec4(position, 1.0);
-        }
-    "#;
-
-    let fragment_shader_src = r#"
-        #version 330
-
-        #ifdef GL_ES
-        precision mediump float;
-        #endif
-        
-        in vec3 v_normal;
-        out vec4 color;
-        uniform vec3 u_light;
-        uniform vec4 rgba;
-
-        void main() {
-            float brightness = dot(normalize(v_normal), normalize(u_light));
-            vec3 dark_color = vec3(0.6 * rgba);
-            vec3 regular_color = vec3(rgba);
-            color = vec4(mix(dark_color, regular_color, brightness), rgba[3]);
-        }
-    "#;
-
-    let program =
-        glium::Program::from_source(&display, vertex_shader_src, fragment_shader_src, None)
-            .unwrap();
-
-    let mut x_location: f32 = 0.0;
-    let mut y_location: f32 = 0.0;
-    let mut z_location: f32 = 2.0;
-    
-
-    let mut x_rotation: f32 = 0.0;
-    let mut y_rotation: f32 = 0.0;
-    let mut z_rotation: f32 = 0.0;
+    let mut scene = match &config.startup.scene {
+        Some(path) => Scene::load(path).unwrap_or_else(|err| {
+            toasts.error(format!("Could not open scene {}: {}", path.display(), err));
+            Scene::default()
+        }),
+        None => Scene::default(),
+    };
+    let mut simulation = Simulation::new(simulation::TIMESTEP);
+    let mut last_update = Instant::now();
 
-    let mut x_size: f32 = 0.01;
-    let mut y_size: f32 = 0.01;
-    let mut z_size: f32 = 0.01;
+    let mut gpu_meshes = GpuMeshes::default();
 
-    let mut color_picker_color = egui::Color32::from_rgba_premultiplied(255, 0, 0, 255);
+    let mut gamepads = input::default_gamepad_backend().unwrap_or_else(|err| {
+        toasts.warn(err);
+        Box::new(VirtualGamepads::default())
+    });
+    // lets mappings be tried out without a controller at hand
+    let mut virtual_gamepads = VirtualGamepads::default();
+    let mut virtual_gamepad = None;
+    let mut virtual_stick = [0.0f32; 2];
+    let mut virtual_south = false;
+
+    let mut selection = Selection::default();
+    selection.set(scene.nodes.first().map(|node| node.id));
+    let mut outliner = Outliner::default();
+    let mut cursor_position = Vec2::ZERO;
+    // camera of the last frame, clicks are picked with what was on screen
+    let mut viewport: Option<Viewport> = None;
+    let mut gizmo = Gizmo::default();
+    // the selected node as it was when the gizmo was grabbed
+    let mut gizmo_before: Option<Node> = None;
+    let mut history = History::default();
+    let mut modifiers = glutin::event::ModifiersState::default();
+    let line_renderer = LineRenderer::new(&display).unwrap_or_else(|err| exit_with(err));
+    let mut asset_browser = AssetBrowser::default();
+    let mut capture = Capture::default();
+    let mut render_stats = RenderStats::default();
+    // of the last frame, for the stats
+    let mut frame_time = std::time::Duration::default();
+    let mut prefabs = Prefabs::default();
+
+    let mut console = Console::default();
+    console.registry.var("r_wireframe", "draws meshes as lines", Value::Bool(false));
+    console.registry.var("r_stats", "shows draw calls and how many nodes were drawn and culled", Value::Bool(false));
+    console.registry.var("capture_fps", "frames per second of simulated time when capturing frames", Value::Float(60.0));
+    history::register_commands(&mut console.registry);
+    outliner::register_commands(&mut console.registry);
+    physics::register_commands(&mut console.registry);
+    render::register_commands(&mut console.registry);
+    simulation::register_commands(&mut console.registry);
+    if std::path::Path::new(console::STARTUP_FILE).exists() {
+        let mut context = console::Context {
+            scene: &mut scene,
+            history: &mut history,
+            selection: &mut selection,
+            simulation: &mut simulation,
+        };
+        console.exec_file(&mut context, console::STARTUP_FILE);
+    }
 
     event_loop.run(move |event, _, control_flow| {
 
         let mut redraw = || {
+            if let Err(err) = asset_browser.render_thumbnails(&display, egui.ctx_and_painter_mut().1, renderer.program(), &mut simulation.meshes) {
+                toasts.error(err);
+            }
             egui.begin_frame(&display);
+            chrome.update(egui.ctx(), display.gl_window().window());
+
+            prefabs.update(&mut scene, &mut history);
+            for err in simulation.meshes.take_errors().into_iter().chain(prefabs.take_errors()) {
+                toasts.push(log::Level::Error, format!("Could not load {}", err));
+            }
+            // undo and redo may have removed selected nodes
+            selection.retain_existing(&scene);
 
             let mut quit = false;
 
 
 
-            egui::TopBottomPanel::top("my_top_panel").show(egui.ctx(), |ui| {
-                let file_popup_id = ui.make_persistent_id("file_popup_0");
-                ui.horizontal(|ui| {
-                    let file_button = ui.button("File");
+            let mut dropped = None;
+            // the game runs without the editor around it
+            if editor {
+                egui::TopBottomPanel::top("my_top_panel").show(egui.ctx(), |ui| {
+                    let file_popup_id = ui.make_persistent_id("file_popup_0");
+                    ui.horizontal(|ui| {
+                        let file_button = ui.button("File");
 
-                    if file_button.clicked() {
-                        ui.memory().toggle_popup(file_popup_id)
-                    }
-                    egui::popup::popup_below_widget(ui, file_popup_id, &file_button, |ui| {
-                        ui.set_min_width(200.0); // if you want to control the size
-                        ui.label("Some more info, or things you can select:");
-                        ui.label("…");
-                        if ui.button("Open").clicked() {
-                            let open_file: String;
-                            match tinyfiledialogs::open_file_dialog("Open", "hi", None) {
-                                Some(file) => open_file = file,
-                                None => open_file = "null".to_string(),
-                            }
-                            println!("Open file {:?}", open_file);
+                        if file_button.clicked() {
+                            ui.memory().toggle_popup(file_popup_id)
                         }
-
-                        /* Maybe use this instead of egui buildin color picker?
-                        if ui.button("color").clicked() {
-                            let color: String;
-                            match tinyfiledialogs::color_chooser_dialog("Choose a Color", tinyfiledialogs::DefaultColorValue::Hex("#FF0000")) {
-                                Some((hex_result, _rgb)) => color = hex_result,
-                                None => color = "null".to_string(),
+                        egui::popup::popup_below_widget(ui, file_popup_id, &file_button, |ui| {
+                            ui.set_min_width(200.0); // if you want to control the size
+                            ui.label("Some more info, or things you can select:");
+                            ui.label("…");
+                            if ui.button("Settings").clicked() {
+                                settings.open = true;
+                            }
+                            if ui.button("Open").clicked() {
+                                let open_file = match tinyfiledialogs::open_file_dialog("Open", "hi", None) {
+                                    Some(file) => file,
+                                    None => "null".to_string(),
+                                };
+                                log::info!("Open file {:?}", open_file);
                             }
-                        }
-
-                         */
-                    });
 
-                    let close_button = ui.button("X");
-                    if close_button.clicked() {
-                        panic!("Closed Window");
-                    }
-                });
-            });
+                            /* Maybe use this instead of egui buildin color picker?
+                            if ui.button("color").clicked() {
+                                let color: String;
+                                match tinyfiledialogs::color_chooser_dialog("Choose a Color", tinyfiledialogs::DefaultColorValue::Hex("#FF0000")) {
+                                    Some((hex_result, _rgb)) => color = hex_result,
+                                    None => color = "null".to_string(),
+                                }
+                            }
 
-            egui::SidePanel::left("my_side_panel").show(egui.ctx(), |ui| {
-                ui.heading("Hello World!");
-                if ui.button("Quit").clicked() {
-                    quit = true;
-                }
+                             */
+                        });
 
-                egui::ComboBox::from_label("Version")
-                    .width(150.0)
-                    .selected_text("Moin leude so ne box hier")
-                    .show_ui(ui, |ui| {
-                        ui.label("Keine Ahnung");
+                        if chrome.title_bar(ui, display.gl_window().window()) {
+                            quit = true;
+                        }
                     });
+                });
 
-                egui::CollapsingHeader::new("Dev")
-                    .default_open(true)
-                    .show(ui, |ui| {
-                        ui.label("contains");
-                        if ui.button("Hi").double_clicked() {
-                            println!("Hi");
-                        };
+                egui::SidePanel::left("my_side_panel").show(egui.ctx(), |ui| {
+                    ui.horizontal(|ui| {
+                        ui.heading("Outliner");
+                        if ui.button("Quit").clicked() {
+                            quit = true;
+                        }
                     });
-            });
+                    outliner.show(ui, &mut scene, &mut selection, &mut history);
+                    ui.separator();
 
-            egui::Window::new("Editor")
-                .scroll(false)
-                .resizable(true)
-                .show(egui.ctx(), |ui| {
-                    ui.code_editor(&mut code);
-                });
-
-            egui::Window::new("Teekanne")
-                .scroll(false)
-                .default_size(vec2(200.0, 256.0))
-                .show(egui.ctx(), |ui| {
-                    egui::CollapsingHeader::new("Location")
+                    egui::CollapsingHeader::new("Gizmo")
                         .default_open(true)
                         .show(ui, |ui| {
-                            // Ui for the teacan location
-                            ui.add(Slider::new(&mut x_location, -2.0..=2.0).text("X")).dragged();
-                            ui.add(Slider::new(&mut y_location, -2.0..=2.0).text("Y")).dragged();
-                            ui.add(Slider::new(&mut z_location, -2.0..=2.0).text("Z")).dragged();
-                        });
-                    egui::CollapsingHeader::new("Rotation")
-                        .default_open(false)
-                        .show(ui, |ui| {
-                            ui.add(Slider::new(&mut x_rotation, -2.0..=2.0).text("X")).dragged();
-                            ui.add(Slider::new(&mut y_rotation, -2.0..=2.0).text("Y")).dragged();
-                            ui.add(Slider::new(&mut z_rotation, -2.0..=2.0).text("Z")).dragged();
+                            ui.horizontal(|ui| {
+                                ui.selectable_value(&mut gizmo.mode, GizmoMode::Translate, "Move");
+                                ui.selectable_value(&mut gizmo.mode, GizmoMode::Rotate, "Rotate");
+                                ui.selectable_value(&mut gizmo.mode, GizmoMode::Scale, "Scale");
+                            });
+                            ui.horizontal(|ui| {
+                                ui.selectable_value(&mut gizmo.space, GizmoSpace::World, "World");
+                                ui.selectable_value(&mut gizmo.space, GizmoSpace::Local, "Local");
+                            });
+                            ui.checkbox(&mut gizmo.snap, "Snap");
+                            if gizmo.snap {
+                                ui.add(egui::DragValue::new(&mut gizmo.translate_step).speed(0.01).clamp_range(0.0..=f32::MAX).prefix("move: "));
+                                ui.add(egui::DragValue::new(&mut gizmo.rotate_step).speed(1.0).clamp_range(0.0..=360.0).prefix("rotate: ").suffix("°"));
+                                ui.add(egui::DragValue::new(&mut gizmo.scale_step).speed(0.01).clamp_range(0.0..=f32::MAX).prefix("scale: "));
+                            }
                         });
 
-                    egui::CollapsingHeader::new("size")
-                        .default_open(false)
+                    egui::CollapsingHeader::new("Dev")
+                        .default_open(true)
                         .show(ui, |ui| {
-                            ui.add(Slider::new(&mut x_size, -1.0..=1.0).text("X")).dragged();
-                            ui.add(Slider::new(&mut y_size, -1.0..=1.0).text("Y")).dragged();
-                            ui.add(Slider::new(&mut z_size, -1.0..=1.0).text("Z")).dragged();
-                        });
+                            ui.label("contains");
+                            if ui.button("Console").clicked() {
+                                console.toggle();
+                            }
+                            let mut show_stats = console.registry.bool("r_stats");
+                            if ui.checkbox(&mut show_stats, "Render stats").changed() {
+                                // the variable is there, setting it can't fail
+                                let _ = console.registry.set("r_stats", if show_stats { "1" } else { "0" });
+                            }
+
+                            if simulation.is_recording() {
+                                if ui.button("Stop recording").clicked() {
+                                    if let Some(recording) = simulation.stop_recording() {
+                                        if let Some(path) = tinyfiledialogs::save_file_dialog("Save recording", "recording.ron") {
+                                            if let Err(err) = recording.save(&path) {
+                                                toasts.error(format!("Could not save recording {}: {}", path, err));
+                                            }
+                                        }
+                                    }
+                                }
+                            } else if ui.button("Record input").clicked() {
+                                simulation.start_recording(&scene);
+                            }
+
+                            let capture_text = if capture.is_capturing_frames() { "Stop capturing frames" } else { "Capture frames" };
+                            if ui.button(capture_text).on_hover_text("F9, the frames go to the captures folder").clicked() {
+                                toggle_frame_capture(&mut capture, &console, &mut toasts);
+                            }
 
-                    ui.color_edit_button_srgba(&mut color_picker_color);
+                            if ui.button("Replay input").clicked() {
+                                if let Some(path) = tinyfiledialogs::open_file_dialog("Replay recording", "", None) {
+                                    match Recording::load(&path) {
+                                        Ok(recording) => {
+                                            simulation.start_playback(recording, &mut scene);
+                                            history.clear();
+                                        }
+                                        Err(err) => toasts.error(format!("Could not load recording {}: {}", path, err)),
+                                    }
+                                }
+                            }
+                            if simulation.is_playing() {
+                                ui.label(format!("Replaying frame {}", simulation.frame));
+                            }
+
+                            ui.collapsing("Physics events", |ui| {
+                                for (frame, event) in simulation.recent_events.iter().rev() {
+                                    ui.label(format!("{}: {:?}", frame, event));
+                                }
+                            });
+
+                            ui.collapsing("Virtual gamepad", |ui| match virtual_gamepad {
+                                None => {
+                                    if ui.button("Connect").clicked() {
+                                        virtual_gamepad = Some(virtual_gamepads.connect());
+                                    }
+                                }
+                                Some(id) => {
+                                    if ui.button("Disconnect").clicked() {
+                                        virtual_gamepads.disconnect(id);
+                                        virtual_gamepad = None;
+                                        virtual_stick = [0.0; 2];
+                                        virtual_south = false;
+                                    }
+                                    if ui.add(Slider::new(&mut virtual_stick[0], -1.0..=1.0).text("Left stick X")).changed() {
+                                        virtual_gamepads.set_axis(id, GamepadAxis::LeftStickX, virtual_stick[0]);
+                                    }
+                                    if ui.add(Slider::new(&mut virtual_stick[1], -1.0..=1.0).text("Left stick Y")).changed() {
+                                        virtual_gamepads.set_axis(id, GamepadAxis::LeftStickY, virtual_stick[1]);
+                                    }
+                                    if ui.checkbox(&mut virtual_south, "South").changed() {
+                                        if virtual_south {
+                                            virtual_gamepads.press(id, GamepadButton::South);
+                                        } else {
+                                            virtual_gamepads.release(id, GamepadButton::South);
+                                        }
+                                    }
+                                }
+                            });
+                        });
                 });
 
-            let (_needs_repaint, shapes) = egui.end_frame(&display);
-            /*
-            *control_flow = if quit {
-                glutin::event_loop::ControlFlow::Exit
-            } else if needs_repaint {
-                glutin::event_loop::ControlFlow::Poll
-            } else {
-                glutin::event_loop::ControlFlow::Wait
-            };
-            */
-
-            let next_frame_time = std::time::Instant::now() +
-                std::time::Duration::from_nanos(16_666_667);
-             //*control_flow = glutin::event_loop::ControlFlow::WaitUntil(next_frame_time);
-            
-                display.gl_window().window().request_redraw();
+                egui::Window::new("Editor")
+                    .scroll(false)
+                    .resizable(true)
+                    .show(egui.ctx(), |ui| {
+                        ui.code_editor(&mut code);
+                    });
 
-            {
-                use glium::Surface as _;
-                let mut target = display.draw();
-            
-                target.clear_color_srgb_and_depth(
-                    (
-                        BACKGROUND_COLOR.get_glfloat_red(),
-                        BACKGROUND_COLOR.get_glfloat_green(),
-                        BACKGROUND_COLOR.get_glfloat_blue(),
-                        1.0,
-                    ),
-                    1.0 // alpha
-                );
+                let inspector_title = match selection.primary().and_then(|id| scene.node(id)) {
+                    Some(node) => node.name.clone(),
+                    None => "Nothing selected".to_string(),
+                };
+                egui::Window::new(inspector_title)
+                    .id(egui::Id::new("inspector"))
+                    .scroll(false)
+                    .default_size(vec2(200.0, 256.0))
+                    .show(egui.ctx(), |ui| {
+                        let node = match selection.primary().and_then(|id| scene.node_mut(id)) {
+                            Some(node) => node,
+                            None => {
+                                ui.label("Click something in the viewport to select it");
+                                return;
+                            }
+                        };
+                        let id = node.id;
+                        let before = node.clone();
+                        node.inspect_fields(ui);
+                        history.record_change(&before, node);
+                        prefab::show(ui, &mut scene, &mut history, &mut prefabs, &mut toasts, id);
+                    });
 
-                /*
-                let model = [
-                        [0.01, 0.0,  0.0,   0.0],
-                        [0.0,  0.01, 0.0,  0.0],
-                        [0.0,  0.0,  0.01,  0.0],
-                        // X, Y, Z location
-                        [x_location, y_location, z_location, 1.0f32],
-                ];
-
-                 */
-
-
-                // Very Unefficient code but idc for now
-                let mut model_math = glam::f32::Mat4::from_cols_array_2d(
-                    &[
-                        [x_size, 0.0,  0.0,   0.0],
-                        [0.0,  y_size, 0.0,  0.0],
-                        [0.0,  0.0,  z_size,  0.0],
-                        // X, Y, Z location
-                        [x_location, y_location, z_location, 1.0f32],
-                    ]
-                );
+                egui::Window::new("History")
+                    .default_size(vec2(150.0, 200.0))
+                    .show(egui.ctx(), |ui| {
+                        let mut jump = None;
+                        if ui.selectable_label(history.position() == 0, "Start").clicked() {
+                            jump = Some(0);
+                        }
+                        for (index, label) in history.labels().enumerate() {
+                            // undone entries stay around until they are redone or replaced by a new edit
+                            let text = if index < history.position() {
+                                label.to_string()
+                            } else {
+                                format!("{} (undone)", label)
+                            };
+                            if ui.selectable_label(history.position() == index + 1, text).clicked() {
+                                jump = Some(index + 1);
+                            }
+                        }
+                        if let Some(position) = jump {
+                            history.jump_to(position, &mut scene);
+                        }
+                    });
 
+                egui::Window::new("Assets")
+                    .default_size(vec2(400.0, 200.0))
+                    .show(egui.ctx(), |ui| {
+                        dropped = asset_browser.show(ui);
+                    });
 
-                model_math = model_math * glam::f32::Mat4::from_rotation_x(x_rotation);
-                model_math = model_math * glam::f32::Mat4::from_rotation_y(y_rotation);
-                model_math = model_math * glam::f32::Mat4::from_rotation_z(z_rotation);
+                egui::Window::new("Log")
+                    .default_size(vec2(500.0, 200.0))
+                    .show(egui.ctx(), |ui| {
+                        log_viewer.show(ui, &log);
+                    });
 
-                let model = model_math.to_cols_array_2d();
+                settings.show(egui.ctx(), &mut toasts);
+            }
 
-                let perspective = {
-                    let (width, height) = target.get_dimensions();
-                    let aspect_ratio = height as f32 / width as f32;
+            console.show(egui.ctx(), &mut console::Context {
+                scene: &mut scene,
+                history: &mut history,
+                selection: &mut selection,
+                simulation: &mut simulation,
+            });
 
-                    let fov: f32 = std::f32::consts::PI / 3.0;
-                    let zfar = 1024.0;
-                    let znear = 0.1;
+            if console.registry.bool("r_stats") {
+                render_stats.show(egui.ctx(), frame_time);
+            }
+            toasts.show(egui.ctx());
 
-                    let f = 1.0 / (fov / 2.0).tan();
+            let (_needs_repaint, shapes) = egui.end_frame(&display);
 
-                    [
-                        [f *   aspect_ratio   ,    0.0,              0.0              ,   0.0],
-                        [         0.0         ,     f ,              0.0              ,   0.0],
-                        [         0.0         ,    0.0,  (zfar+znear)/(zfar-znear)    ,   1.0],
-                        [         0.0         ,    0.0, -(2.0*zfar*znear)/(zfar-znear),   0.0],
-                    ]
-                };   
+            if let (Some(asset), Some(viewport)) = (dropped, &viewport) {
+                let ray = viewport.ray(cursor_position);
+                if let Err(err) = assets::place(&asset, &ray, &mut scene, &mut history, &mut selection, &mut simulation.meshes, &mut prefabs) {
+                    toasts.warn(format!("Could not place {}: {}", asset.name(), err));
+                }
+            }
 
-                let light = [-1.0, 0.4, 0.9f32];
-                let color = [
-                    color_picker_color[0] as f32 / 250.0,
-                    color_picker_color[1] as f32 / 250.0,
-                    color_picker_color[2] as f32 / 250.0,
-                    color_picker_color[3] as f32 / 250.0,
-                ];
+            // dragging a slider or typing into a field is one step in the history,
+            // it ends once the drag is released or the field lost focus
+            if !egui.ctx().is_using_pointer() && !egui.ctx().wants_keyboard_input() {
+                history.seal();
+            }
 
-                let params = glium::DrawParameters {
-                    depth: glium::Depth {
-                        test: glium::draw_parameters::DepthTest::IfLess,
-                        write: true,
-                        .. Default::default()
-                    },
-                    blend: glium::draw_parameters::Blend::alpha_blending(),
-                    backface_culling: glium::draw_parameters::BackfaceCullingMode::CullClockwise,
-                        .. Default::default()
-                };
-                
-                let view = view_matrix(&[2.0, -1.0, 1.0], &[-2.0, 1.0, 1.0], &[0.0, 1.0, 0.0]);
-                ////let view = view_matrix(&[2.01, 0.0, 0.0], &[2.0, 0.01, 0.0], &[2.0, 0.0, 0.01]);
-
-                target
-                    .draw(
-                        (&positions, &normals),
-                        &indices,
-                        &program,
-                        &uniform! { model: model, view: view, perspective: perspective, u_light: light, rgba: color },
-                        &params,
-                    )
-                    .unwrap();
+            for event in gamepads.poll().into_iter().chain(virtual_gamepads.poll()) {
+                match event {
+                    GamepadEvent::Connected(id) => log::info!("Gamepad {} connected", id.0),
+                    GamepadEvent::Disconnected(id) => log::info!("Gamepad {} disconnected", id.0),
+                    _ => {}
+                }
+                simulation.push_event(InputEvent::Gamepad(event));
+            }
+
+            let now = Instant::now();
+            let timescale = console.registry.float("timescale").unwrap_or(1.0).max(0.0);
+            let elapsed = capture.frame_time().unwrap_or(now - last_update);
+            simulation.update(&mut scene, elapsed.mul_f32(timescale));
+            frame_time = now - last_update;
+            last_update = now;
+            if quit {
+                *control_flow = glutin::event_loop::ControlFlow::Exit;
+            }
+
+            display.gl_window().window().request_redraw();
+
+            {
+                use glium::Surface as _;
+                let mut target = display.draw();
+            
+                let (width, height) = target.get_dimensions();
+                let current_viewport = Viewport::new(
+                    render::perspective(width, height),
+                    render::scene_view(&scene),
+                    Vec2::new(width as f32, height as f32),
+                );
+                viewport = Some(current_viewport);
+
+                if let Err(err) = gpu_meshes.sync(&display, &simulation.meshes) {
+                    toasts.error(err);
+                }
+                let wireframe = console.registry.bool("r_wireframe");
+                match renderer.draw(&display, &mut target, &scene, &gpu_meshes, &current_viewport, wireframe) {
+                    Ok(stats) => render_stats = stats,
+                    Err(err) => toasts.error(err),
+                }
                 // draw things behind egui here
 
-                if gui_is_active {
+                if let Some(node) = selection.primary().and_then(|id| scene.node(id)).filter(|node| !node.locked && editor) {
+                    let lines = gizmo.lines(&scene.world_transform(node), &current_viewport);
+                    if let Err(err) = line_renderer.draw(&display, &mut target, &lines, &current_viewport) {
+                        toasts.error(err);
+                    }
+                }
+
+                if gui_is_active && !capture.hides_overlay() {
                     egui.paint(&display, &mut target, shapes);
                 }
 
                 // draw things on top of egui here
 
-                target.finish().unwrap();
+                match capture.frame_drawn(&display, &target) {
+                    Ok(Some(path)) => toasts.info(format!("Saved {}", path.display())),
+                    Ok(None) => {}
+                    Err(err) => toasts.error(err),
+                }
+                // the frame has to be finished even when drawing failed
+                if let Err(err) = target.finish().rendering("showing the frame") {
+                    toasts.error(err);
+                }
             }
         };
 
@@ -331,35 +510,119 @@ fn main() {
             // See: https://github.com/rust-windowing/winit/issues/1619
             glutin::event::Event::RedrawEventsCleared if cfg!(windows) => redraw(),
             glutin::event::Event::RedrawRequested(_) if !cfg!(windows) => redraw(),
-            glutin::event::Event::DeviceEvent { event, device_id } => match event {
-                glutin::event::DeviceEvent::Key(kin) => {
-                    //println!("DeviceEvent Key: {:?} DeviceId: {:?}", kin, device_id);
-                    let keycode = kin.virtual_keycode.unwrap();
+            glutin::event::Event::LoopDestroyed => chrome.save(),
+            glutin::event::Event::DeviceEvent { event: glutin::event::DeviceEvent::Key(kin), .. } => {
+                let keycode = match kin.virtual_keycode {
+                    Some(keycode) => keycode,
+                    None => return,
+                };
 
-                    use glutin::event::VirtualKeyCode;
-                    use glutin::event::ElementState;
+                use glutin::event::VirtualKeyCode;
+                use glutin::event::ElementState;
 
-                    if kin.state == ElementState::Released {
-                        return;
-                    }
+                match kin.state {
+                    // typing into the console or a text field shouldn't move anything or end up in recordings,
+                    // releases still go through so no key stays held
+                    ElementState::Pressed if egui.ctx().wants_keyboard_input() => {}
+                    ElementState::Pressed => simulation.push_event(InputEvent::KeyPressed(keycode)),
+                    ElementState::Released => simulation.push_event(InputEvent::KeyReleased(keycode)),
+                }
 
-                    match keycode {
-                        VirtualKeyCode::Escape => gui_is_active = !gui_is_active,
-                        VirtualKeyCode::W => println!("w"),
-                        VirtualKeyCode::A => println!("A"),
-                        VirtualKeyCode::S => println!("S"),
-                        VirtualKeyCode::D => println!("D"),
-                        VirtualKeyCode::Space => println!("Juump1!"),
-                        VirtualKeyCode::LShift => println!("Shiiift"),
-                        _ => println!("Not Escape :(")
-                    }
+                if kin.state == ElementState::Released {
+                    return;
+                }
 
-                },
-                _ => {},
-            },
+                match keycode {
+                    VirtualKeyCode::Escape => gui_is_active = !gui_is_active,
+                    // the character controller picks these up through the action map
+                    VirtualKeyCode::W
+                    | VirtualKeyCode::A
+                    | VirtualKeyCode::S
+                    | VirtualKeyCode::D
+                    | VirtualKeyCode::Space
+                    | VirtualKeyCode::LShift => {}
+                    _ => log::trace!("Key {:?} pressed", keycode),
+                }
+            }
 
             glutin::event::Event::WindowEvent { event, .. } => {
-
+                use glutin::event::{ElementState, KeyboardInput, MouseButton, VirtualKeyCode, WindowEvent};
+
+                match event {
+                    WindowEvent::CloseRequested => *control_flow = glutin::event_loop::ControlFlow::Exit,
+                    // editor shortcuts, unlike device events these only arrive while the window has focus
+                    WindowEvent::KeyboardInput {
+                        input: KeyboardInput { state: ElementState::Pressed, virtual_keycode: Some(keycode), .. },
+                        ..
+                    } => match keycode {
+                        // while typing the console closes itself, other fields just get the character
+                        VirtualKeyCode::Grave if !egui.ctx().wants_keyboard_input() => console.toggle(),
+                        // shift leaves the editor out of the picture
+                        VirtualKeyCode::F12 => capture.screenshot(!modifiers.shift()),
+                        VirtualKeyCode::F9 => toggle_frame_capture(&mut capture, &console, &mut toasts),
+                        VirtualKeyCode::Z if modifiers.ctrl() && !egui.ctx().wants_keyboard_input() => {
+                            if modifiers.shift() {
+                                history.redo(&mut scene);
+                            } else {
+                                history.undo(&mut scene);
+                            }
+                        }
+                        VirtualKeyCode::Delete if !egui.ctx().wants_keyboard_input() => {
+                            history.remove_nodes(&mut scene, selection.ids());
+                            selection.retain_existing(&scene);
+                        }
+                        _ => {}
+                    },
+                    WindowEvent::CursorMoved { position, .. } => {
+                        cursor_position = Vec2::new(position.x as f32, position.y as f32);
+                        let node = selection.primary().and_then(|id| scene.node(id)).filter(|node| !node.locked);
+                        if let (Some(viewport), Some(node)) = (&viewport, node) {
+                            // the gizmo works in world space, the node keeps its transform relative to its parent
+                            let parent = scene.parent_transform(node);
+                            let mut world = parent.child_to_world(&node.transform);
+                            if gizmo.is_dragging() {
+                                gizmo.drag(&mut world, viewport, cursor_position);
+                                let id = node.id;
+                                scene.node_mut(id).unwrap().transform = parent.world_to_child(&world);
+                            } else {
+                                gizmo.hover(&world, viewport, cursor_position);
+                            }
+                        }
+                    }
+                    WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Left, .. }
+                        if editor && !(gui_is_active && egui.ctx().wants_pointer_input()) =>
+                    {
+                        if let Some(viewport) = &viewport {
+                            let grabbed = match selection.primary().and_then(|id| scene.node(id)).filter(|node| !node.locked) {
+                                Some(node) if gizmo.begin(&scene.world_transform(node), viewport, cursor_position) => {
+                                    gizmo_before = Some(node.clone());
+                                    true
+                                }
+                                _ => false,
+                            };
+                            if !grabbed {
+                                let ray = viewport.ray(cursor_position);
+                                let hit = scene.raycast(&ray, &simulation.meshes).map(|(id, _)| id);
+                                match hit {
+                                    Some(id) if modifiers.ctrl() => selection.toggle(id),
+                                    hit if !modifiers.ctrl() => selection.set(hit),
+                                    _ => {}
+                                }
+                            }
+                        }
+                    }
+                    WindowEvent::MouseInput { state: ElementState::Released, button: MouseButton::Left, .. } => {
+                        gizmo.end();
+                        if let Some(before) = gizmo_before.take() {
+                            if let Some(after) = scene.node_mut(before.id) {
+                                history.record_change(&before, after);
+                                history.seal();
+                            }
+                        }
+                    }
+                    WindowEvent::ModifiersChanged(state) => modifiers = state,
+                    _ => {}
+                }
 
 
             egui.on_event(&event/*, control_flow*/);
@@ -371,37 +634,22 @@ fn main() {
     });
 }
 
+/// Ends the editor for errors it can't go on without
+fn exit_with(error: Error) -> ! {
+    log::error!("{}", error);
+    log::logger().flush();
+    std::process::exit(1);
+}
 
-fn view_matrix(position: &[f32; 3], direction: &[f32; 3], up: &[f32; 3]) -> [[f32; 4]; 4] {
-    let f = {
-        let f = direction;
-        let len = f[0] * f[0] + f[1] * f[1] + f[2] * f[2];
-        let len = len.sqrt();
-        [f[0] / len, f[1] / len, f[2] / len]
-    };
-
-    let s = [up[1] * f[2] - up[2] * f[1],
-             up[2] * f[0] - up[0] * f[2],
-             up[0] * f[1] - up[1] * f[0]];
-
-    let s_norm = {
-        let len = s[0] * s[0] + s[1] * s[1] + s[2] * s[2];
-        let len = len.sqrt();
-        [s[0] / len, s[1] / len, s[2] / len]
-    };
-
-    let u = [f[1] * s_norm[2] - f[2] * s_norm[1],
-             f[2] * s_norm[0] - f[0] * s_norm[2],
-             f[0] * s_norm[1] - f[1] * s_norm[0]];
-
-    let p = [-position[0] * s_norm[0] - position[1] * s_norm[1] - position[2] * s_norm[2],
-             -position[0] * u[0] - position[1] * u[1] - position[2] * u[2],
-             -position[0] * f[0] - position[1] * f[1] - position[2] * f[2]];
-
-    [
-        [s_norm[0], u[0], f[0], 0.0],
-        [s_norm[1], u[1], f[1], 0.0],
-        [s_norm[2], u[2], f[2], 0.0],
-        [p[0], p[1], p[2], 1.0],
-    ]
-}
\ No newline at end of file
+fn toggle_frame_capture(capture: &mut Capture, console: &Console, toasts: &mut Toasts) {
+    if capture.is_capturing_frames() {
+        let frames = capture.stop_frames();
+        toasts.info(format!("Captured {} frames", frames));
+        return;
+    }
+    let fps = console.registry.float("capture_fps").unwrap_or(60.0);
+    match capture.start_frames(fps) {
+        Ok(dir) => toasts.info(format!("Capturing frames to {}", dir.display())),
+        Err(err) => toasts.error(err),
+    }
+}
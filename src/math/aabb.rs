@@ -0,0 +1,91 @@
+use glam::f32::{Mat4, Vec3};
+use serde::{Deserialize, Serialize};
+
+/// Axis aligned bounding box
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_center(center: Vec3, half_extents: Vec3) -> Self {
+        Self::new(center - half_extents, center + half_extents)
+    }
+
+    /// Smallest box around all `points`, `None` if there are none
+    pub fn from_points<I: IntoIterator<Item = Vec3>>(points: I) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Self::new(first, first), |aabb, point| {
+            Self::new(aabb.min.min(point), aabb.max.max(point))
+        }))
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.cmple(other.max).all() && other.min.cmple(self.max).all()
+    }
+
+    pub fn closest_point(&self, point: Vec3) -> Vec3 {
+        point.max(self.min).min(self.max)
+    }
+
+    pub fn corners(&self) -> [Vec3; 8] {
+        let (min, max) = (self.min, self.max);
+        [
+            Vec3::new(min.x, min.y, min.z),
+            Vec3::new(max.x, min.y, min.z),
+            Vec3::new(min.x, max.y, min.z),
+            Vec3::new(max.x, max.y, min.z),
+            Vec3::new(min.x, min.y, max.z),
+            Vec3::new(max.x, min.y, max.z),
+            Vec3::new(min.x, max.y, max.z),
+            Vec3::new(max.x, max.y, max.z),
+        ]
+    }
+
+    /// Box around this one after `matrix`, bigger than needed once it's rotated
+    pub fn transformed(&self, matrix: Mat4) -> Self {
+        Self::from_points(self.corners().iter().map(|&corner| matrix.transform_point3(corner)))
+            .expect("a box has corners")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::f32::Quat;
+
+    #[test]
+    fn transformed_boxes_hold_every_corner() {
+        let aabb = Aabb::new(Vec3::new(-1.0, -2.0, -1.0), Vec3::new(1.0, 2.0, 1.0));
+        let matrix = Mat4::from_scale_rotation_translation(
+            Vec3::splat(2.0),
+            Quat::from_rotation_y(std::f32::consts::FRAC_PI_4),
+            Vec3::new(10.0, 0.0, 0.0),
+        );
+        let transformed = aabb.transformed(matrix);
+        let reach = 2.0 * std::f32::consts::SQRT_2;
+        assert!(transformed.min.abs_diff_eq(Vec3::new(10.0 - reach, -4.0, -reach), 1e-5), "{:?}", transformed);
+        assert!(transformed.max.abs_diff_eq(Vec3::new(10.0 + reach, 4.0, reach), 1e-5), "{:?}", transformed);
+        for corner in aabb.corners().iter().map(|&corner| matrix.transform_point3(corner)) {
+            assert_eq!(transformed.closest_point(corner), corner);
+        }
+    }
+
+    #[test]
+    fn touching_boxes_intersect() {
+        let a = Aabb::new(Vec3::ZERO, Vec3::ONE);
+        assert!(a.intersects(&Aabb::new(Vec3::ONE, Vec3::splat(2.0))));
+        assert!(!a.intersects(&Aabb::new(Vec3::new(1.1, 0.0, 0.0), Vec3::splat(2.0))));
+        assert_eq!(Aabb::from_points(Vec::new()), None);
+    }
+}
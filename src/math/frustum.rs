@@ -0,0 +1,96 @@
+use crate::math::{Aabb, Sphere};
+use glam::f32::{Mat4, Vec3, Vec4};
+
+/// What a camera can see, as six planes with their normals pointing inwards
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frustum {
+    /// `xyz` is the normal and `w` the distance, a point is inside when `normal·point + w >= 0`
+    planes: [Vec4; 6],
+}
+
+impl Frustum {
+    /// From `perspective * view`, the planes come out in world space
+    pub fn from_matrix(matrix: Mat4) -> Self {
+        let (x, y, z, w) = (matrix.row(0), matrix.row(1), matrix.row(2), matrix.row(3));
+        let planes = [w + x, w - x, w + y, w - y, w + z, w - z];
+        Self {
+            planes: planes.map(|plane| plane / plane.truncate().length()),
+        }
+    }
+
+    pub fn intersects_sphere(&self, sphere: &Sphere) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.truncate().dot(sphere.center) + plane.w >= -sphere.radius)
+    }
+
+    /// Only `false` when the box is entirely behind one of the planes, so a box beside a corner still counts
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            let normal = plane.truncate();
+            // the corner furthest along the normal
+            let corner = Vec3::select(normal.cmpge(Vec3::ZERO), aabb.max, aabb.min);
+            normal.dot(corner) + plane.w >= 0.0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render;
+
+    /// Looking from the origin along +Z, 60° high and 4:3 wide.
+    /// 10 in front of the camera the sides are at about x = ±7.7 and y = ±5.8.
+    fn frustum() -> Frustum {
+        let view = Mat4::from_cols_array_2d(&render::view_matrix(&[0.0, 0.0, 0.0], &[0.0, 0.0, 1.0], &[0.0, 1.0, 0.0]));
+        Frustum::from_matrix(render::perspective(800, 600) * view)
+    }
+
+    fn sphere(x: f32, y: f32, z: f32, radius: f32) -> Sphere {
+        Sphere::new(Vec3::new(x, y, z), radius)
+    }
+
+    #[test]
+    fn spheres_inside_and_outside() {
+        let frustum = frustum();
+        assert!(frustum.intersects_sphere(&sphere(0.0, 0.0, 10.0, 1.0)));
+        assert!(frustum.intersects_sphere(&sphere(6.0, -4.0, 10.0, 0.5)));
+        // behind the camera, past the far plane, beside and above
+        assert!(!frustum.intersects_sphere(&sphere(0.0, 0.0, -5.0, 1.0)));
+        assert!(!frustum.intersects_sphere(&sphere(0.0, 0.0, 1100.0, 1.0)));
+        assert!(!frustum.intersects_sphere(&sphere(20.0, 0.0, 10.0, 9.0)));
+        assert!(!frustum.intersects_sphere(&sphere(0.0, 12.0, 10.0, 1.0)));
+    }
+
+    #[test]
+    fn spheres_across_a_plane_are_kept() {
+        let frustum = frustum();
+        // the center is outside, the sphere reaches in past the right plane
+        assert!(frustum.intersects_sphere(&sphere(20.0, 0.0, 10.0, 10.5)));
+        // around the camera, cut by the near plane
+        assert!(frustum.intersects_sphere(&sphere(0.0, 0.0, -0.5, 1.0)));
+    }
+
+    #[test]
+    fn boxes_inside_outside_and_across() {
+        let frustum = frustum();
+        let aabb = |min: [f32; 3], max: [f32; 3]| Aabb::new(Vec3::from(min), Vec3::from(max));
+        assert!(frustum.intersects_aabb(&aabb([-1.0, -1.0, 9.0], [1.0, 1.0, 11.0])));
+        assert!(!frustum.intersects_aabb(&aabb([20.0, -1.0, 9.0], [22.0, 1.0, 11.0])));
+        assert!(!frustum.intersects_aabb(&aabb([-1.0, -1.0, -1.0], [1.0, 1.0, 0.05])));
+        assert!(!frustum.intersects_aabb(&aabb([-1.0, -1.0, 1100.0], [1.0, 1.0, 1200.0])));
+        // across the left plane and across the near plane
+        assert!(frustum.intersects_aabb(&aabb([-9.0, -1.0, 9.0], [-7.0, 1.0, 11.0])));
+        assert!(frustum.intersects_aabb(&aabb([-1.0, -1.0, -1.0], [1.0, 1.0, 1.0])));
+    }
+
+    #[test]
+    fn planes_follow_the_camera() {
+        // turned around to look along -Z from further back
+        let view = Mat4::from_cols_array_2d(&render::view_matrix(&[0.0, 0.0, 20.0], &[0.0, 0.0, -1.0], &[0.0, 1.0, 0.0]));
+        let frustum = Frustum::from_matrix(render::perspective(800, 600) * view);
+        assert!(frustum.intersects_sphere(&sphere(0.0, 0.0, 10.0, 1.0)));
+        assert!(!frustum.intersects_sphere(&sphere(0.0, 0.0, 25.0, 1.0)));
+    }
+}
@@ -0,0 +1,15 @@
+mod aabb;
+mod closest;
+mod frustum;
+mod obb;
+mod ray;
+mod sphere;
+mod viewport;
+
+pub use aabb::*;
+pub use closest::*;
+pub use frustum::*;
+pub use obb::*;
+pub use ray::*;
+pub use sphere::*;
+pub use viewport::*;
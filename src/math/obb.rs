@@ -0,0 +1,53 @@
+use crate::math::Aabb;
+use glam::f32::{Quat, Vec3};
+
+/// Box that turns with its node, unlike `Aabb`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Obb {
+    pub center: Vec3,
+    pub rotation: Quat,
+    pub half_extents: Vec3,
+}
+
+impl Obb {
+    pub fn new(center: Vec3, rotation: Quat, half_extents: Vec3) -> Self {
+        Self {
+            center,
+            rotation,
+            half_extents,
+        }
+    }
+
+    /// Directions of the box's x, y and z edges
+    pub fn axes(&self) -> [Vec3; 3] {
+        [self.rotation * Vec3::X, self.rotation * Vec3::Y, self.rotation * Vec3::Z]
+    }
+
+    /// `point` relative to the center, along the box's axes
+    pub fn to_local(self, point: Vec3) -> Vec3 {
+        self.rotation.inverse() * (point - self.center)
+    }
+
+    /// The box before it was turned and moved to its center
+    pub fn local_box(&self) -> Aabb {
+        Aabb::from_center(Vec3::ZERO, self.half_extents)
+    }
+
+    /// Half the length of the box's shadow on `axis`
+    pub fn projected_radius(&self, axis: Vec3) -> f32 {
+        self.axes()
+            .iter()
+            .zip(self.half_extents.to_array())
+            .map(|(edge, half_extent)| edge.dot(axis).abs() * half_extent)
+            .sum()
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        let extents = Vec3::new(
+            self.projected_radius(Vec3::X),
+            self.projected_radius(Vec3::Y),
+            self.projected_radius(Vec3::Z),
+        );
+        Aabb::from_center(self.center, extents)
+    }
+}
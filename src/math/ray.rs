@@ -0,0 +1,142 @@
+use crate::math::Aabb;
+use glam::f32::{Mat4, Vec3, Vec4};
+
+/// Half line starting at `origin`. `direction` doesn't have to be normalized,
+/// distances along the ray are measured in multiples of it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub direction: Vec3,
+}
+
+impl Ray {
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Self { origin, direction }
+    }
+
+    /// Ray through a point on the screen, from the near plane towards the far plane.
+    /// `x` and `y` are normalized device coordinates from -1 to 1 with y pointing up.
+    pub fn unproject(x: f32, y: f32, perspective: Mat4, view: Mat4) -> Self {
+        let inverse = (perspective * view).inverse();
+        let unproject = |z: f32| {
+            let point = inverse * Vec4::new(x, y, z, 1.0);
+            point.truncate() / point.w
+        };
+        let near = unproject(-1.0);
+        let far = unproject(1.0);
+        Self::new(near, far - near)
+    }
+
+    /// The same ray in the space `matrix` transforms into.
+    /// Distances stay the same since the direction isn't normalized again.
+    pub fn transformed(&self, matrix: Mat4) -> Self {
+        Self::new(matrix.transform_point3(self.origin), matrix.transform_vector3(self.direction))
+    }
+
+    /// Distance to where the ray enters `aabb`, 0 if it starts inside
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+        let inverse = self.direction.recip();
+        let to_min = (aabb.min - self.origin) * inverse;
+        let to_max = (aabb.max - self.origin) * inverse;
+        let enter = to_min.min(to_max).max_element().max(0.0);
+        let exit = to_min.max(to_max).min_element();
+        // NaN from a zero direction component on a face fails both comparisons
+        if enter <= exit {
+            Some(enter)
+        } else {
+            None
+        }
+    }
+
+    /// Distance to where the ray hits `triangle`, from either side
+    pub fn intersect_triangle(&self, [a, b, c]: [Vec3; 3]) -> Option<f32> {
+        let edge_ab = b - a;
+        let edge_ac = c - a;
+        let p = self.direction.cross(edge_ac);
+        let determinant = edge_ab.dot(p);
+        if determinant.abs() < f32::EPSILON {
+            // parallel to the triangle
+            return None;
+        }
+        let inverse = 1.0 / determinant;
+
+        let to_origin = self.origin - a;
+        let u = to_origin.dot(p) * inverse;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = to_origin.cross(edge_ab);
+        let v = self.direction.dot(q) * inverse;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let distance = edge_ac.dot(q) * inverse;
+        if distance >= 0.0 {
+            Some(distance)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRIANGLE: [Vec3; 3] = [
+        glam::const_vec3!([0.0, 0.0, 0.0]),
+        glam::const_vec3!([1.0, 0.0, 0.0]),
+        glam::const_vec3!([0.0, 1.0, 0.0]),
+    ];
+
+    #[test]
+    fn triangles_are_hit_from_either_side() {
+        let front = Ray::new(Vec3::new(0.25, 0.25, -2.0), Vec3::Z);
+        assert_eq!(front.intersect_triangle(TRIANGLE), Some(2.0));
+        let back = Ray::new(Vec3::new(0.25, 0.25, 3.0), -Vec3::Z * 2.0);
+        assert_eq!(back.intersect_triangle(TRIANGLE), Some(1.5));
+    }
+
+    #[test]
+    fn triangles_are_missed() {
+        // beside the long edge, behind the origin and parallel to the triangle
+        assert_eq!(Ray::new(Vec3::new(0.6, 0.6, -1.0), Vec3::Z).intersect_triangle(TRIANGLE), None);
+        assert_eq!(Ray::new(Vec3::new(0.25, 0.25, 1.0), Vec3::Z).intersect_triangle(TRIANGLE), None);
+        assert_eq!(Ray::new(Vec3::new(-1.0, 0.25, 0.0), Vec3::X).intersect_triangle(TRIANGLE), None);
+    }
+
+    #[test]
+    fn boxes_are_entered() {
+        let aabb = Aabb::new(Vec3::splat(-1.0), Vec3::splat(1.0));
+        assert_eq!(Ray::new(Vec3::new(-3.0, 0.5, 0.5), Vec3::X).intersect_aabb(&aabb), Some(2.0));
+        assert_eq!(Ray::new(Vec3::new(0.0, 5.0, 0.0), -Vec3::Y * 2.0).intersect_aabb(&aabb), Some(2.0));
+        // starting inside
+        assert_eq!(Ray::new(Vec3::ZERO, Vec3::new(1.0, 2.0, 3.0)).intersect_aabb(&aabb), Some(0.0));
+        let diagonal = Ray::new(Vec3::splat(-2.0), Vec3::ONE).intersect_aabb(&aabb).unwrap();
+        assert!((diagonal - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn boxes_are_missed() {
+        let aabb = Aabb::new(Vec3::splat(-1.0), Vec3::splat(1.0));
+        assert_eq!(Ray::new(Vec3::new(-3.0, 1.5, 0.0), Vec3::X).intersect_aabb(&aabb), None);
+        // pointing away from it
+        assert_eq!(Ray::new(Vec3::new(-3.0, 0.0, 0.0), -Vec3::X).intersect_aabb(&aabb), None);
+        // parallel to a face, outside of it
+        assert_eq!(Ray::new(Vec3::new(-3.0, 0.0, 2.0), Vec3::X).intersect_aabb(&aabb), None);
+    }
+
+    #[test]
+    fn transformed_rays_keep_their_distances() {
+        let matrix = Mat4::from_scale_rotation_translation(
+            Vec3::splat(2.0),
+            glam::f32::Quat::from_rotation_y(1.0),
+            Vec3::new(1.0, 2.0, 3.0),
+        );
+        let ray = Ray::new(Vec3::new(0.25, 0.25, -2.0), Vec3::Z);
+        let triangle = TRIANGLE.map(|corner| matrix.transform_point3(corner));
+        let distance = ray.transformed(matrix).intersect_triangle(triangle).unwrap();
+        assert!((distance - 2.0).abs() < 1e-5, "{}", distance);
+    }
+}
@@ -0,0 +1,57 @@
+use crate::math::Aabb;
+use glam::f32::{Mat4, Vec3};
+
+/// Bounding sphere, cheaper to test than a box but not as tight
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl Sphere {
+    pub fn new(center: Vec3, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    /// Around the center of the points' bounding box, not the smallest sphere but close for most meshes
+    pub fn from_points<I: IntoIterator<Item = Vec3> + Clone>(points: I) -> Option<Self> {
+        let center = Aabb::from_points(points.clone())?.center();
+        let radius = points.into_iter().map(|point| point.distance(center)).fold(0.0, f32::max);
+        Some(Self::new(center, radius))
+    }
+
+    /// Still around everything it was around after `matrix`, a stretched sphere grows to its longest axis
+    pub fn transformed(&self, matrix: Mat4) -> Self {
+        let scale = matrix.x_axis.truncate().length().max(matrix.y_axis.truncate().length()).max(matrix.z_axis.truncate().length());
+        Self::new(matrix.transform_point3(self.center), self.radius * scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::f32::Quat;
+
+    #[test]
+    fn spheres_hold_their_points() {
+        let points = [Vec3::new(-1.0, 0.0, 0.0), Vec3::new(3.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 0.0)];
+        let sphere = Sphere::from_points(points.iter().copied()).unwrap();
+        assert_eq!(sphere.center, Vec3::new(1.0, 0.5, 0.0));
+        for point in &points {
+            assert!(point.distance(sphere.center) <= sphere.radius + 1e-6);
+        }
+    }
+
+    #[test]
+    fn transformed_spheres_grow_to_the_longest_axis() {
+        let sphere = Sphere::new(Vec3::new(1.0, 0.0, 0.0), 2.0);
+        let matrix = Mat4::from_scale_rotation_translation(
+            Vec3::new(1.0, 3.0, 0.5),
+            Quat::from_rotation_z(std::f32::consts::FRAC_PI_2),
+            Vec3::new(0.0, 0.0, 5.0),
+        );
+        let transformed = sphere.transformed(matrix);
+        assert!(transformed.center.abs_diff_eq(Vec3::new(0.0, 1.0, 5.0), 1e-5), "{}", transformed.center);
+        assert!((transformed.radius - 6.0).abs() < 1e-5);
+    }
+}
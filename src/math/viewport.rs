@@ -0,0 +1,83 @@
+use crate::math::{Frustum, Ray};
+use glam::f32::{Mat4, Vec2, Vec3};
+
+/// What the camera saw in the last frame, to map between the screen and the world
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub perspective: Mat4,
+    pub view: Mat4,
+    /// in physical pixels, like cursor positions
+    pub size: Vec2,
+}
+
+impl Viewport {
+    pub fn new(perspective: Mat4, view: Mat4, size: Vec2) -> Self {
+        Self { perspective, view, size }
+    }
+
+    /// Ray through `pixel`, counted from the top left corner
+    pub fn ray(&self, pixel: Vec2) -> Ray {
+        let x = 2.0 * pixel.x / self.size.x - 1.0;
+        let y = 1.0 - 2.0 * pixel.y / self.size.y;
+        Ray::unproject(x, y, self.perspective, self.view)
+    }
+
+    /// Pixel `point` ends up on, `None` if it's behind the camera
+    pub fn project(&self, point: Vec3) -> Option<Vec2> {
+        let clip = self.perspective * self.view * point.extend(1.0);
+        if clip.w <= f32::EPSILON {
+            return None;
+        }
+        let x = clip.x / clip.w;
+        let y = clip.y / clip.w;
+        Some(Vec2::new((x + 1.0) * 0.5 * self.size.x, (1.0 - y) * 0.5 * self.size.y))
+    }
+
+    pub fn frustum(&self) -> Frustum {
+        Frustum::from_matrix(self.perspective * self.view)
+    }
+
+    /// Position of the camera
+    pub fn eye(&self) -> Vec3 {
+        self.view.inverse().transform_point3(Vec3::ZERO)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render;
+
+    fn viewport() -> Viewport {
+        let view = Mat4::from_cols_array_2d(&render::view_matrix(&[1.0, 2.0, -5.0], &[-0.2, -0.3, 1.0], &[0.0, 1.0, 0.0]));
+        Viewport::new(render::perspective(800, 600), view, Vec2::new(800.0, 600.0))
+    }
+
+    #[test]
+    fn rays_start_at_the_camera() {
+        let viewport = viewport();
+        let ray = viewport.ray(Vec2::new(400.0, 300.0));
+        let towards = (ray.origin - viewport.eye()).normalize();
+        assert!(towards.abs_diff_eq(ray.direction.normalize(), 1e-4), "{} {}", towards, ray.direction);
+        assert!(viewport.eye().abs_diff_eq(Vec3::new(1.0, 2.0, -5.0), 1e-4));
+    }
+
+    #[test]
+    fn points_on_a_ray_project_back_to_its_pixel() {
+        let viewport = viewport();
+        for &pixel in &[Vec2::new(400.0, 300.0), Vec2::new(10.0, 20.0), Vec2::new(790.0, 550.0)] {
+            let ray = viewport.ray(pixel);
+            for &distance in &[0.0, 0.01, 0.5, 1.0] {
+                let projected = viewport.project(ray.origin + ray.direction * distance).unwrap();
+                assert!(projected.abs_diff_eq(pixel, 0.05), "{} projected to {}", pixel, projected);
+            }
+        }
+    }
+
+    #[test]
+    fn points_behind_the_camera_are_not_projected() {
+        let viewport = viewport();
+        let ray = viewport.ray(Vec2::new(100.0, 100.0));
+        assert_eq!(viewport.project(viewport.eye() - ray.direction), None);
+    }
+}
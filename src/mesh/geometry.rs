@@ -0,0 +1,80 @@
+use crate::math::{Aabb, Sphere};
+use crate::teapot;
+use glam::f32::{Mat4, Vec3};
+
+/// Triangle mesh on the CPU side, used for physics and uploaded for rendering
+#[derive(Clone, Debug, Default)]
+pub struct Mesh {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    /// three per triangle, clockwise seen from the outside like the teapot data
+    pub indices: Vec<u32>,
+    /// simpler versions for when it's small on screen, from most to least detailed, see `with_lods`
+    pub lods: Vec<Mesh>,
+}
+
+impl Mesh {
+    pub fn teapot() -> Self {
+        Self {
+            positions: teapot::VERTICES
+                .iter()
+                .map(|vertex| Vec3::new(vertex.position.0, vertex.position.1, vertex.position.2))
+                .collect(),
+            normals: teapot::NORMALS
+                .iter()
+                .map(|normal| Vec3::new(normal.normal.0, normal.normal.1, normal.normal.2))
+                .collect(),
+            indices: teapot::INDICES.iter().map(|&index| index as u32).collect(),
+            lods: Vec::new(),
+        }
+    }
+
+    /// Cube from -0.5 to 0.5 with flat shaded faces
+    pub fn cube() -> Self {
+        let mut mesh = Self::default();
+        for &normal in &[Vec3::X, -Vec3::X, Vec3::Y, -Vec3::Y, Vec3::Z, -Vec3::Z] {
+            // two axes spanning the face, u x v points along the normal
+            let u = Vec3::new(normal.y, normal.z, normal.x);
+            let v = normal.cross(u);
+
+            let first = mesh.positions.len() as u32;
+            for &(a, b) in &[(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+                mesh.positions.push((normal + u * a + v * b) * 0.5);
+                mesh.normals.push(normal);
+            }
+            mesh.indices.extend_from_slice(&[first, first + 2, first + 1, first, first + 3, first + 2]);
+        }
+        mesh
+    }
+
+    /// Box around all vertices, `None` for an empty mesh
+    pub fn bounds(&self) -> Option<Aabb> {
+        Aabb::from_points(self.positions.iter().copied())
+    }
+
+    /// Sphere around all vertices, `None` for an empty mesh
+    pub fn bounding_sphere(&self) -> Option<Sphere> {
+        Sphere::from_points(self.positions.iter().copied())
+    }
+
+    pub fn triangles(&self) -> impl Iterator<Item = [Vec3; 3]> + '_ {
+        self.indices.chunks_exact(3).map(move |triangle| {
+            [
+                self.positions[triangle[0] as usize],
+                self.positions[triangle[1] as usize],
+                self.positions[triangle[2] as usize],
+            ]
+        })
+    }
+
+    /// Triangles with `matrix` applied to every corner
+    pub fn transformed_triangles(&self, matrix: Mat4) -> impl Iterator<Item = [Vec3; 3]> + '_ {
+        self.triangles().map(move |[a, b, c]| {
+            [
+                matrix.transform_point3(a),
+                matrix.transform_point3(b),
+                matrix.transform_point3(c),
+            ]
+        })
+    }
+}
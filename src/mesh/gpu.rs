@@ -0,0 +1,110 @@
+use crate::error::{Error, RenderContext};
+use crate::math::{Aabb, Sphere};
+use crate::mesh::{Mesh, MeshId, MeshLibrary};
+use glium::backend::Facade;
+use glium::implement_vertex;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Copy, Clone)]
+pub struct Position {
+    position: [f32; 3],
+}
+
+implement_vertex!(Position, position);
+
+#[derive(Copy, Clone)]
+pub struct Normal {
+    normal: [f32; 3],
+}
+
+implement_vertex!(Normal, normal);
+
+/// A `Mesh` uploaded to the graphics card
+pub struct GpuMesh {
+    pub positions: glium::VertexBuffer<Position>,
+    pub normals: glium::VertexBuffer<Normal>,
+    pub indices: glium::IndexBuffer<u32>,
+    /// in the mesh's own space, `None` if it has no vertices
+    pub bounds: Option<Aabb>,
+    pub sphere: Option<Sphere>,
+    /// the mesh's levels of detail
+    pub lods: Vec<GpuMesh>,
+}
+
+impl GpuMesh {
+    pub fn new<F: Facade>(facade: &F, mesh: &Mesh) -> Result<Self, Error> {
+        let positions: Vec<Position> = mesh
+            .positions
+            .iter()
+            .map(|position| Position { position: position.to_array() })
+            .collect();
+        let normals: Vec<Normal> = mesh.normals.iter().map(|normal| Normal { normal: normal.to_array() }).collect();
+
+        Ok(Self {
+            positions: glium::VertexBuffer::new(facade, &positions).rendering("uploading a mesh")?,
+            normals: glium::VertexBuffer::new(facade, &normals).rendering("uploading a mesh")?,
+            indices: glium::IndexBuffer::new(facade, glium::index::PrimitiveType::TrianglesList, &mesh.indices)
+                .rendering("uploading a mesh")?,
+            bounds: mesh.bounds(),
+            sphere: mesh.bounding_sphere(),
+            lods: mesh.lods.iter().map(|lod| GpuMesh::new(facade, lod)).collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// How many versions there are to pick from, the mesh itself and its levels of detail
+    pub fn levels(&self) -> usize {
+        self.lods.len() + 1
+    }
+
+    /// Level 0 is the mesh itself, the higher ones are simpler
+    pub fn level(&self, level: usize) -> &GpuMesh {
+        match level.checked_sub(1).and_then(|index| self.lods.get(index)) {
+            Some(lod) => lod,
+            None => self,
+        }
+    }
+}
+
+/// The meshes of a `MeshLibrary` on the graphics card, kept in step with it
+#[derive(Default)]
+pub struct GpuMeshes {
+    /// with the version of the mesh they were uploaded from
+    meshes: HashMap<MeshId, (u64, GpuMesh)>,
+    /// versions that couldn't be uploaded, they're tried again once the mesh changes
+    failed: HashMap<MeshId, u64>,
+}
+
+impl GpuMeshes {
+    pub fn get(&self, id: &MeshId) -> Option<&GpuMesh> {
+        self.meshes.get(id).map(|(_, mesh)| mesh)
+    }
+
+    /// Uploads new and reloaded meshes and frees the ones the library let go of.
+    /// A mesh that fails to upload isn't drawn, the first failure is returned.
+    pub fn sync<F: Facade>(&mut self, facade: &F, library: &MeshLibrary) -> Result<(), Error> {
+        let mut current = HashSet::new();
+        let mut result = Ok(());
+        for (id, version, mesh) in library.iter() {
+            let uploaded = matches!(self.meshes.get(&id), Some((uploaded, _)) if *uploaded == version);
+            if !uploaded && self.failed.get(&id) != Some(&version) {
+                match GpuMesh::new(facade, mesh) {
+                    Ok(gpu_mesh) => {
+                        self.meshes.insert(id.clone(), (version, gpu_mesh));
+                        self.failed.remove(&id);
+                    }
+                    Err(err) => {
+                        self.meshes.remove(&id);
+                        self.failed.insert(id.clone(), version);
+                        if result.is_ok() {
+                            result = Err(err);
+                        }
+                    }
+                }
+            }
+            current.insert(id);
+        }
+        self.meshes.retain(|id, _| current.contains(id));
+        self.failed.retain(|id, _| current.contains(id));
+        result
+    }
+}
@@ -0,0 +1,178 @@
+use crate::assets::{Assets, Handle, Loadable};
+use crate::error::Error;
+use crate::inspect::Inspect;
+use crate::mesh::Mesh;
+use crate::physics::Shape;
+use crate::scene::Scene;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Meshes a node can be drawn with
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MeshId {
+    #[default]
+    Teapot,
+    Cube,
+    /// an OBJ file, usually placed from the asset browser
+    File(PathBuf),
+}
+
+impl MeshId {
+    /// Meshes that are always there
+    pub const BUILT_IN: [MeshId; 2] = [MeshId::Teapot, MeshId::Cube];
+
+    pub fn name(&self) -> String {
+        match self {
+            MeshId::File(path) => match path.file_name() {
+                Some(name) => name.to_string_lossy().into_owned(),
+                None => path.display().to_string(),
+            },
+            built_in => format!("{:?}", built_in),
+        }
+    }
+}
+
+/// Built-in meshes can be picked, files are placed from the asset browser
+impl Inspect for MeshId {
+    fn inspect(&mut self, ui: &mut egui::Ui, label: &str) -> bool {
+        let before = self.clone();
+        egui::ComboBox::from_label(label)
+            .selected_text(self.name())
+            .show_ui(ui, |ui| {
+                for mesh in MeshId::BUILT_IN.iter() {
+                    ui.selectable_value(self, mesh.clone(), mesh.name());
+                }
+                if let MeshId::File(_) = &before {
+                    ui.selectable_value(self, before.clone(), before.name());
+                }
+            });
+        *self != before
+    }
+}
+
+impl Loadable for Mesh {
+    fn load(path: &Path) -> Result<Self, Error> {
+        Mesh::load_obj(path).map(Mesh::with_lods).map_err(|err| Error::file(path, err))
+    }
+}
+
+/// The built-in meshes and the mesh files in use, which load in the background
+pub struct MeshLibrary {
+    built_in: HashMap<MeshId, Mesh>,
+    files: Assets<Mesh>,
+    /// handles of the files nodes use, they keep them loaded
+    used: HashMap<PathBuf, Handle<Mesh>>,
+}
+
+impl MeshLibrary {
+    pub fn new() -> Self {
+        let mut built_in = HashMap::new();
+        built_in.insert(MeshId::Teapot, Mesh::teapot().with_lods());
+        built_in.insert(MeshId::Cube, Mesh::cube());
+        Self {
+            built_in,
+            files: Assets::new(),
+            used: HashMap::new(),
+        }
+    }
+
+    /// `None` for files that aren't loaded (yet)
+    pub fn get(&self, id: &MeshId) -> Option<&Mesh> {
+        match id {
+            MeshId::File(path) => self.files.get_path(path),
+            built_in => self.built_in.get(built_in),
+        }
+    }
+
+    /// Starts loading a mesh file, it stays loaded while the handle is kept
+    pub fn request<P: AsRef<Path>>(&mut self, path: P) -> Handle<Mesh> {
+        self.files.load(path)
+    }
+
+    pub fn get_handle(&self, handle: &Handle<Mesh>) -> Option<&Mesh> {
+        self.files.get(handle)
+    }
+
+    pub fn version(&self, handle: &Handle<Mesh>) -> u64 {
+        self.files.version(handle)
+    }
+
+    pub fn is_loading(&self, handle: &Handle<Mesh>) -> bool {
+        self.files.is_loading(handle)
+    }
+
+    /// Loads `id` right away instead of in the background, e.g. to place it
+    pub fn load_now(&mut self, id: &MeshId) -> Result<&Mesh, Error> {
+        if let MeshId::File(path) = id {
+            let handle = self.files.load(path);
+            self.files.wait();
+            if let Some(error) = self.files.error(&handle) {
+                return Err(error.clone());
+            }
+        }
+        Ok(self.get(id).expect("built-in meshes and loaded files are always there"))
+    }
+
+    /// Keeps the mesh files `scene` uses loaded and lets go of the others
+    pub fn track(&mut self, scene: &Scene) {
+        let mut paths = HashSet::new();
+        for node in &scene.nodes {
+            if let Some(MeshId::File(path)) = &node.mesh {
+                paths.insert(path);
+            }
+        }
+        self.used.retain(|path, _| paths.contains(path));
+        for path in paths {
+            if !self.used.contains_key(path) {
+                let handle = self.files.load(path);
+                self.used.insert(path.clone(), handle);
+            }
+        }
+    }
+
+    /// Takes in loaded files, frees unused ones and reloads the ones that changed on disk
+    pub fn update(&mut self) {
+        self.files.update();
+    }
+
+    /// Blocks until all files that are being loaded are there
+    pub fn wait(&mut self) {
+        self.files.wait();
+    }
+
+    /// Blocks until the mesh files `scene` collides with are there, the ones that are only drawn keep loading in the background
+    pub fn wait_for_colliders(&mut self, scene: &Scene) {
+        let paths: Vec<&Path> = scene
+            .nodes
+            .iter()
+            .filter(|node| matches!(&node.collider, Some(collider) if collider.shape == Shape::Mesh))
+            .filter_map(|node| match &node.mesh {
+                Some(MeshId::File(path)) => Some(path.as_path()),
+                _ => None,
+            })
+            .collect();
+        self.files.wait_for(&paths);
+    }
+
+    /// Mesh files that failed to load since the last call
+    pub fn take_errors(&mut self) -> Vec<Error> {
+        self.files.take_errors()
+    }
+
+    /// Every mesh that is loaded, with a version that goes up when it's reloaded
+    pub fn iter(&self) -> impl Iterator<Item = (MeshId, u64, &Mesh)> {
+        let built_in = self.built_in.iter().map(|(id, mesh)| (id.clone(), 0, mesh));
+        let files = self
+            .files
+            .iter()
+            .map(|(path, version, mesh)| (MeshId::File(path.to_path_buf()), version, mesh));
+        built_in.chain(files)
+    }
+}
+
+impl Default for MeshLibrary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
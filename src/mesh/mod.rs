@@ -0,0 +1,9 @@
+mod geometry;
+mod gpu;
+mod library;
+mod obj;
+mod simplify;
+
+pub use geometry::*;
+pub use gpu::*;
+pub use library::*;
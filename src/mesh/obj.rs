@@ -0,0 +1,91 @@
+use crate::mesh::Mesh;
+use glam::f32::Vec3;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+impl Mesh {
+    pub fn load_obj<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let text = fs::read_to_string(path)?;
+        Ok(Self::from_obj(&text)?)
+    }
+
+    /// Reads the positions, normals and faces of a Wavefront OBJ file, everything else is skipped.
+    /// Faces with more than three corners are split into a fan.
+    /// Faces without normals get the flat normal of the face.
+    pub fn from_obj(text: &str) -> Result<Self, String> {
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut mesh = Mesh::default();
+        // corners that already are a vertex of the mesh, by position and normal index
+        let mut vertices: HashMap<(usize, usize), u32> = HashMap::new();
+
+        for (number, line) in text.lines().enumerate() {
+            let error = |message: &str| format!("line {}: {}", number + 1, message);
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("v") => positions.push(parse_vec3(words).ok_or_else(|| error("bad vertex"))?),
+                Some("vn") => normals.push(parse_vec3(words).ok_or_else(|| error("bad normal"))?),
+                Some("f") => {
+                    let mut corners = Vec::new();
+                    for word in words {
+                        let mut indices = word.split('/');
+                        let position = resolve(indices.next(), positions.len()).ok_or_else(|| error("bad face"))?;
+                        let normal = match indices.nth(1) {
+                            Some("") | None => None,
+                            index => Some(resolve(index, normals.len()).ok_or_else(|| error("bad face"))?),
+                        };
+                        corners.push((position, normal));
+                    }
+                    if corners.len() < 3 {
+                        return Err(error("face with less than 3 corners"));
+                    }
+
+                    let flat = {
+                        let [a, b, c] = [corners[0].0, corners[1].0, corners[2].0].map(|index| positions[index]);
+                        (b - a).cross(c - a).normalize_or_zero()
+                    };
+                    let mut indices = Vec::with_capacity(corners.len());
+                    for (position, normal) in corners {
+                        let index = match normal {
+                            Some(normal) => *vertices.entry((position, normal)).or_insert_with(|| {
+                                mesh.positions.push(positions[position]);
+                                mesh.normals.push(normals[normal]);
+                                mesh.positions.len() as u32 - 1
+                            }),
+                            None => {
+                                mesh.positions.push(positions[position]);
+                                mesh.normals.push(flat);
+                                mesh.positions.len() as u32 - 1
+                            }
+                        };
+                        indices.push(index);
+                    }
+                    // OBJ faces are counter-clockwise, the meshes here clockwise
+                    for i in 1..indices.len() - 1 {
+                        mesh.indices.extend_from_slice(&[indices[0], indices[i + 1], indices[i]]);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(mesh)
+    }
+}
+
+fn parse_vec3<'a>(mut words: impl Iterator<Item = &'a str>) -> Option<Vec3> {
+    let mut next = || words.next()?.parse().ok();
+    Some(Vec3::new(next()?, next()?, next()?))
+}
+
+/// Turns a 1-based or negative (counted from the end) OBJ index into a 0-based one
+fn resolve(index: Option<&str>, count: usize) -> Option<usize> {
+    let index: i64 = index?.parse().ok()?;
+    let resolved = if index < 0 { count as i64 + index } else { index - 1 };
+    if (0..count as i64).contains(&resolved) {
+        Some(resolved as usize)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,416 @@
+use crate::math::Aabb;
+use crate::mesh::Mesh;
+use glam::f32::Vec3;
+use glam::f64::{DMat3, DVec3};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+/// Meshes with fewer triangles don't get levels of detail, there's little to gain
+const LOD_MIN_TRIANGLES: usize = 256;
+
+/// The simplest level of detail has at least this many triangles
+const LOD_MIN_LEVEL_TRIANGLES: usize = 64;
+
+/// Each level of detail has about this share of the triangles of the one before
+const LOD_RATIO: f32 = 0.5;
+
+const MAX_LODS: usize = 3;
+
+/// How much more the planes along open borders count than the faces, borders would shrink away otherwise
+const BORDER_WEIGHT: f64 = 1000.0;
+
+/// Corners closer than this share of the mesh's size are welded together before simplifying
+const WELD_DISTANCE: f32 = 1e-5;
+
+impl Mesh {
+    /// The mesh with simpler versions of itself in `lods`, made by `simplify`
+    pub fn with_lods(mut self) -> Self {
+        let triangles = self.indices.len() / 3;
+        self.lods.clear();
+        if triangles < LOD_MIN_TRIANGLES {
+            return self;
+        }
+        let mut targets = Vec::new();
+        let mut target = triangles;
+        while targets.len() < MAX_LODS {
+            target = (target as f32 * LOD_RATIO) as usize;
+            if target < LOD_MIN_LEVEL_TRIANGLES {
+                break;
+            }
+            targets.push(target);
+        }
+        self.lods = self.simplify(&targets);
+        self
+    }
+
+    /// Versions of the mesh with about as many triangles as each of `targets`, from most to least, made by collapsing
+    /// the edges that change the shape least by Garland and Heckbert's quadric error metric.
+    /// Targets that can't be reached without tearing the mesh apart are left out.
+    /// The simpler meshes are smooth shaded, vertices sharing a position share a normal.
+    pub fn simplify(&self, targets: &[usize]) -> Vec<Mesh> {
+        match Simplifier::new(self) {
+            Some(mut simplifier) => simplifier.run(targets),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Sum of squared distances to planes, as a symmetric 4x4 matrix
+#[derive(Clone, Copy, Debug, Default)]
+struct Quadric {
+    /// upper triangle, row by row
+    entries: [f64; 10],
+}
+
+impl Quadric {
+    /// For the plane through `point` with the unit `normal`
+    fn plane(normal: DVec3, point: DVec3, weight: f64) -> Self {
+        let [a, b, c] = normal.to_array();
+        let d = -normal.dot(point);
+        let entries = [a * a, a * b, a * c, a * d, b * b, b * c, b * d, c * c, c * d, d * d];
+        Self {
+            entries: entries.map(|entry| entry * weight),
+        }
+    }
+
+    fn add(&self, other: &Quadric) -> Quadric {
+        let mut entries = self.entries;
+        for (entry, other) in entries.iter_mut().zip(other.entries.iter()) {
+            *entry += other;
+        }
+        Quadric { entries }
+    }
+
+    fn error(&self, point: DVec3) -> f64 {
+        let [a, b, c, d, e, f, g, h, i, j] = self.entries;
+        let [x, y, z] = point.to_array();
+        a * x * x + 2.0 * b * x * y + 2.0 * c * x * z + 2.0 * d * x + e * y * y + 2.0 * f * y * z + 2.0 * g * y
+            + h * z * z
+            + 2.0 * i * z
+            + j
+    }
+
+    /// Where the error is smallest, `None` if there's no single such point, e.g. on a flat surface
+    fn minimum(&self) -> Option<DVec3> {
+        let [a, b, c, d, e, f, g, h, i, _] = self.entries;
+        let matrix = DMat3::from_cols(DVec3::new(a, b, c), DVec3::new(b, e, f), DVec3::new(c, f, h));
+        if matrix.determinant().abs() < 1e-12 {
+            return None;
+        }
+        Some(matrix.inverse() * -DVec3::new(d, g, i))
+    }
+}
+
+/// An edge that could be collapsed, as it was when its points had these versions
+struct Collapse {
+    cost: f64,
+    points: [usize; 2],
+    versions: [u32; 2],
+    position: DVec3,
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    /// Cheapest first out of the `BinaryHeap`
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+struct Simplifier {
+    points: Vec<DVec3>,
+    /// sum of the normals of the vertices welded into each point
+    normals: Vec<Vec3>,
+    quadrics: Vec<Quadric>,
+    /// goes up whenever a point moves, collapses computed before are outdated then
+    versions: Vec<u32>,
+    removed: Vec<bool>,
+    triangles: Vec<[usize; 3]>,
+    triangle_removed: Vec<bool>,
+    /// triangles around each point
+    around: Vec<Vec<usize>>,
+    remaining: usize,
+    collapses: BinaryHeap<Collapse>,
+}
+
+impl Simplifier {
+    fn new(mesh: &Mesh) -> Option<Self> {
+        let bounds = Aabb::from_points(mesh.positions.iter().copied())?;
+        let weld = ((bounds.max - bounds.min).length() * WELD_DISTANCE).max(f32::EPSILON);
+
+        let mut points = Vec::new();
+        let mut normals: Vec<Vec3> = Vec::new();
+        let mut welded = HashMap::new();
+        let point_of_vertex: Vec<usize> = mesh
+            .positions
+            .iter()
+            .zip(mesh.normals.iter())
+            .map(|(&position, &normal)| {
+                let key = (position / weld).round().to_array().map(|coordinate| coordinate as i64);
+                let point = *welded.entry(key).or_insert_with(|| {
+                    points.push(position.as_f64());
+                    normals.push(Vec3::ZERO);
+                    points.len() - 1
+                });
+                normals[point] += normal;
+                point
+            })
+            .collect();
+
+        let triangles: Vec<[usize; 3]> = mesh
+            .indices
+            .chunks_exact(3)
+            .map(|triangle| [0, 1, 2].map(|corner| point_of_vertex[triangle[corner] as usize]))
+            .filter(|[a, b, c]| a != b && b != c && c != a)
+            .collect();
+
+        let mut simplifier = Self {
+            quadrics: vec![Quadric::default(); points.len()],
+            versions: vec![0; points.len()],
+            removed: vec![false; points.len()],
+            around: vec![Vec::new(); points.len()],
+            triangle_removed: vec![false; triangles.len()],
+            remaining: triangles.len(),
+            collapses: BinaryHeap::new(),
+            points,
+            normals,
+            triangles,
+        };
+        simplifier.add_quadrics();
+        for (index, triangle) in simplifier.triangles.iter().enumerate() {
+            for &point in triangle {
+                simplifier.around[point].push(index);
+            }
+        }
+        let edges: HashSet<[usize; 2]> = simplifier.triangles.iter().flat_map(|&triangle| edges(triangle)).collect();
+        for [a, b] in edges {
+            simplifier.push_collapse(a, b);
+        }
+        Some(simplifier)
+    }
+
+    /// The planes of the faces, and planes standing on the open borders so they keep their place
+    fn add_quadrics(&mut self) {
+        let mut edge_count: HashMap<[usize; 2], (usize, usize)> = HashMap::new();
+        for (index, &triangle) in self.triangles.iter().enumerate() {
+            let [a, b, c] = triangle.map(|point| self.points[point]);
+            let cross = (b - a).cross(c - a);
+            let area = cross.length() * 0.5;
+            if area <= 0.0 {
+                continue;
+            }
+            let quadric = Quadric::plane(cross.normalize(), a, area);
+            for &point in &triangle {
+                self.quadrics[point] = self.quadrics[point].add(&quadric);
+            }
+            for edge in edges(triangle) {
+                edge_count.entry(edge).or_insert((0, index)).0 += 1;
+            }
+        }
+
+        for ([a, b], (count, triangle)) in edge_count {
+            if count != 1 {
+                continue;
+            }
+            let [p, q, r] = self.triangles[triangle].map(|point| self.points[point]);
+            let face = (q - p).cross(r - p).normalize();
+            let edge = self.points[b] - self.points[a];
+            let normal = edge.cross(face).normalize();
+            let quadric = Quadric::plane(normal, self.points[a], BORDER_WEIGHT * edge.length_squared());
+            self.quadrics[a] = self.quadrics[a].add(&quadric);
+            self.quadrics[b] = self.quadrics[b].add(&quadric);
+        }
+    }
+
+    fn push_collapse(&mut self, a: usize, b: usize) {
+        let quadric = self.quadrics[a].add(&self.quadrics[b]);
+        let (position, cost) = match quadric.minimum() {
+            Some(position) => (position, quadric.error(position)),
+            None => [self.points[a], self.points[b], (self.points[a] + self.points[b]) * 0.5]
+                .iter()
+                .map(|&position| (position, quadric.error(position)))
+                .min_by(|x, y| x.1.total_cmp(&y.1))
+                .expect("there are three candidates"),
+        };
+        self.collapses.push(Collapse {
+            cost,
+            points: [a, b],
+            versions: [self.versions[a], self.versions[b]],
+            position,
+        });
+    }
+
+    fn run(&mut self, targets: &[usize]) -> Vec<Mesh> {
+        let mut meshes = Vec::new();
+        let mut targets = targets.iter().peekable();
+        while let Some(&&target) = targets.peek() {
+            if self.remaining <= target {
+                meshes.push(self.mesh());
+                targets.next();
+                continue;
+            }
+            let collapse = match self.collapses.pop() {
+                Some(collapse) => collapse,
+                None => break,
+            };
+            let [a, b] = collapse.points;
+            let outdated = self.removed[a] || self.removed[b] || collapse.versions != [self.versions[a], self.versions[b]];
+            if !outdated && !self.flips(a, b, collapse.position) {
+                self.collapse(a, b, collapse.position);
+            }
+        }
+        meshes
+    }
+
+    /// Whether moving `a` and `b` to `position` would turn one of the triangles around them over
+    fn flips(&self, a: usize, b: usize, position: DVec3) -> bool {
+        self.around[a].iter().chain(self.around[b].iter()).any(|&index| {
+            let triangle = self.triangles[index];
+            if self.triangle_removed[index] || (triangle.contains(&a) && triangle.contains(&b)) {
+                return false;
+            }
+            let before = triangle.map(|point| self.points[point]);
+            let after = triangle.map(|point| if point == a || point == b { position } else { self.points[point] });
+            let normal = |[p, q, r]: [DVec3; 3]| (q - p).cross(r - p);
+            normal(after).dot(normal(before)) <= 0.0
+        })
+    }
+
+    /// Merges `b` into `a` at `position`
+    fn collapse(&mut self, a: usize, b: usize, position: DVec3) {
+        self.points[a] = position;
+        self.quadrics[a] = self.quadrics[a].add(&self.quadrics[b]);
+        let normal = self.normals[b];
+        self.normals[a] += normal;
+        self.versions[a] += 1;
+        self.removed[b] = true;
+
+        for index in std::mem::take(&mut self.around[b]) {
+            if self.triangle_removed[index] {
+                continue;
+            }
+            let triangle = &mut self.triangles[index];
+            if triangle.contains(&a) {
+                // the collapsed edge was one of its sides
+                self.triangle_removed[index] = true;
+                self.remaining -= 1;
+            } else {
+                for point in triangle.iter_mut().filter(|point| **point == b) {
+                    *point = a;
+                }
+                self.around[a].push(index);
+            }
+        }
+        let removed = &self.triangle_removed;
+        self.around[a].retain(|&index| !removed[index]);
+
+        let neighbours: HashSet<usize> = self.around[a]
+            .iter()
+            .flat_map(|&index| self.triangles[index])
+            .filter(|&point| point != a)
+            .collect();
+        for neighbour in neighbours {
+            self.push_collapse(a, neighbour);
+        }
+    }
+
+    /// The triangles that are left, as a mesh of their own
+    fn mesh(&self) -> Mesh {
+        let mut mesh = Mesh::default();
+        let mut vertex_of_point = HashMap::new();
+        for (index, triangle) in self.triangles.iter().enumerate() {
+            if self.triangle_removed[index] {
+                continue;
+            }
+            for &point in triangle {
+                let vertex = *vertex_of_point.entry(point).or_insert_with(|| {
+                    mesh.positions.push(self.points[point].as_f32());
+                    mesh.normals.push(self.normals[point].normalize_or_zero());
+                    mesh.positions.len() as u32 - 1
+                });
+                mesh.indices.push(vertex);
+            }
+        }
+        mesh
+    }
+}
+
+/// The sides of `triangle`, with the lower point first so each side is the same from both triangles sharing it
+fn edges([a, b, c]: [usize; 3]) -> [[usize; 2]; 3] {
+    [[a, b], [b, c], [c, a]].map(|[p, q]| [p.min(q), p.max(q)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triangles(mesh: &Mesh) -> usize {
+        mesh.indices.len() / 3
+    }
+
+    #[test]
+    fn teapot_lods_get_simpler() {
+        let teapot = Mesh::teapot().with_lods();
+        assert_eq!(teapot.lods.len(), MAX_LODS);
+
+        let mut previous = triangles(&teapot);
+        for lod in &teapot.lods {
+            let count = triangles(lod);
+            let target = (previous as f32 * LOD_RATIO) as usize;
+            assert!(count < previous, "{} triangles after {}", count, previous);
+            // each collapse takes away two triangles, or one along a border
+            assert!(count <= target && count + target / 10 >= target, "{} triangles for a target of {}", count, target);
+            previous = target;
+        }
+    }
+
+    #[test]
+    fn teapot_lods_are_valid_meshes() {
+        let teapot = Mesh::teapot().with_lods();
+        for lod in &teapot.lods {
+            assert_eq!(lod.positions.len(), lod.normals.len());
+            assert!(lod.indices.iter().all(|&index| (index as usize) < lod.positions.len()));
+            for [a, b, c] in lod.triangles() {
+                assert!((b - a).cross(c - a).length() > 0.0, "degenerate triangle {:?}", [a, b, c]);
+            }
+            assert!(lod.normals.iter().all(|normal| normal.is_normalized()));
+        }
+    }
+
+    #[test]
+    fn teapot_lods_keep_their_size() {
+        let teapot = Mesh::teapot().with_lods();
+        let bounds = teapot.bounds().unwrap();
+        let tolerance = (bounds.max - bounds.min).length() * 0.05;
+        for lod in &teapot.lods {
+            let lod_bounds = lod.bounds().unwrap();
+            assert!(
+                (lod_bounds.min - bounds.min).abs().max_element() < tolerance
+                    && (lod_bounds.max - bounds.max).abs().max_element() < tolerance,
+                "{:?} strayed from {:?}",
+                lod_bounds,
+                bounds
+            );
+        }
+    }
+
+    #[test]
+    fn small_meshes_get_no_lods() {
+        let cube = Mesh::cube().with_lods();
+        assert!(triangles(&cube) < LOD_MIN_TRIANGLES);
+        assert!(cube.lods.is_empty());
+    }
+}
@@ -0,0 +1,328 @@
+use crate::console::{self, Registry};
+use crate::history::{Command, History};
+use crate::mesh::MeshId;
+use crate::scene::{Node, NodeId, Scene, Transform};
+use crate::selection::Selection;
+use egui::{Id, Sense, Ui};
+use glam::f32::Vec3;
+use std::collections::{HashMap, HashSet};
+
+/// Indentation per level of the tree
+const INDENT: f32 = 12.0;
+
+/// The tree scrolls once it's taller than this
+const MAX_TREE_HEIGHT: f32 = 400.0;
+
+/// Console commands to add and remove nodes
+pub fn register_commands(registry: &mut Registry) {
+    registry.command("spawn", "spawn <teapot|cube> [x y z]: adds a node and selects it", |context, args| {
+        let (mesh, location) = match args {
+            [mesh, location @ ..] => (*mesh, location),
+            [] => return Err("usage: spawn <teapot|cube> [x y z]".to_string()),
+        };
+        let mut node = match mesh {
+            "teapot" => {
+                let mut node = Node::new("Teapot");
+                node.mesh = Some(MeshId::Teapot);
+                // the teapot model is about 100 units across
+                node.transform.size = Vec3::splat(0.01);
+                node
+            }
+            "cube" => {
+                let mut node = Node::new("Cube");
+                node.mesh = Some(MeshId::Cube);
+                node.transform.size = Vec3::splat(0.5);
+                node
+            }
+            _ => return Err(format!("can't spawn {}, only teapot or cube", mesh)),
+        };
+        node.transform.location = match location {
+            [] => Vec3::new(0.0, 0.0, 2.0),
+            location => console::parse_vec3(location)?,
+        };
+        context.selection.set(Some(context.history.add_node(context.scene, node)));
+        Ok(String::new())
+    });
+    registry.command("delete", "deletes the selected nodes with their children", |context, _| {
+        context.history.remove_nodes(context.scene, context.selection.ids());
+        context.selection.retain_existing(context.scene);
+        Ok(String::new())
+    });
+}
+
+/// A node as a line of the tree
+struct Row {
+    id: NodeId,
+    depth: usize,
+    has_children: bool,
+}
+
+/// What the rows of the tree work on
+struct Tree<'a> {
+    scene: &'a mut Scene,
+    selection: &'a mut Selection,
+    history: &'a mut History,
+    /// row the pointer is over while dragging, `Some(None)` is the top level
+    drop_target: Option<Option<NodeId>>,
+}
+
+/// Panel showing the scene as a tree, to select, rename, reparent, duplicate and delete nodes
+#[derive(Default)]
+pub struct Outliner {
+    /// nodes whose children are folded away
+    collapsed: HashSet<NodeId>,
+    dragging: Option<NodeId>,
+    /// node that is being renamed and the name typed so far
+    renaming: Option<(NodeId, String)>,
+    focus_rename: bool,
+}
+
+impl Outliner {
+    pub fn show(&mut self, ui: &mut Ui, scene: &mut Scene, selection: &mut Selection, history: &mut History) {
+        ui.horizontal(|ui| {
+            if ui.add(egui::Button::new("Undo").enabled(history.can_undo())).clicked() {
+                history.undo(scene);
+            }
+            if ui.add(egui::Button::new("Redo").enabled(history.can_redo())).clicked() {
+                history.redo(scene);
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Add cube").clicked() {
+                let mut cube = Node::new("Cube");
+                cube.mesh = Some(MeshId::Cube);
+                cube.transform.location = Vec3::new(0.0, 0.0, 2.0);
+                cube.transform.size = Vec3::splat(0.5);
+                selection.set(Some(history.add_node(scene, cube)));
+            }
+            let any_selected = !selection.ids().is_empty();
+            if ui.add(egui::Button::new("Duplicate").enabled(any_selected)).clicked() {
+                let copies = history.duplicate_nodes(scene, selection.ids());
+                selection.set(None);
+                for id in copies {
+                    selection.toggle(id);
+                }
+            }
+            if ui.add(egui::Button::new("Delete").enabled(any_selected)).clicked() {
+                history.remove_nodes(scene, selection.ids());
+                selection.retain_existing(scene);
+            }
+        });
+        ui.separator();
+
+        // only the rows in view are laid out, big scenes have thousands
+        let rows = self.rows(scene);
+        let mut tree = Tree {
+            scene: &mut *scene,
+            selection: &mut *selection,
+            history: &mut *history,
+            drop_target: None,
+        };
+        let row_height = ui.spacing().interact_size.y;
+        egui::ScrollArea::from_max_height(MAX_TREE_HEIGHT).show_rows(ui, row_height, rows.len(), |ui, range| {
+            for row in &rows[range] {
+                self.show_node(ui, &mut tree, row);
+            }
+        });
+        let mut drop_target = tree.drop_target;
+
+        if let Some(dragged) = self.dragging {
+            let response = ui.add(egui::Label::new("Drop here to move to the top level").weak());
+            if pointer_over(ui, response.rect) {
+                drop_target = Some(None);
+            }
+            if let Some(node) = scene.node(dragged) {
+                egui::show_tooltip_text(ui.ctx(), Id::new("outliner_drag"), format!("Move {}", node.name));
+            }
+
+            if ui.input().pointer.any_released() {
+                self.dragging = None;
+                if let Some(parent) = drop_target {
+                    // dragging one of the selected nodes moves all of them
+                    let ids = if selection.contains(dragged) {
+                        selection.ids().to_vec()
+                    } else {
+                        vec![dragged]
+                    };
+                    reparent(scene, history, &ids, parent);
+                }
+            }
+        }
+    }
+
+    /// The nodes that aren't folded away, in the order they're listed
+    fn rows(&self, scene: &Scene) -> Vec<Row> {
+        let children: HashMap<Option<NodeId>, Vec<NodeId>> = scene.children_by_parent();
+        let below = |parent: Option<NodeId>| children.get(&parent).map(Vec::as_slice).unwrap_or_default();
+
+        let mut rows = Vec::new();
+        let mut stack: Vec<(NodeId, usize)> = below(None).iter().rev().map(|&id| (id, 0)).collect();
+        while let Some((id, depth)) = stack.pop() {
+            let children = below(Some(id));
+            rows.push(Row {
+                id,
+                depth,
+                has_children: !children.is_empty(),
+            });
+            // a broken file could have parents pointing in a circle
+            if !self.collapsed.contains(&id) && depth < scene.nodes.len() {
+                stack.extend(children.iter().rev().map(|&child| (child, depth + 1)));
+            }
+        }
+        rows
+    }
+
+    fn show_node(&mut self, ui: &mut Ui, tree: &mut Tree, row: &Row) {
+        let Tree {
+            scene,
+            selection,
+            history,
+            drop_target,
+        } = tree;
+        let Row { id, depth, has_children } = *row;
+        let node = match scene.node(id) {
+            Some(node) => node.clone(),
+            None => return,
+        };
+
+        ui.horizontal(|ui| {
+            ui.add_space(depth as f32 * INDENT);
+            if has_children {
+                let collapsed = self.collapsed.contains(&id);
+                if ui.small_button(if collapsed { "+" } else { "-" }).clicked() {
+                    if collapsed {
+                        self.collapsed.remove(&id);
+                    } else {
+                        self.collapsed.insert(id);
+                    }
+                }
+            } else {
+                ui.add_space(INDENT);
+            }
+
+            let mut visible = node.visible;
+            if ui.checkbox(&mut visible, "").on_hover_text("Visible").changed() {
+                edit_node(scene, history, id, |node| node.visible = visible);
+            }
+            let mut locked = node.locked;
+            if ui.checkbox(&mut locked, "").on_hover_text("Locked").changed() {
+                edit_node(scene, history, id, |node| node.locked = locked);
+            }
+
+            match &mut self.renaming {
+                Some((renamed, name)) if *renamed == id => {
+                    let response = ui.text_edit_singleline(name);
+                    if self.focus_rename {
+                        response.request_focus();
+                        self.focus_rename = false;
+                    }
+                    if response.lost_focus() {
+                        let name = name.clone();
+                        self.renaming = None;
+                        if !name.is_empty() {
+                            edit_node(scene, history, id, |node| node.name = name);
+                        }
+                    }
+                }
+                _ => {
+                    let response = ui
+                        .selectable_label(selection.contains(id), &node.name)
+                        .interact(Sense::click_and_drag());
+                    if response.double_clicked() {
+                        self.renaming = Some((id, node.name.clone()));
+                        self.focus_rename = true;
+                    } else if response.clicked() {
+                        if ui.input().modifiers.ctrl {
+                            selection.toggle(id);
+                        } else {
+                            selection.set(Some(id));
+                        }
+                    }
+                    if response.drag_started() {
+                        self.dragging = Some(id);
+                    }
+                    if self.dragging.is_some() && pointer_over(ui, response.rect) {
+                        *drop_target = Some(Some(id));
+                    }
+                }
+            }
+        });
+    }
+}
+
+fn pointer_over(ui: &Ui, rect: egui::Rect) -> bool {
+    match ui.input().pointer.hover_pos() {
+        Some(position) => rect.contains(position),
+        None => false,
+    }
+}
+
+/// Changes a node through the history, so it can be undone
+fn edit_node(scene: &mut Scene, history: &mut History, id: NodeId, edit: impl FnOnce(&mut Node)) {
+    if let Some(node) = scene.node_mut(id) {
+        let before = node.clone();
+        edit(node);
+        history.record_change(&before, node);
+        history.seal();
+    }
+}
+
+/// Moves the nodes below `parent`, keeping them where they are in the world
+fn reparent(scene: &mut Scene, history: &mut History, ids: &[NodeId], parent: Option<NodeId>) {
+    let parent_transform = match parent.and_then(|id| scene.node(id)) {
+        Some(node) => scene.world_transform(node),
+        None => Transform::default(),
+    };
+
+    let mut commands = Vec::new();
+    for &id in ids {
+        let node = match scene.node(id) {
+            Some(node) if node.parent != parent && scene.can_reparent(id, parent) => node,
+            _ => continue,
+        };
+        let mut after = node.clone();
+        after.parent = parent;
+        after.transform = parent_transform.world_to_child(&scene.world_transform(node));
+        commands.push(Command::Change {
+            id,
+            before: Box::new(node.clone()),
+            after: Box::new(after),
+        });
+    }
+
+    let label = match commands.as_slice() {
+        [] => return,
+        [Command::Change { after, .. }] => format!("Reparent {}", after.name),
+        _ => format!("Reparent {} nodes", commands.len()),
+    };
+    history.execute(scene, &label, Command::Group(commands));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: u64, parent: Option<u64>) -> Node {
+        let mut node = Node::new(&id.to_string());
+        node.id = NodeId(id);
+        node.parent = parent.map(NodeId);
+        node
+    }
+
+    #[test]
+    fn rows_follow_the_hierarchy() {
+        let scene = Scene {
+            // 5's parent is gone, it's listed at the top level
+            nodes: vec![node(1, None), node(2, Some(3)), node(3, Some(1)), node(4, None), node(5, Some(9))],
+            ..Scene::default()
+        };
+        let mut outliner = Outliner::default();
+        let rows = |outliner: &Outliner| -> Vec<(u64, usize, bool)> {
+            outliner.rows(&scene).iter().map(|row| (row.id.0, row.depth, row.has_children)).collect()
+        };
+        assert_eq!(rows(&outliner), [(1, 0, true), (3, 1, true), (2, 2, false), (4, 0, false), (5, 0, false)]);
+
+        outliner.collapsed.insert(NodeId(3));
+        assert_eq!(rows(&outliner), [(1, 0, true), (3, 1, true), (4, 0, false), (5, 0, false)]);
+    }
+}
@@ -0,0 +1,64 @@
+use crate::inspect::{self, inspect_enum, inspect_fields};
+use glam::f32::Vec3;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BodyKind {
+    /// moved by gravity and collisions
+    Dynamic,
+    /// moved only by its velocity, pushes dynamic bodies away but is never pushed itself
+    Kinematic,
+    /// never moves
+    Static,
+}
+
+/// Makes a node take part in the physics simulation.
+/// Bodies only move, collisions don't make them rotate.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RigidBody {
+    pub kind: BodyKind,
+    pub mass: f32,
+    pub velocity: Vec3,
+    /// bounciness from 0 (none) to 1 (keeps all energy)
+    pub restitution: f32,
+    pub friction: f32,
+    pub gravity_scale: f32,
+}
+
+impl RigidBody {
+    pub fn new(kind: BodyKind) -> Self {
+        Self {
+            kind,
+            mass: 1.0,
+            velocity: Vec3::ZERO,
+            restitution: 0.2,
+            friction: 0.5,
+            gravity_scale: 1.0,
+        }
+    }
+
+    /// 0 for bodies that can't be pushed
+    pub fn inverse_mass(&self) -> f32 {
+        match self.kind {
+            BodyKind::Dynamic if self.mass > 0.0 => 1.0 / self.mass,
+            _ => 0.0,
+        }
+    }
+}
+
+impl Default for RigidBody {
+    fn default() -> Self {
+        Self::new(BodyKind::Dynamic)
+    }
+}
+
+inspect_enum!(BodyKind { Dynamic, Kinematic, Static });
+
+inspect_fields!(RigidBody {
+    kind,
+    mass: inspect::positive,
+    velocity,
+    restitution: inspect::fraction,
+    friction: inspect::non_negative,
+    gravity_scale,
+});
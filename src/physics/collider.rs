@@ -0,0 +1,181 @@
+use crate::inspect::{self, inspect_fields, Inspect};
+use crate::math::{Aabb, Obb};
+use crate::mesh::Mesh;
+use crate::scene::Transform;
+use egui::Ui;
+use glam::f32::Vec3;
+use serde::{Deserialize, Serialize};
+
+/// Collider shapes, in the local space of their node
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Shape {
+    Sphere { radius: f32 },
+    /// turns with the node
+    Box { half_extents: Vec3 },
+    /// along the node's Y axis, `half_height` is the distance from the center to the middle of either cap
+    Capsule { radius: f32, half_height: f32 },
+    /// the triangles of the node's mesh. Collides with everything but other meshes.
+    Mesh,
+}
+
+/// Layer every collider is on unless told otherwise
+pub const DEFAULT_LAYER: u32 = 1;
+pub const ALL_LAYERS: u32 = u32::MAX;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Collider {
+    pub shape: Shape,
+    /// center of the shape relative to the node
+    pub offset: Vec3,
+    /// triggers don't push anything, they only report what enters and leaves them
+    #[serde(default)]
+    pub trigger: bool,
+    /// bits of the layers this collider is on
+    #[serde(default = "default_layer")]
+    pub layer: u32,
+    /// bits of the layers this collider interacts with
+    #[serde(default = "all_layers")]
+    pub mask: u32,
+}
+
+fn default_layer() -> u32 {
+    DEFAULT_LAYER
+}
+
+fn all_layers() -> u32 {
+    ALL_LAYERS
+}
+
+/// Whether colliders on `layer_a` with `mask_a` and on `layer_b` with `mask_b` interact.
+/// Both have to accept the other.
+pub fn layers_interact(layer_a: u32, mask_a: u32, layer_b: u32, mask_b: u32) -> bool {
+    layer_a & mask_b != 0 && layer_b & mask_a != 0
+}
+
+impl Collider {
+    pub fn new(shape: Shape) -> Self {
+        Self {
+            shape,
+            offset: Vec3::ZERO,
+            trigger: false,
+            layer: DEFAULT_LAYER,
+            mask: ALL_LAYERS,
+        }
+    }
+
+    /// Places the collider in the world, scaled by the node's size.
+    /// Mesh colliders need the node's mesh and are `None` without one.
+    pub fn world_shape(&self, transform: &Transform, mesh: Option<&Mesh>) -> Option<WorldShape> {
+        let matrix = transform.matrix();
+        let center = matrix.transform_point3(self.offset);
+        let size = transform.size.abs();
+
+        Some(match self.shape {
+            Shape::Sphere { radius } => WorldShape::Sphere {
+                center,
+                radius: radius * size.max_element(),
+            },
+            Shape::Box { half_extents } => WorldShape::Box(Obb::new(center, transform.rotation, half_extents * size)),
+            Shape::Capsule { radius, half_height } => {
+                let half_height = transform.rotation * Vec3::new(0.0, half_height * size.y, 0.0);
+                WorldShape::Capsule {
+                    a: center - half_height,
+                    b: center + half_height,
+                    radius: radius * size.x.max(size.z),
+                }
+            }
+            Shape::Mesh => {
+                let triangles: Vec<[Vec3; 3]> = mesh?.transformed_triangles(matrix).collect();
+                let bounds = Aabb::from_points(triangles.iter().flatten().copied())?;
+                WorldShape::Mesh { triangles, bounds }
+            }
+        })
+    }
+}
+
+impl Default for Collider {
+    fn default() -> Self {
+        Self::new(Shape::Sphere { radius: 0.5 })
+    }
+}
+
+inspect_fields!(Collider {
+    shape,
+    offset,
+    trigger,
+    layer: inspect::layers,
+    mask: inspect::layers,
+});
+
+impl Shape {
+    /// One of each kind, for picking a new shape
+    const EXAMPLES: [Shape; 4] = [
+        Shape::Sphere { radius: 0.5 },
+        Shape::Box { half_extents: glam::const_vec3!([0.5, 0.5, 0.5]) },
+        Shape::Capsule { radius: 0.3, half_height: 0.6 },
+        Shape::Mesh,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Shape::Sphere { .. } => "Sphere",
+            Shape::Box { .. } => "Box",
+            Shape::Capsule { .. } => "Capsule",
+            Shape::Mesh => "Mesh",
+        }
+    }
+}
+
+impl Inspect for Shape {
+    fn inspect(&mut self, ui: &mut Ui, label: &str) -> bool {
+        let before = *self;
+        egui::ComboBox::from_label(label)
+            .selected_text(self.name())
+            .show_ui(ui, |ui| {
+                for example in Shape::EXAMPLES.iter() {
+                    // switching keeps the current shape if it's already of that kind
+                    if ui.selectable_label(self.name() == example.name(), example.name()).clicked()
+                        && self.name() != example.name()
+                    {
+                        *self = *example;
+                    }
+                }
+            });
+        match self {
+            Shape::Sphere { radius } => {
+                inspect::row(ui, "radius", |ui| inspect::positive(ui, radius));
+            }
+            Shape::Box { half_extents } => {
+                half_extents.inspect(ui, "half extents");
+            }
+            Shape::Capsule { radius, half_height } => {
+                inspect::row(ui, "radius", |ui| inspect::positive(ui, radius));
+                inspect::row(ui, "half height", |ui| inspect::non_negative(ui, half_height));
+            }
+            Shape::Mesh => {}
+        }
+        *self != before
+    }
+}
+
+/// A collider moved to where its node is
+#[derive(Clone, Debug)]
+pub enum WorldShape {
+    Sphere { center: Vec3, radius: f32 },
+    Box(Obb),
+    Capsule { a: Vec3, b: Vec3, radius: f32 },
+    Mesh { triangles: Vec<[Vec3; 3]>, bounds: Aabb },
+}
+
+impl WorldShape {
+    pub fn bounds(&self) -> Aabb {
+        match self {
+            WorldShape::Sphere { center, radius } => Aabb::from_center(*center, Vec3::splat(*radius)),
+            WorldShape::Box(obb) => obb.bounds(),
+            WorldShape::Capsule { a, b, radius } => {
+                Aabb::new(a.min(*b) - Vec3::splat(*radius), a.max(*b) + Vec3::splat(*radius))
+            }
+            WorldShape::Mesh { bounds, .. } => *bounds,
+        }
+    }
+}
@@ -0,0 +1,357 @@
+use crate::math::{self, Aabb, Obb};
+use crate::physics::WorldShape;
+use glam::f32::Vec3;
+
+/// Two colliders touching each other
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Contact {
+    /// index of the first node in the scene
+    pub a: usize,
+    /// index of the second node in the scene
+    pub b: usize,
+    /// direction to push `b` away from `a`
+    pub normal: Vec3,
+    /// how far the two overlap along `normal`
+    pub depth: f32,
+    /// one of them is a trigger, so they only overlap without pushing each other
+    pub trigger: bool,
+}
+
+/// Edges are only pushed apart along when they overlap clearly less than the faces,
+/// resting boxes would otherwise jitter between both
+const EDGE_PREFERENCE: f32 = 0.95;
+
+/// Spheres and capsules are both a segment with a radius around it
+/// (for a sphere the segment is a single point)
+struct Rounded {
+    a: Vec3,
+    b: Vec3,
+    radius: f32,
+}
+
+impl WorldShape {
+    fn rounded(&self) -> Option<Rounded> {
+        match *self {
+            WorldShape::Sphere { center, radius } => Some(Rounded {
+                a: center,
+                b: center,
+                radius,
+            }),
+            WorldShape::Capsule { a, b, radius } => Some(Rounded { a, b, radius }),
+            _ => None,
+        }
+    }
+}
+
+/// Normal pointing from `shape_a` to `shape_b` and penetration depth, if they overlap
+pub fn collide(shape_a: &WorldShape, shape_b: &WorldShape) -> Option<(Vec3, f32)> {
+    if !shape_a.bounds().intersects(&shape_b.bounds()) {
+        return None;
+    }
+
+    let flip = |(normal, depth): (Vec3, f32)| (-normal, depth);
+
+    match (shape_a, shape_b) {
+        // not supported, `warn_unsupported` says so when the scene is loaded
+        (WorldShape::Mesh { .. }, WorldShape::Mesh { .. }) => None,
+        (WorldShape::Box(a), WorldShape::Box(b)) => box_box(a, b),
+        (WorldShape::Mesh { triangles, .. }, WorldShape::Box(obb)) => mesh_box(triangles, obb),
+        (WorldShape::Box(obb), WorldShape::Mesh { triangles, .. }) => mesh_box(triangles, obb).map(flip),
+        (WorldShape::Box(obb), other) => box_rounded(obb, &other.rounded()?),
+        (other, WorldShape::Box(obb)) => box_rounded(obb, &other.rounded()?).map(flip),
+        (WorldShape::Mesh { triangles, .. }, other) => mesh_rounded(triangles, &other.rounded()?),
+        (other, WorldShape::Mesh { triangles, .. }) => mesh_rounded(triangles, &other.rounded()?).map(flip),
+        (a, b) => rounded_rounded(&a.rounded()?, &b.rounded()?),
+    }
+}
+
+/// Turns the closest points of two shapes into a contact, if they are closer than `radius`.
+/// `fallback` is used as normal when the points coincide.
+fn separate(from: Vec3, to: Vec3, radius: f32, fallback: Vec3) -> Option<(Vec3, f32)> {
+    let offset = to - from;
+    let distance = offset.length();
+    if distance >= radius {
+        return None;
+    }
+    let normal = if distance > f32::EPSILON { offset / distance } else { fallback };
+    Some((normal, radius - distance))
+}
+
+fn rounded_rounded(a: &Rounded, b: &Rounded) -> Option<(Vec3, f32)> {
+    let (on_a, on_b) = math::closest_points_on_segments(a.a, a.b, b.a, b.b);
+    separate(on_a, on_b, a.radius + b.radius, Vec3::Y)
+}
+
+/// Separating axis test: the shapes are apart if they don't overlap on one of the axes,
+/// otherwise they're pushed apart along the one they overlap least on.
+/// Each candidate is a normal pointing towards the second shape and how far it has to move along it.
+fn least_overlap(
+    faces: impl IntoIterator<Item = (Vec3, f32)>,
+    edges: impl IntoIterator<Item = (Vec3, f32)>,
+) -> Option<(Vec3, f32)> {
+    let mut least: Option<(Vec3, f32)> = None;
+    for (normal, depth) in faces {
+        if depth <= 0.0 {
+            return None;
+        }
+        if least.is_none_or(|(_, least)| depth < least) {
+            least = Some((normal, depth));
+        }
+    }
+    for (normal, depth) in edges {
+        if depth <= 0.0 {
+            return None;
+        }
+        if least.is_none_or(|(_, least)| depth < least * EDGE_PREFERENCE) {
+            least = Some((normal, depth));
+        }
+    }
+    least
+}
+
+/// Directions perpendicular to an edge of each, leaving out parallel edges
+fn edge_axes<'a>(edges_a: &'a [Vec3], edges_b: &'a [Vec3]) -> impl Iterator<Item = Vec3> + 'a {
+    edges_a
+        .iter()
+        .flat_map(move |a| edges_b.iter().map(move |b| a.cross(*b)))
+        .filter(|axis| axis.length_squared() > 1e-6)
+        .map(Vec3::normalize)
+}
+
+fn box_box(a: &Obb, b: &Obb) -> Option<(Vec3, f32)> {
+    let offset = b.center - a.center;
+    let (axes_a, axes_b) = (a.axes(), b.axes());
+    let candidate = |axis: Vec3| {
+        let distance = offset.dot(axis);
+        let normal = if distance < 0.0 { -axis } else { axis };
+        (normal, a.projected_radius(axis) + b.projected_radius(axis) - distance.abs())
+    };
+    least_overlap(
+        axes_a.iter().chain(&axes_b).copied().map(candidate),
+        edge_axes(&axes_a, &axes_b).map(candidate),
+    )
+}
+
+fn box_rounded(obb: &Obb, rounded: &Rounded) -> Option<(Vec3, f32)> {
+    // seen from the box it's axis aligned
+    let local = Rounded {
+        a: obb.to_local(rounded.a),
+        b: obb.to_local(rounded.b),
+        radius: rounded.radius,
+    };
+    let (normal, depth) = aabb_rounded(&obb.local_box(), &local)?;
+    Some((obb.rotation * normal, depth))
+}
+
+fn aabb_rounded(aabb: &Aabb, rounded: &Rounded) -> Option<(Vec3, f32)> {
+    let (on_segment, on_box) = math::closest_points_segment_convex(rounded.a, rounded.b, |point| aabb.closest_point(point));
+
+    if on_segment != on_box {
+        return separate(on_box, on_segment, rounded.radius, Vec3::Y);
+    }
+
+    // the segment reaches into the box, push it out through the nearest face
+    let to_min = on_segment - aabb.min;
+    let to_max = aabb.max - on_segment;
+    let faces = [
+        (to_min.x, -Vec3::X),
+        (to_max.x, Vec3::X),
+        (to_min.y, -Vec3::Y),
+        (to_max.y, Vec3::Y),
+        (to_min.z, -Vec3::Z),
+        (to_max.z, Vec3::Z),
+    ];
+    let mut nearest = faces[0];
+    for &face in &faces[1..] {
+        if face.0 < nearest.0 {
+            nearest = face;
+        }
+    }
+    Some((nearest.1, nearest.0 + rounded.radius))
+}
+
+/// Deepest contact of `rounded` with any of the triangles
+fn mesh_rounded(triangles: &[[Vec3; 3]], rounded: &Rounded) -> Option<(Vec3, f32)> {
+    let bounds = Aabb::new(
+        rounded.a.min(rounded.b) - Vec3::splat(rounded.radius),
+        rounded.a.max(rounded.b) + Vec3::splat(rounded.radius),
+    );
+
+    let mut deepest: Option<(Vec3, f32)> = None;
+    for &triangle in triangles {
+        let triangle_bounds = Aabb::from_points(triangle.iter().copied()).unwrap();
+        if !bounds.intersects(&triangle_bounds) {
+            continue;
+        }
+
+        let (on_segment, on_triangle) = math::closest_points_segment_convex(rounded.a, rounded.b, |point| {
+            math::closest_point_on_triangle(point, triangle)
+        });
+        // triangles are wound clockwise seen from the outside
+        let [a, b, c] = triangle;
+        let outside = (c - a).cross(b - a).normalize_or_zero();
+
+        if let Some(contact) = separate(on_triangle, on_segment, rounded.radius, outside) {
+            let deeper = match deepest {
+                Some((_, depth)) => contact.1 > depth,
+                None => true,
+            };
+            if deeper {
+                deepest = Some(contact);
+            }
+        }
+    }
+    deepest
+}
+
+/// Deepest contact of the box with any of the triangles
+fn mesh_box(triangles: &[[Vec3; 3]], obb: &Obb) -> Option<(Vec3, f32)> {
+    let bounds = obb.bounds();
+    let box_axes = obb.axes();
+
+    let mut deepest: Option<(Vec3, f32)> = None;
+    for &triangle in triangles {
+        let triangle_bounds = Aabb::from_points(triangle.iter().copied()).unwrap();
+        if !bounds.intersects(&triangle_bounds) {
+            continue;
+        }
+
+        // pushed out to whichever side of the triangle is closer
+        let candidate = |axis: Vec3| {
+            let (box_min, box_max) = projection(obb, axis);
+            let (triangle_min, triangle_max) = triangle
+                .iter()
+                .map(|corner| corner.dot(axis))
+                .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), value| (min.min(value), max.max(value)));
+            let (up, down) = (triangle_max - box_min, box_max - triangle_min);
+            if up <= down {
+                (axis, up)
+            } else {
+                (-axis, down)
+            }
+        };
+        let [a, b, c] = triangle;
+        // triangles are wound clockwise seen from the outside, boxes are only pushed out that way
+        let outside = (c - a).cross(b - a).normalize_or_zero();
+        let face = (outside != Vec3::ZERO).then(|| {
+            let (box_min, box_max) = projection(obb, outside);
+            let plane = a.dot(outside);
+            (outside, if box_max > plane { plane - box_min } else { 0.0 })
+        });
+        let triangle_edges = [b - a, c - b, a - c];
+        let contact = least_overlap(
+            face.into_iter().chain(box_axes.iter().copied().map(candidate)),
+            edge_axes(&box_axes, &triangle_edges).map(candidate),
+        );
+
+        if let Some(contact) = contact {
+            if deepest.is_none_or(|(_, depth)| contact.1 > depth) {
+                deepest = Some(contact);
+            }
+        }
+    }
+    deepest
+}
+
+/// Where the box starts and ends along `axis`
+fn projection(obb: &Obb, axis: Vec3) -> (f32, f32) {
+    let center = obb.center.dot(axis);
+    let radius = obb.projected_radius(axis);
+    (center - radius, center + radius)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::{Collider, Shape};
+    use crate::scene::Transform;
+    use glam::f32::Quat;
+    use std::f32::consts::FRAC_PI_4;
+
+    fn assert_contact(contact: Option<(Vec3, f32)>, normal: Vec3, depth: f32) {
+        let (found_normal, found_depth) = contact.expect("no contact");
+        assert!(found_normal.abs_diff_eq(normal, 1e-4), "normal {} instead of {}", found_normal, normal);
+        assert!((found_depth - depth).abs() < 1e-4, "depth {} instead of {}", found_depth, depth);
+    }
+
+    fn cube(center: Vec3, rotation: Quat) -> WorldShape {
+        WorldShape::Box(Obb::new(center, rotation, Vec3::splat(0.5)))
+    }
+
+    /// Two triangles from -2 to 2 on x and z, facing up
+    fn floor() -> WorldShape {
+        let corners = [
+            Vec3::new(-2.0, 0.0, -2.0),
+            Vec3::new(2.0, 0.0, -2.0),
+            Vec3::new(2.0, 0.0, 2.0),
+            Vec3::new(-2.0, 0.0, 2.0),
+        ];
+        let triangles = vec![[corners[0], corners[1], corners[3]], [corners[1], corners[2], corners[3]]];
+        let bounds = Aabb::from_points(corners.iter().copied()).unwrap();
+        WorldShape::Mesh { triangles, bounds }
+    }
+
+    #[test]
+    fn boxes() {
+        let a = cube(Vec3::ZERO, Quat::IDENTITY);
+        assert_contact(collide(&a, &cube(Vec3::new(0.8, 0.1, 0.0), Quat::IDENTITY)), Vec3::X, 0.2);
+        assert_contact(collide(&a, &cube(Vec3::new(0.0, -0.9, 0.05), Quat::IDENTITY)), -Vec3::Y, 0.1);
+        assert_eq!(collide(&a, &cube(Vec3::new(1.1, 0.0, 0.0), Quat::IDENTITY)), None);
+    }
+
+    #[test]
+    fn rotated_boxes() {
+        let a = cube(Vec3::ZERO, Quat::IDENTITY);
+        let turned = Quat::from_rotation_y(FRAC_PI_4);
+        // a corner pointing at the other box reaches further than its side would
+        let corner = 0.5 * std::f32::consts::SQRT_2;
+        assert_contact(collide(&a, &cube(Vec3::new(0.5 + corner - 0.1, 0.0, 0.0), turned)), Vec3::X, 0.1);
+        // their bounds overlap, the boxes don't
+        let b = cube(Vec3::new(0.9, 0.0, 0.9), turned);
+        assert!(a.bounds().intersects(&b.bounds()));
+        assert_eq!(collide(&a, &b), None);
+    }
+
+    #[test]
+    fn sphere_on_a_mesh() {
+        let sphere = WorldShape::Sphere { center: Vec3::new(0.3, 0.4, -0.2), radius: 0.5 };
+        assert_contact(collide(&floor(), &sphere), Vec3::Y, 0.1);
+        assert_contact(collide(&sphere, &floor()), -Vec3::Y, 0.1);
+        let above = WorldShape::Sphere { center: Vec3::new(0.3, 0.6, -0.2), radius: 0.5 };
+        assert_eq!(collide(&floor(), &above), None);
+    }
+
+    #[test]
+    fn box_on_a_mesh() {
+        assert_contact(collide(&floor(), &cube(Vec3::new(1.0, 0.4, 0.5), Quat::IDENTITY)), Vec3::Y, 0.1);
+        // standing on an edge it reaches deeper
+        let tipped = cube(Vec3::new(0.0, 0.6, 0.0), Quat::from_rotation_z(FRAC_PI_4));
+        assert_contact(collide(&floor(), &tipped), Vec3::Y, 0.5 * std::f32::consts::SQRT_2 - 0.6);
+        assert_eq!(collide(&floor(), &cube(Vec3::new(1.0, 0.6, 0.5), Quat::IDENTITY)), None);
+        assert_eq!(collide(&floor(), &floor()), None);
+    }
+
+    #[test]
+    fn capsule_on_a_box() {
+        let ground = cube(Vec3::ZERO, Quat::IDENTITY);
+        let capsule = WorldShape::Capsule { a: Vec3::new(0.1, 0.7, 0.0), b: Vec3::new(0.1, 1.7, 0.0), radius: 0.3 };
+        assert_contact(collide(&ground, &capsule), Vec3::Y, 0.1);
+        let beside = WorldShape::Capsule { a: Vec3::new(0.7, -0.5, 0.0), b: Vec3::new(0.7, 0.5, 0.0), radius: 0.3 };
+        assert_contact(collide(&ground, &beside), Vec3::X, 0.1);
+        assert_contact(collide(&beside, &ground), -Vec3::X, 0.1);
+    }
+
+    #[test]
+    fn capsules_turn_with_their_node() {
+        let collider = Collider::new(Shape::Capsule { radius: 0.25, half_height: 1.0 });
+        let transform = Transform {
+            rotation: Quat::from_rotation_z(std::f32::consts::FRAC_PI_2),
+            ..Transform::default()
+        };
+        let lying = collider.world_shape(&transform, None).unwrap();
+        let ground = cube(Vec3::new(0.0, -0.7, 0.0), Quat::IDENTITY);
+        assert_contact(collide(&ground, &lying), Vec3::Y, 0.05);
+        let beside = cube(Vec3::new(1.7, 0.0, 0.0), Quat::IDENTITY);
+        assert_contact(collide(&beside, &lying), -Vec3::X, 0.05);
+    }
+}
@@ -0,0 +1,68 @@
+use crate::physics::Contact;
+use crate::scene::{NodeId, Scene};
+use std::collections::BTreeMap;
+
+/// Something gameplay may want to react to, reported once when it happens
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PhysicsEvent {
+    CollisionBegin(NodeId, NodeId),
+    CollisionEnd(NodeId, NodeId),
+    TriggerEnter { trigger: NodeId, other: NodeId },
+    TriggerExit { trigger: NodeId, other: NodeId },
+}
+
+/// How two nodes touch
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Touch {
+    Collision,
+    Trigger { trigger: NodeId, other: NodeId },
+}
+
+/// Remembers which nodes touched in the last step, to turn contacts into begin and end events
+#[derive(Clone, Debug, Default)]
+pub struct ContactTracker {
+    /// sorted so the events come out in the same order every time
+    touching: BTreeMap<(NodeId, NodeId), Touch>,
+}
+
+impl ContactTracker {
+    /// Compares the `contacts` of the step that just ran with the ones of the step before
+    pub fn update(&mut self, scene: &Scene, contacts: &[Contact]) -> Vec<PhysicsEvent> {
+        let mut touching = BTreeMap::new();
+        for contact in contacts {
+            let a = scene.nodes[contact.a].id;
+            let b = scene.nodes[contact.b].id;
+            let touch = if contact.trigger {
+                if matches!(&scene.nodes[contact.a].collider, Some(collider) if collider.trigger) {
+                    Touch::Trigger { trigger: a, other: b }
+                } else {
+                    Touch::Trigger { trigger: b, other: a }
+                }
+            } else {
+                Touch::Collision
+            };
+            touching.insert((a.min(b), a.max(b)), touch);
+        }
+
+        let mut events = Vec::new();
+        for (&(a, b), &touch) in &self.touching {
+            if !touching.contains_key(&(a, b)) {
+                events.push(match touch {
+                    Touch::Collision => PhysicsEvent::CollisionEnd(a, b),
+                    Touch::Trigger { trigger, other } => PhysicsEvent::TriggerExit { trigger, other },
+                });
+            }
+        }
+        for (&(a, b), &touch) in &touching {
+            if !self.touching.contains_key(&(a, b)) {
+                events.push(match touch {
+                    Touch::Collision => PhysicsEvent::CollisionBegin(a, b),
+                    Touch::Trigger { trigger, other } => PhysicsEvent::TriggerEnter { trigger, other },
+                });
+            }
+        }
+
+        self.touching = touching;
+        events
+    }
+}
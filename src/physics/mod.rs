@@ -0,0 +1,11 @@
+mod body;
+mod collider;
+mod contact;
+mod events;
+mod world;
+
+pub use body::*;
+pub use collider::*;
+pub use contact::*;
+pub use events::*;
+pub use world::*;
@@ -0,0 +1,234 @@
+use crate::console::{self, Registry};
+use crate::mesh::MeshLibrary;
+use crate::physics::{collide, layers_interact, BodyKind, Contact, RigidBody, Shape, WorldShape, ALL_LAYERS, DEFAULT_LAYER};
+use crate::scene::{Node, Scene};
+use glam::f32::Vec3;
+
+pub const DEFAULT_GRAVITY: Vec3 = glam::const_vec3!([0.0, -9.81, 0.0]);
+
+/// How often the contacts are solved per step, more is stiffer but slower
+const SOLVER_ITERATIONS: usize = 4;
+/// Bodies hitting each other slower than this don't bounce, so resting bodies stay at rest
+const BOUNCE_THRESHOLD: f32 = 0.5;
+/// Overlap that is left alone, avoids jitter of resting bodies
+const PENETRATION_SLOP: f32 = 0.005;
+/// Share of the remaining overlap resolved per step
+const POSITION_CORRECTION: f32 = 0.8;
+
+/// A collider placed in the world, with the index of its node
+pub struct PlacedCollider {
+    pub node: usize,
+    pub shape: WorldShape,
+    pub trigger: bool,
+    pub layer: u32,
+    pub mask: u32,
+}
+
+impl PlacedCollider {
+    pub fn interacts_with(&self, other: &PlacedCollider) -> bool {
+        layers_interact(self.layer, self.mask, other.layer, other.mask)
+    }
+}
+
+/// Collider of a node, with the body it moves with
+struct Participant {
+    collider: PlacedCollider,
+    body: RigidBody,
+}
+
+/// Console commands of the physics
+pub fn register_commands(registry: &mut Registry) {
+    registry.command("gravity", "gravity [x y z]: shows or sets the gravity of the scene", |context, args| {
+        if !args.is_empty() {
+            context.scene.gravity = console::parse_vec3(args)?;
+        }
+        Ok(format!("gravity is {}", context.scene.gravity))
+    });
+}
+
+/// Advances every body in `scene` by `dt` seconds, resolves collisions
+/// and returns the contacts that were found.
+///
+/// Colliders are placed with their world transform, but bodies move their node's
+/// own location, so bodies below rotated or scaled parents don't move as expected.
+///
+/// Nodes are visited in order and nothing depends on time or randomness,
+/// so the same scene always steps to the same result.
+pub fn step(scene: &mut Scene, meshes: &MeshLibrary, dt: f32) -> Vec<Contact> {
+    let gravity = scene.gravity;
+
+    for node in &mut scene.nodes {
+        if node.controller.is_some() {
+            // moved by `controller::update` instead
+            continue;
+        }
+        if let Some(body) = &mut node.body {
+            match body.kind {
+                BodyKind::Dynamic => {
+                    body.velocity += gravity * body.gravity_scale * dt;
+                    node.transform.location += body.velocity * dt;
+                }
+                BodyKind::Kinematic => node.transform.location += body.velocity * dt,
+                BodyKind::Static => {}
+            }
+        }
+    }
+
+    let mut participants: Vec<Participant> = world_shapes(scene, meshes)
+        .into_iter()
+        .map(|collider| Participant {
+            body: participant_body(&scene.nodes[collider.node]),
+            collider,
+        })
+        .collect();
+
+    let mut contacts = Vec::new();
+    let mut pairs = Vec::new();
+    for a in 0..participants.len() {
+        for b in a + 1..participants.len() {
+            let (first, second) = (&participants[a], &participants[b]);
+            if first.body.kind == BodyKind::Static && second.body.kind == BodyKind::Static {
+                continue;
+            }
+            if first.collider.trigger && second.collider.trigger {
+                continue;
+            }
+            if !first.collider.interacts_with(&second.collider) {
+                continue;
+            }
+            if let Some((normal, depth)) = collide(&first.collider.shape, &second.collider.shape) {
+                contacts.push(Contact {
+                    a: first.collider.node,
+                    b: second.collider.node,
+                    normal,
+                    depth,
+                    trigger: first.collider.trigger || second.collider.trigger,
+                });
+                pairs.push((a, b));
+            }
+        }
+    }
+
+    for _ in 0..SOLVER_ITERATIONS {
+        for (contact, &(a, b)) in contacts.iter().zip(&pairs) {
+            if !contact.trigger {
+                resolve_velocity(&mut participants, a, b, contact);
+            }
+        }
+    }
+
+    for (contact, &(a, b)) in contacts.iter().zip(&pairs) {
+        if contact.trigger {
+            continue;
+        }
+        let inverse_a = participants[a].body.inverse_mass();
+        let inverse_b = participants[b].body.inverse_mass();
+        let total = inverse_a + inverse_b;
+        if total <= 0.0 {
+            continue;
+        }
+        let correction = contact.normal * ((contact.depth - PENETRATION_SLOP).max(0.0) * POSITION_CORRECTION / total);
+        scene.nodes[participants[a].collider.node].transform.location -= correction * inverse_a;
+        scene.nodes[participants[b].collider.node].transform.location += correction * inverse_b;
+    }
+
+    for participant in participants {
+        if let Some(body) = &mut scene.nodes[participant.collider.node].body {
+            body.velocity = participant.body.velocity;
+        }
+    }
+
+    contacts
+}
+
+/// Colliders of every node that can collide, placed in the world.
+/// Characters without a collider of their own use their capsule.
+pub fn world_shapes(scene: &Scene, meshes: &MeshLibrary) -> Vec<PlacedCollider> {
+    (0..scene.nodes.len()).filter_map(|index| placed_collider(scene, meshes, index)).collect()
+}
+
+/// The collider of the node at `index` as `world_shapes` places it, `None` if it can't collide
+pub fn placed_collider(scene: &Scene, meshes: &MeshLibrary, index: usize) -> Option<PlacedCollider> {
+    let node = &scene.nodes[index];
+    match (&node.collider, &node.controller) {
+        (Some(collider), _) => Some(PlacedCollider {
+            node: index,
+            shape: collider.world_shape(&scene.world_transform(node), node.mesh.as_ref().and_then(|id| meshes.get(id)))?,
+            trigger: collider.trigger,
+            layer: collider.layer,
+            mask: collider.mask,
+        }),
+        (None, Some(controller)) => Some(PlacedCollider {
+            node: index,
+            shape: controller.shape(scene.world_transform(node).location),
+            trigger: false,
+            layer: DEFAULT_LAYER,
+            mask: ALL_LAYERS,
+        }),
+        (None, None) => None,
+    }
+}
+
+/// Warns about colliders that won't collide the way the scene expects them to:
+/// mesh colliders don't collide with each other, so a moving one passes through the rest
+pub fn warn_unsupported(scene: &Scene) {
+    let is_mesh = |node: &&Node| matches!(&node.collider, Some(collider) if collider.shape == Shape::Mesh && !collider.trigger);
+    if scene.nodes.iter().filter(is_mesh).count() < 2 {
+        return;
+    }
+    for node in scene.nodes.iter().filter(is_mesh) {
+        if matches!(&node.body, Some(body) if body.kind != BodyKind::Static) {
+            log::warn!("{} has a moving mesh collider, it passes through the other mesh colliders", node.name);
+        }
+    }
+}
+
+/// How a node is moved by collisions
+fn participant_body(node: &Node) -> RigidBody {
+    if let Some(controller) = &node.controller {
+        // characters push bodies away but only move on their own
+        let mut body = RigidBody::new(BodyKind::Kinematic);
+        body.velocity = controller.velocity;
+        return body;
+    }
+    // colliders without a body act like static ones
+    node.body.clone().unwrap_or_else(|| RigidBody::new(BodyKind::Static))
+}
+
+/// Applies the bounce and friction impulses between two participants
+fn resolve_velocity(participants: &mut [Participant], a: usize, b: usize, contact: &Contact) {
+    let body_a = &participants[a].body;
+    let body_b = &participants[b].body;
+    let inverse_a = body_a.inverse_mass();
+    let inverse_b = body_b.inverse_mass();
+    let total = inverse_a + inverse_b;
+    if total <= 0.0 {
+        return;
+    }
+
+    let relative = body_b.velocity - body_a.velocity;
+    let normal_speed = relative.dot(contact.normal);
+    if normal_speed >= 0.0 {
+        // already moving apart
+        return;
+    }
+
+    let restitution = if -normal_speed < BOUNCE_THRESHOLD {
+        0.0
+    } else {
+        body_a.restitution.max(body_b.restitution)
+    };
+    let normal_impulse = -(1.0 + restitution) * normal_speed / total;
+    let mut impulse = contact.normal * normal_impulse;
+
+    let tangent_velocity = relative - contact.normal * normal_speed;
+    let tangent_speed = tangent_velocity.length();
+    if tangent_speed > f32::EPSILON {
+        let friction = (body_a.friction * body_b.friction).sqrt();
+        let tangent_impulse = (tangent_speed / total).min(friction * normal_impulse);
+        impulse -= tangent_velocity / tangent_speed * tangent_impulse;
+    }
+
+    participants[a].body.velocity -= impulse * inverse_a;
+    participants[b].body.velocity += impulse * inverse_b;
+}
@@ -0,0 +1,532 @@
+use crate::assets::{Assets, Handle, Loadable};
+use crate::error;
+use crate::history::{Command, History};
+use crate::scene::{Node, NodeId, Scene};
+use crate::toasts::Toasts;
+use egui::Ui;
+use glam::f32::Vec3;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A property of a node that an instance can keep different from its prefab
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Property {
+    Name,
+    Visible,
+    Locked,
+    Location,
+    Rotation,
+    Size,
+    Color,
+    Mesh,
+    Body,
+    Collider,
+    Controller,
+    Script,
+    Light,
+}
+
+impl Property {
+    pub const ALL: [Property; 13] = [
+        Property::Name,
+        Property::Visible,
+        Property::Locked,
+        Property::Location,
+        Property::Rotation,
+        Property::Size,
+        Property::Color,
+        Property::Mesh,
+        Property::Body,
+        Property::Collider,
+        Property::Controller,
+        Property::Script,
+        Property::Light,
+    ];
+
+    /// Where an instance stands is its own, the root always keeps these
+    const PLACEMENT: [Property; 2] = [Property::Location, Property::Rotation];
+
+    fn differs(self, a: &Node, b: &Node) -> bool {
+        match self {
+            Property::Name => a.name != b.name,
+            Property::Visible => a.visible != b.visible,
+            Property::Locked => a.locked != b.locked,
+            Property::Location => a.transform.location != b.transform.location,
+            Property::Rotation => a.transform.rotation != b.transform.rotation,
+            Property::Size => a.transform.size != b.transform.size,
+            Property::Color => a.color != b.color,
+            Property::Mesh => a.mesh != b.mesh,
+            Property::Body => a.body != b.body,
+            Property::Collider => a.collider != b.collider,
+            Property::Controller => a.controller != b.controller,
+            Property::Script => a.script != b.script,
+            Property::Light => a.light != b.light,
+        }
+    }
+
+    fn copy(self, from: &Node, to: &mut Node) {
+        match self {
+            Property::Name => to.name = from.name.clone(),
+            Property::Visible => to.visible = from.visible,
+            Property::Locked => to.locked = from.locked,
+            Property::Location => to.transform.location = from.transform.location,
+            Property::Rotation => to.transform.rotation = from.transform.rotation,
+            Property::Size => to.transform.size = from.transform.size,
+            Property::Color => to.color = from.color,
+            Property::Mesh => to.mesh = from.mesh.clone(),
+            Property::Body => to.body = from.body.clone(),
+            Property::Collider => to.collider = from.collider.clone(),
+            Property::Controller => to.controller = from.controller.clone(),
+            Property::Script => to.script = from.script.clone(),
+            Property::Light => to.light = from.light.clone(),
+        }
+    }
+}
+
+/// Where a node of a prefab instance comes from
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PrefabLink {
+    pub source: PathBuf,
+    /// id of the node in the prefab file
+    pub node: NodeId,
+    /// properties changed on this instance, they are kept when the prefab changes
+    #[serde(default)]
+    pub overrides: Vec<Property>,
+}
+
+/// Nodes saved to be placed many times. The first node without a parent is the root,
+/// nodes that aren't below it are ignored.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Prefab {
+    pub nodes: Vec<Node>,
+}
+
+impl Prefab {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let text = fs::read_to_string(path)?;
+        let prefab: Self = ron::from_str(&text)?;
+        if prefab.root().is_none() {
+            return Err("the prefab has no root node".into());
+        }
+        Ok(prefab)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::new())?;
+        fs::write(path, text)?;
+        Ok(())
+    }
+
+    pub fn root(&self) -> Option<&Node> {
+        self.nodes.iter().find(|node| node.parent.is_none())
+    }
+
+    pub fn node(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    /// The root and everything below it, in file order
+    fn members(&self) -> Vec<&Node> {
+        let mut ids: HashSet<NodeId> = self.root().map(|root| root.id).into_iter().collect();
+        // a parent can come after its children, a loop ends when nothing new is found
+        loop {
+            let count = ids.len();
+            for node in &self.nodes {
+                if matches!(node.parent, Some(parent) if ids.contains(&parent)) {
+                    ids.insert(node.id);
+                }
+            }
+            if ids.len() == count {
+                break;
+            }
+        }
+        self.nodes.iter().filter(|node| ids.contains(&node.id)).collect()
+    }
+
+    /// Linked copies of the nodes, with the ids of the file, to be added with `History::insert_nodes`
+    pub fn instantiate(&self, source: &Path) -> Vec<Node> {
+        let root = self.root().map(|root| root.id);
+        self.members()
+            .into_iter()
+            .map(|node| {
+                let mut node = node.clone();
+                let overrides = if Some(node.id) == root { Property::PLACEMENT.to_vec() } else { Vec::new() };
+                node.prefab = Some(PrefabLink {
+                    source: source.to_path_buf(),
+                    node: node.id,
+                    overrides,
+                });
+                node
+            })
+            .collect()
+    }
+}
+
+impl Loadable for Prefab {
+    fn load(path: &Path) -> Result<Self, error::Error> {
+        Prefab::load(path).map_err(|err| error::Error::file(path, err))
+    }
+}
+
+/// Makes the properties that differ between `before` and `after` overrides, if `after` is part of an instance
+pub fn mark_overrides(before: &Node, after: &mut Node) {
+    let changed: Vec<Property> = Property::ALL.iter().copied().filter(|property| property.differs(before, after)).collect();
+    if let Some(link) = &mut after.prefab {
+        for property in changed {
+            if !link.overrides.contains(&property) {
+                link.overrides.push(property);
+            }
+        }
+    }
+}
+
+fn is_instance_root(node: &Node, source: &Path, prefab_root: NodeId) -> bool {
+    matches!(&node.prefab, Some(link) if link.source == source && link.node == prefab_root)
+}
+
+/// The instance root `id` belongs to, if it's part of an instance of the prefab at `source`
+pub fn instance_root(scene: &Scene, id: NodeId, source: &Path, prefab: &Prefab) -> Option<NodeId> {
+    let prefab_root = prefab.root()?.id;
+    let mut node = scene.node(id)?;
+    for _ in 0..scene.nodes.len() {
+        if is_instance_root(node, source, prefab_root) {
+            return Some(node.id);
+        }
+        node = scene.node(node.parent?)?;
+    }
+    None
+}
+
+/// `root` and everything below it, except for other instances of the same prefab
+fn instance_nodes(scene: &Scene, root: NodeId, source: &Path, prefab_root: NodeId) -> Vec<NodeId> {
+    let subtree = scene.subtree(root);
+    let nested: Vec<NodeId> = subtree
+        .iter()
+        .copied()
+        .filter(|&id| id != root && matches!(scene.node(id), Some(node) if is_instance_root(node, source, prefab_root)))
+        .collect();
+    subtree
+        .into_iter()
+        .filter(|&id| !nested.iter().any(|&other| id == other || scene.is_descendant(id, other)))
+        .collect()
+}
+
+/// The linked nodes of the instance at `root`, by their id in the prefab
+fn instance_members(scene: &Scene, root: NodeId, source: &Path, prefab_root: NodeId) -> HashMap<NodeId, NodeId> {
+    instance_nodes(scene, root, source, prefab_root)
+        .into_iter()
+        .filter_map(|id| match &scene.node(id)?.prefab {
+            Some(link) if link.source == source => Some((link.node, id)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Commands bringing every instance of `prefab` up to date with it, keeping their overrides.
+/// Nodes added to the prefab are added to the instances, removed ones are removed.
+pub fn propagate(scene: &Scene, source: &Path, prefab: &Prefab) -> Vec<Command> {
+    let prefab_root = match prefab.root() {
+        Some(root) => root.id,
+        None => return Vec::new(),
+    };
+    let prefab_nodes = prefab.members();
+    let in_prefab: HashSet<NodeId> = prefab_nodes.iter().map(|node| node.id).collect();
+
+    let mut commands = Vec::new();
+    let mut added = Vec::new();
+    let mut removed = Vec::new();
+    let mut next_id = scene.next_id().0;
+    for root in scene.nodes.iter().filter(|node| is_instance_root(node, source, prefab_root)) {
+        let members = instance_members(scene, root.id, source, prefab_root);
+        // all ids first, a parent can come after its children
+        let mut ids = members.clone();
+        for prefab_node in &prefab_nodes {
+            ids.entry(prefab_node.id).or_insert_with(|| {
+                next_id += 1;
+                NodeId(next_id - 1)
+            });
+        }
+
+        for &prefab_node in &prefab_nodes {
+            match members.get(&prefab_node.id).and_then(|&id| scene.node(id)) {
+                Some(node) => {
+                    let mut after = node.clone();
+                    let overrides = node.prefab.as_ref().map(|link| link.overrides.as_slice()).unwrap_or_default();
+                    for property in Property::ALL.iter().filter(|property| !overrides.contains(property)) {
+                        property.copy(prefab_node, &mut after);
+                    }
+                    if after != *node {
+                        commands.push(Command::Change {
+                            id: node.id,
+                            before: Box::new(node.clone()),
+                            after: Box::new(after),
+                        });
+                    }
+                }
+                None => {
+                    let mut node = prefab_node.clone();
+                    node.id = ids[&prefab_node.id];
+                    node.parent = prefab_node.parent.and_then(|parent| ids.get(&parent).copied());
+                    node.prefab = Some(PrefabLink {
+                        source: source.to_path_buf(),
+                        node: prefab_node.id,
+                        overrides: Vec::new(),
+                    });
+                    added.push(node);
+                }
+            }
+        }
+
+        for (prefab_id, &id) in &members {
+            if !in_prefab.contains(prefab_id) {
+                removed.extend(scene.subtree(id).into_iter().filter_map(|id| scene.index_of(id)));
+            }
+        }
+    }
+
+    for (offset, node) in added.into_iter().enumerate() {
+        commands.push(Command::Add {
+            index: scene.nodes.len() + offset,
+            node: Box::new(node),
+        });
+    }
+    // from the back, so the indices of the ones before stay valid
+    removed.sort_unstable();
+    removed.dedup();
+    for &index in removed.iter().rev() {
+        commands.push(Command::Remove {
+            index,
+            node: Box::new(scene.nodes[index].clone()),
+        });
+    }
+    commands
+}
+
+/// Writes the nodes at and below `root` to the prefab file at `source`, overrides included,
+/// and links them to it. `prefab` is what the file held before, if it's an instance already.
+pub fn save_instance(
+    scene: &mut Scene,
+    history: &mut History,
+    root: NodeId,
+    source: &Path,
+    prefab: Option<&Prefab>,
+) -> Result<(), Box<dyn Error>> {
+    let old_root = prefab.and_then(|prefab| prefab.root());
+    let nodes = match old_root {
+        Some(old_root) => instance_nodes(scene, root, source, old_root.id),
+        None => scene.subtree(root),
+    };
+
+    // linked nodes keep their id in the file, the others get new ones
+    let mut prefab_ids = HashMap::new();
+    for &id in &nodes {
+        if let Some(link) = scene.node(id).and_then(|node| node.prefab.as_ref()).filter(|link| link.source == source) {
+            prefab_ids.insert(id, link.node);
+        }
+    }
+    let mut next_id = prefab_ids.values().map(|id| id.0).max().unwrap_or(0) + 1;
+    for &id in &nodes {
+        prefab_ids.entry(id).or_insert_with(|| {
+            next_id += 1;
+            NodeId(next_id - 1)
+        });
+    }
+
+    let mut saved = Prefab::default();
+    let mut commands = Vec::new();
+    for &id in &nodes {
+        let node = scene.node(id).unwrap();
+        let mut prefab_node = node.clone();
+        prefab_node.id = prefab_ids[&id];
+        prefab_node.prefab = None;
+        let overrides = if id == root {
+            prefab_node.parent = None;
+            // the placement of this instance isn't the one of all the others
+            match old_root {
+                Some(old_root) => {
+                    prefab_node.transform.location = old_root.transform.location;
+                    prefab_node.transform.rotation = old_root.transform.rotation;
+                }
+                None => prefab_node.transform.location = Vec3::ZERO,
+            }
+            Property::PLACEMENT.to_vec()
+        } else {
+            prefab_node.parent = node.parent.and_then(|parent| prefab_ids.get(&parent).copied());
+            Vec::new()
+        };
+        saved.nodes.push(prefab_node);
+
+        let mut after = node.clone();
+        after.prefab = Some(PrefabLink {
+            source: source.to_path_buf(),
+            node: prefab_ids[&id],
+            overrides,
+        });
+        if after != *node {
+            commands.push(Command::Change {
+                id,
+                before: Box::new(node.clone()),
+                after: Box::new(after),
+            });
+        }
+    }
+
+    saved.save(source)?;
+    if !commands.is_empty() {
+        history.execute(scene, &format!("Save prefab {}", file_name(source)), Command::Group(commands));
+    }
+    Ok(())
+}
+
+/// Prefab files the scene uses, loaded in the background and reloaded when they change
+#[derive(Default)]
+pub struct Prefabs {
+    assets: Assets<Prefab>,
+    used: HashMap<PathBuf, Handle<Prefab>>,
+    /// version of each prefab its instances were last brought up to date with
+    synced: HashMap<PathBuf, u64>,
+}
+
+impl Prefabs {
+    pub fn get(&self, source: &Path) -> Option<&Prefab> {
+        self.assets.get_path(source)
+    }
+
+    /// Loads `source` right away instead of in the background, e.g. to place it
+    pub fn load_now(&mut self, source: &Path) -> Result<&Prefab, error::Error> {
+        let handle = self.assets.load(source);
+        self.assets.wait();
+        if let Some(error) = self.assets.error(&handle) {
+            return Err(error.clone());
+        }
+        Ok(self.assets.get_path(source).expect("a prefab that didn't fail is loaded after waiting"))
+    }
+
+    /// Prefabs that failed to load since the last call
+    pub fn take_errors(&mut self) -> Vec<error::Error> {
+        self.assets.take_errors()
+    }
+
+    /// Keeps the prefabs of `scene` loaded and updates their instances when a prefab changed on disk
+    pub fn update(&mut self, scene: &mut Scene, history: &mut History) {
+        let sources: HashSet<PathBuf> = scene
+            .nodes
+            .iter()
+            .filter_map(|node| node.prefab.as_ref().map(|link| link.source.clone()))
+            .collect();
+        self.used.retain(|source, _| sources.contains(source));
+        self.synced.retain(|source, _| sources.contains(source));
+        for source in sources {
+            if !self.used.contains_key(&source) {
+                let handle = self.assets.load(&source);
+                self.used.insert(source, handle);
+            }
+        }
+        self.assets.update();
+
+        for (source, handle) in &self.used {
+            let version = self.assets.version(handle);
+            if version == 0 || self.synced.get(source) == Some(&version) {
+                continue;
+            }
+            self.synced.insert(source.clone(), version);
+            if let Some(prefab) = self.assets.get(handle) {
+                let commands = propagate(scene, source, prefab);
+                if !commands.is_empty() {
+                    history.execute(scene, &format!("Update {}", file_name(source)), Command::Group(commands));
+                }
+            }
+        }
+    }
+}
+
+/// Inspector section for the prefab side of a node: its overrides, or saving it as a prefab
+pub fn show(ui: &mut Ui, scene: &mut Scene, history: &mut History, prefabs: &mut Prefabs, toasts: &mut Toasts, id: NodeId) {
+    let link = match scene.node(id) {
+        Some(node) => node.prefab.clone(),
+        None => return,
+    };
+    ui.separator();
+    let link = match link {
+        Some(link) => link,
+        None => {
+            if ui.button("Save as prefab…").clicked() {
+                let name = format!("{}.prefab.ron", scene.node(id).unwrap().name);
+                if let Some(path) = tinyfiledialogs::save_file_dialog("Save prefab", &name) {
+                    if let Err(err) = save_instance(scene, history, id, Path::new(&path), None) {
+                        toasts.error(format!("Could not save prefab {}: {}", path, err));
+                    }
+                }
+            }
+            return;
+        }
+    };
+
+    ui.label(format!("Instance of {}", file_name(&link.source)));
+    let prefab = match prefabs.get(&link.source) {
+        Some(prefab) => prefab.clone(),
+        None => {
+            ui.label("The prefab isn't loaded");
+            return;
+        }
+    };
+
+    for &property in &link.overrides {
+        ui.horizontal(|ui| {
+            ui.label(format!("{:?} overridden", property));
+            let source = prefab.node(link.node);
+            if ui.add(egui::Button::new("Revert").enabled(source.is_some())).clicked() {
+                let node = scene.node(id).unwrap();
+                let mut after = node.clone();
+                property.copy(source.unwrap(), &mut after);
+                if let Some(link) = &mut after.prefab {
+                    link.overrides.retain(|&other| other != property);
+                }
+                let command = Command::Change {
+                    id,
+                    before: Box::new(node.clone()),
+                    after: Box::new(after),
+                };
+                history.execute(scene, &format!("Revert {:?}", property), command);
+            }
+        });
+    }
+
+    let root = instance_root(scene, id, &link.source, &prefab);
+    ui.horizontal(|ui| {
+        if ui.add(egui::Button::new("Apply to prefab").enabled(root.is_some())).clicked() {
+            if let Err(err) = save_instance(scene, history, root.unwrap(), &link.source, Some(&prefab)) {
+                toasts.error(format!("Could not save prefab {}: {}", link.source.display(), err));
+            }
+        }
+        // unlinked nodes stay as they are, the prefab can't change them anymore
+        if ui.add(egui::Button::new("Unpack").enabled(root.is_some())).clicked() {
+            let prefab_root = prefab.root().unwrap().id;
+            let commands = instance_nodes(scene, root.unwrap(), &link.source, prefab_root)
+                .into_iter()
+                .filter_map(|id| {
+                    let node = scene.node(id)?;
+                    node.prefab.as_ref()?;
+                    let mut after = node.clone();
+                    after.prefab = None;
+                    Some(Command::Change {
+                        id,
+                        before: Box::new(node.clone()),
+                        after: Box::new(after),
+                    })
+                })
+                .collect();
+            history.execute(scene, &format!("Unpack {}", file_name(&link.source)), Command::Group(commands));
+        }
+    });
+}
+
+fn file_name(path: &Path) -> String {
+    match path.file_name() {
+        Some(name) => name.to_string_lossy().into_owned(),
+        None => path.display().to_string(),
+    }
+}
@@ -0,0 +1,90 @@
+use crate::math::Viewport;
+use crate::mesh::{GpuMeshes, MeshId};
+use crate::render::{screen_size, LodSelection};
+use crate::scene::Scene;
+use glam::f32::Mat4;
+use glium::implement_vertex;
+use std::collections::HashMap;
+
+/// What differs between the copies of a mesh drawn in one call
+#[derive(Copy, Clone, Debug)]
+pub struct Instance {
+    model: [[f32; 4]; 4],
+    rgba: [f32; 4],
+}
+
+implement_vertex!(Instance, model, rgba);
+
+impl Instance {
+    pub fn new(model: Mat4, rgba: [f32; 4]) -> Self {
+        Self {
+            model: model.to_cols_array_2d(),
+            rgba,
+        }
+    }
+}
+
+/// Nodes sharing a mesh and level of detail, drawn with one call
+#[derive(Clone, Debug)]
+pub struct Batch {
+    pub mesh: MeshId,
+    /// see `GpuMesh::level`
+    pub lod: usize,
+    pub instances: Vec<Instance>,
+}
+
+/// Groups the visible nodes of `scene` by mesh, the color goes with each instance.
+/// Opaque nodes come first, one batch per mesh. See-through ones follow in scene order
+/// so they blend the same as before, only nodes next to each other with the same mesh share a batch.
+/// Nodes outside the viewport are left out, returns how many along with the batches.
+/// Each node gets the level of detail that fits its size on screen.
+pub fn batches(scene: &Scene, meshes: &GpuMeshes, viewport: &Viewport, lods: &mut LodSelection) -> (Vec<Batch>, usize) {
+    let frustum = viewport.frustum();
+    lods.next_frame();
+    let mut opaque: Vec<Batch> = Vec::new();
+    let mut by_mesh: HashMap<(&MeshId, usize), usize> = HashMap::new();
+    let mut see_through: Vec<Batch> = Vec::new();
+    let mut culled = 0;
+
+    for node in scene.nodes.iter().filter(|node| node.visible) {
+        let (mesh, levels, bounds, sphere) = match node.mesh.as_ref().and_then(|id| Some((id, meshes.get(id)?))) {
+            Some((id, gpu_mesh)) => match (gpu_mesh.bounds, gpu_mesh.sphere) {
+                (Some(bounds), Some(sphere)) => (id, gpu_mesh.levels(), bounds, sphere),
+                _ => continue,
+            },
+            None => continue,
+        };
+        let model = scene.world_transform(node).matrix();
+        // the sphere is quicker to check, the box is tighter
+        let sphere = sphere.transformed(model);
+        if !frustum.intersects_sphere(&sphere) || !frustum.intersects_aabb(&bounds.transformed(model)) {
+            culled += 1;
+            continue;
+        }
+        let lod = lods.select(node.id, screen_size(viewport, &sphere), levels);
+        let instance = Instance::new(model, node.rgba());
+        if node.color[3] == 255 {
+            let index = *by_mesh.entry((mesh, lod)).or_insert_with(|| {
+                opaque.push(Batch {
+                    mesh: mesh.clone(),
+                    lod,
+                    instances: Vec::new(),
+                });
+                opaque.len() - 1
+            });
+            opaque[index].instances.push(instance);
+        } else {
+            match see_through.last_mut() {
+                Some(batch) if &batch.mesh == mesh && batch.lod == lod => batch.instances.push(instance),
+                _ => see_through.push(Batch {
+                    mesh: mesh.clone(),
+                    lod,
+                    instances: vec![instance],
+                }),
+            }
+        }
+    }
+
+    opaque.extend(see_through);
+    (opaque, culled)
+}
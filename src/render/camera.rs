@@ -0,0 +1,105 @@
+use crate::scene::{Node, Scene};
+use glam::f32::{Mat4, Vec3};
+
+/// Where the editor looks from unless a character's camera takes over
+const DEFAULT_EYE: [f32; 3] = [2.0, -1.0, 1.0];
+const DEFAULT_DIRECTION: [f32; 3] = [-2.0, 1.0, 1.0];
+
+/// Projection for a picture `width` by `height` pixels
+pub fn perspective(width: u32, height: u32) -> Mat4 {
+    let aspect_ratio = height as f32 / width as f32;
+
+    let fov: f32 = std::f32::consts::PI / 3.0;
+    let zfar = 1024.0;
+    let znear = 0.1;
+
+    let f = 1.0 / (fov / 2.0).tan();
+
+    Mat4::from_cols_array_2d(&[
+        [f *   aspect_ratio   ,    0.0,              0.0              ,   0.0],
+        [         0.0         ,     f ,              0.0              ,   0.0],
+        [         0.0         ,    0.0,  (zfar+znear)/(zfar-znear)    ,   1.0],
+        [         0.0         ,    0.0, -(2.0*zfar*znear)/(zfar-znear),   0.0],
+    ])
+}
+
+/// The camera of the first character that has one, otherwise the editor's default view
+pub fn scene_view(scene: &Scene) -> Mat4 {
+    let follow = scene.nodes.iter().find_map(|node| {
+        let controller = node.controller.as_ref()?;
+        let transform = scene.world_transform(node);
+        controller.camera(transform.location, transform.rotation)
+    });
+    let view = match follow {
+        Some((eye, direction)) => view_matrix(&eye.to_array(), &direction.to_array(), &[0.0, 1.0, 0.0]),
+        None => view_matrix(&DEFAULT_EYE, &DEFAULT_DIRECTION, &[0.0, 1.0, 0.0]),
+    };
+    Mat4::from_cols_array_2d(&view)
+}
+
+/// Looking out of `node` along its forward direction, +Z like the characters, with its Y axis up.
+/// Unlike a fixed up direction that also works for nodes looking straight up or down.
+pub fn node_view(scene: &Scene, node: &Node) -> Mat4 {
+    let transform = scene.world_transform(node);
+    let direction = transform.rotation * Vec3::Z;
+    let up = transform.rotation * Vec3::Y;
+    Mat4::from_cols_array_2d(&view_matrix(&transform.location.to_array(), &direction.to_array(), &up.to_array()))
+}
+
+pub fn view_matrix(position: &[f32; 3], direction: &[f32; 3], up: &[f32; 3]) -> [[f32; 4]; 4] {
+    let f = {
+        let f = direction;
+        let len = f[0] * f[0] + f[1] * f[1] + f[2] * f[2];
+        let len = len.sqrt();
+        [f[0] / len, f[1] / len, f[2] / len]
+    };
+
+    let s = [up[1] * f[2] - up[2] * f[1],
+             up[2] * f[0] - up[0] * f[2],
+             up[0] * f[1] - up[1] * f[0]];
+
+    let s_norm = {
+        let len = s[0] * s[0] + s[1] * s[1] + s[2] * s[2];
+        let len = len.sqrt();
+        [s[0] / len, s[1] / len, s[2] / len]
+    };
+
+    let u = [f[1] * s_norm[2] - f[2] * s_norm[1],
+             f[2] * s_norm[0] - f[0] * s_norm[2],
+             f[0] * s_norm[1] - f[1] * s_norm[0]];
+
+    let p = [-position[0] * s_norm[0] - position[1] * s_norm[1] - position[2] * s_norm[2],
+             -position[0] * u[0] - position[1] * u[1] - position[2] * u[2],
+             -position[0] * f[0] - position[1] * f[1] - position[2] * f[2]];
+
+    [
+        [s_norm[0], u[0], f[0], 0.0],
+        [s_norm[1], u[1], f[1], 0.0],
+        [s_norm[2], u[2], f[2], 0.0],
+        [p[0], p[1], p[2], 1.0],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::f32::Quat;
+
+    #[test]
+    fn cameras_can_look_straight_down() {
+        let mut scene = Scene {
+            nodes: Vec::new(),
+            ..Scene::default()
+        };
+        let mut camera = Node::new("camera");
+        camera.transform.location = Vec3::new(0.0, 10.0, 0.0);
+        camera.transform.rotation = Quat::from_rotation_x(std::f32::consts::FRAC_PI_2);
+        let id = scene.add(camera);
+
+        let view = node_view(&scene, scene.node(id).unwrap());
+        assert!(view.is_finite(), "{}", view);
+        // the ground below is straight ahead
+        let ahead = view.transform_point3(Vec3::ZERO);
+        assert!(ahead.abs_diff_eq(Vec3::new(0.0, 0.0, 10.0), 1e-4), "{}", ahead);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::error::{Error, RenderContext};
+use glium::framebuffer::SimpleFrameBuffer;
+use glium::texture::{MipmapsOption, RawImage2d, SrgbFormat, SrgbTexture2d, Texture2d, UncompressedFloatFormat};
+use glium::uniforms::MagnifySamplerFilter;
+use glium::{BlitTarget, Surface};
+use image::RgbaImage;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Where the screenshot hotkey saves to
+pub const SCREENSHOT_DIR: &str = "screenshots";
+
+/// Every frame sequence gets a directory of its own in here
+pub const CAPTURE_DIR: &str = "captures";
+
+/// OpenGL reads pixels from the bottom row up, images go from the top down
+pub fn image_from_gl(pixels: RawImage2d<u8>) -> RgbaImage {
+    let mut image = RgbaImage::from_raw(pixels.width, pixels.height, pixels.data.into_owned())
+        .expect("OpenGL returned fewer pixels than the size says");
+    // blending leaves whatever in the alpha channel, the window ignores it and so should the picture
+    for pixel in image.pixels_mut() {
+        pixel[3] = 255;
+    }
+    image::imageops::flip_vertical(&image)
+}
+
+pub fn save_png<P: AsRef<Path>>(image: &RgbaImage, path: P) -> Result<(), Error> {
+    let path = path.as_ref();
+    image
+        .save_with_format(path, image::ImageFormat::Png)
+        .map_err(|err| Error::file(path, err))
+}
+
+/// The frames being written to numbered images
+struct FrameSequence {
+    dir: PathBuf,
+    next: u32,
+    frame_time: Duration,
+}
+
+/// Screenshots and frame sequences, read back from the window after each frame is shown
+#[derive(Default)]
+pub struct Capture {
+    /// a screenshot is taken of the next frame, `Some(false)` leaves egui out of it
+    screenshot: Option<bool>,
+    frames: Option<FrameSequence>,
+}
+
+impl Capture {
+    pub fn screenshot(&mut self, overlay: bool) {
+        self.screenshot = Some(overlay);
+    }
+
+    /// Whether egui should be left out of the next frame
+    pub fn hides_overlay(&self) -> bool {
+        self.screenshot == Some(false)
+    }
+
+    /// Starts writing every frame to a new directory, returns the directory
+    pub fn start_frames(&mut self, fps: f32) -> Result<PathBuf, Error> {
+        let dir = unused_path(Path::new(CAPTURE_DIR), &format!("capture_{}", timestamp()), "");
+        fs::create_dir_all(&dir).map_err(|err| Error::file(&dir, err))?;
+        self.frames = Some(FrameSequence {
+            dir: dir.clone(),
+            next: 0,
+            frame_time: Duration::from_secs_f32(1.0 / fps.max(1.0)),
+        });
+        Ok(dir)
+    }
+
+    /// Returns how many frames were written
+    pub fn stop_frames(&mut self) -> u32 {
+        self.frames.take().map_or(0, |frames| frames.next)
+    }
+
+    pub fn is_capturing_frames(&self) -> bool {
+        self.frames.is_some()
+    }
+
+    /// While frames are written the simulation advances by a fixed time per frame, however long writing takes
+    pub fn frame_time(&self) -> Option<Duration> {
+        self.frames.as_ref().map(|frames| frames.frame_time)
+    }
+
+    /// Saves `frame` if it's wanted, call once everything is drawn into it but before finishing it.
+    /// Returns where a screenshot went.
+    pub fn frame_drawn(&mut self, display: &glium::Display, frame: &glium::Frame) -> Result<Option<PathBuf>, Error> {
+        if self.screenshot.is_none() && self.frames.is_none() {
+            return Ok(None);
+        }
+        let image = image_from_gl(read_frame(display, frame)?);
+
+        if let Some(frames) = &mut self.frames {
+            let path = frames.dir.join(format!("frame_{:05}.png", frames.next));
+            frames.next += 1;
+            if let Err(err) = save_png(&image, path) {
+                // the rest would fail the same way
+                self.frames = None;
+                return Err(err);
+            }
+        }
+
+        match self.screenshot.take() {
+            Some(_) => {
+                let dir = Path::new(SCREENSHOT_DIR);
+                fs::create_dir_all(dir).map_err(|err| Error::file(dir, err))?;
+                let path = unused_path(dir, &format!("screenshot_{}", timestamp()), ".png");
+                save_png(&image, &path)?;
+                Ok(Some(path))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// The back buffer `frame` is drawn into. The front buffer can't be read instead,
+/// after swapping what's in it is up to the driver.
+fn read_frame(display: &glium::Display, frame: &glium::Frame) -> Result<RawImage2d<'static, u8>, Error> {
+    let what = "reading the frame back";
+    let (width, height) = frame.get_dimensions();
+    let target = BlitTarget {
+        left: 0,
+        bottom: 0,
+        width: width as i32,
+        height: height as i32,
+    };
+    // copied into the same encoding as the window, so the colors come out unchanged either way
+    let srgb = display.gl_window().get_pixel_format().srgb;
+    let pixels = if srgb {
+        let format = SrgbFormat::U8U8U8U8;
+        let texture = SrgbTexture2d::empty_with_format(display, format, MipmapsOption::NoMipmap, width, height)
+            .rendering(what)?;
+        let surface = SimpleFrameBuffer::new(display, &texture).rendering(what)?;
+        frame.blit_whole_color_to(&surface, &target, MagnifySamplerFilter::Nearest);
+        texture.read()
+    } else {
+        let format = UncompressedFloatFormat::U8U8U8U8;
+        let texture =
+            Texture2d::empty_with_format(display, format, MipmapsOption::NoMipmap, width, height).rendering(what)?;
+        frame.blit_whole_color_to(&texture.as_surface(), &target, MagnifySamplerFilter::Nearest);
+        texture.read()
+    };
+    Ok(pixels)
+}
+
+/// `dir/name` + `extension`, numbered if two land in the same second
+fn unused_path(dir: &Path, name: &str, extension: &str) -> PathBuf {
+    let mut path = dir.join(format!("{}{}", name, extension));
+    let mut number = 2;
+    while path.exists() {
+        path = dir.join(format!("{}_{}{}", name, number, extension));
+        number += 1;
+    }
+    path
+}
+
+/// The current time in UTC like `2021-06-30_17-04-59`, sorts the same as by date
+fn timestamp() -> String {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let (days, time) = ((seconds / 86400) as i64, seconds % 86400);
+
+    // days since 1970 to a date, from Howard Hinnant's `civil_from_days`
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}_{:02}-{:02}-{:02}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    )
+}
@@ -0,0 +1,135 @@
+use crate::error::Error;
+use glium::backend::{Context, Facade};
+use std::rc::Rc;
+
+/// An OpenGL context without a window, for rendering from the command line or in CI.
+/// On Linux it goes through Mesa's surfaceless EGL, so it works without a display server and with software rendering.
+pub struct Headless {
+    context: Rc<Context>,
+    /// glutin can't make a context without an event loop elsewhere, it has to outlive the context
+    #[cfg(not(target_os = "linux"))]
+    _renderer: (glium::HeadlessRenderer, glium::glutin::event_loop::EventLoop<()>),
+}
+
+impl Facade for Headless {
+    fn get_context(&self) -> &Rc<Context> {
+        &self.context
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Headless {
+    pub fn new() -> Result<Self, Error> {
+        let backend = egl::EglBackend::new()?;
+        let context = unsafe { Context::new(backend, true, Default::default()) }
+            .map_err(|err| Error::Headless(err.to_string()))?;
+        Ok(Self { context })
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+impl Headless {
+    pub fn new() -> Result<Self, Error> {
+        use glium::glutin;
+
+        let event_loop = glutin::event_loop::EventLoop::new();
+        let context = glutin::ContextBuilder::new()
+            .build_headless(&event_loop, glutin::dpi::PhysicalSize::new(1, 1))
+            .map_err(|err| Error::Headless(err.to_string()))?;
+        let renderer = glium::HeadlessRenderer::new(context).map_err(|err| Error::Headless(err.to_string()))?;
+        Ok(Self {
+            context: renderer.get_context().clone(),
+            _renderer: (renderer, event_loop),
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod egl {
+    use crate::error::Error;
+    use glium::backend::Backend;
+    use glium::SwapBuffersError;
+    use khronos_egl as egl;
+    use std::os::raw::c_void;
+    use std::ptr;
+
+    /// EGL_PLATFORM_SURFACELESS_MESA, a display that isn't tied to any screen
+    const PLATFORM_SURFACELESS: egl::Enum = 0x31DD;
+
+    pub struct EglBackend {
+        egl: egl::DynamicInstance<egl::EGL1_5>,
+        display: egl::Display,
+        context: egl::Context,
+    }
+
+    impl EglBackend {
+        pub fn new() -> Result<Self, Error> {
+            let error = |err: egl::Error| Error::Headless(err.to_string());
+            let egl = unsafe { egl::DynamicInstance::<egl::EGL1_5>::load_required() }
+                .map_err(|err| Error::Headless(format!("EGL 1.5 is not available: {}", err)))?;
+            let display = egl
+                .get_platform_display(PLATFORM_SURFACELESS, ptr::null_mut(), &[egl::ATTRIB_NONE])
+                .map_err(error)?;
+            egl.initialize(display).map_err(error)?;
+            egl.bind_api(egl::OPENGL_API).map_err(error)?;
+
+            // EGL_KHR_no_config_context, a config only matters for surfaces and there aren't any
+            let config = unsafe { egl::Config::from_ptr(ptr::null_mut()) };
+            let context = egl
+                .create_context(
+                    display,
+                    config,
+                    None,
+                    &[
+                        egl::CONTEXT_MAJOR_VERSION,
+                        3,
+                        egl::CONTEXT_MINOR_VERSION,
+                        3,
+                        egl::CONTEXT_OPENGL_PROFILE_MASK,
+                        egl::CONTEXT_OPENGL_CORE_PROFILE_BIT,
+                        egl::NONE,
+                    ],
+                )
+                .map_err(error)?;
+            // without surfaces everything is drawn into framebuffers of our own
+            egl.make_current(display, None, None, Some(context)).map_err(error)?;
+            Ok(Self { egl, display, context })
+        }
+    }
+
+    unsafe impl Backend for EglBackend {
+        fn swap_buffers(&self) -> Result<(), SwapBuffersError> {
+            Ok(())
+        }
+
+        unsafe fn get_proc_address(&self, symbol: &str) -> *const c_void {
+            match self.egl.get_proc_address(symbol) {
+                Some(function) => function as *const c_void,
+                None => ptr::null(),
+            }
+        }
+
+        /// There's no default framebuffer to draw into
+        fn get_framebuffer_dimensions(&self) -> (u32, u32) {
+            (1, 1)
+        }
+
+        fn is_current(&self) -> bool {
+            self.egl.get_current_context() == Some(self.context)
+        }
+
+        unsafe fn make_current(&self) {
+            if let Err(err) = self.egl.make_current(self.display, None, None, Some(self.context)) {
+                log::error!("Could not make the headless context current: {}", err);
+            }
+        }
+    }
+
+    impl Drop for EglBackend {
+        fn drop(&mut self) {
+            let _ = self.egl.make_current(self.display, None, None, None);
+            let _ = self.egl.destroy_context(self.display, self.context);
+            let _ = self.egl.terminate(self.display);
+        }
+    }
+}
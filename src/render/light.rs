@@ -0,0 +1,138 @@
+use crate::inspect::{self, inspect_enum, inspect_fields};
+use crate::scene::Scene;
+use glam::f32::{Mat4, Vec3};
+use glium::uniforms::{UniformValue, Uniforms};
+use serde::{Deserialize, Serialize};
+
+/// Lights past this many in a scene are left out, the shader has room for no more
+pub const MAX_LIGHTS: usize = 8;
+/// Shortest range a point light is given, the shader divides by it.
+/// Scene files can hold any range, not only what the inspector allows.
+const MIN_RANGE: f32 = 0.001;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LightKind {
+    /// like the sun, shines along the node's Z axis from infinitely far away
+    Directional,
+    /// shines from the node's location in all directions, fading out towards `Light::range`
+    Point,
+}
+
+inspect_enum!(LightKind { Directional, Point });
+
+/// Makes a node light up the meshes around it.
+/// Scenes without any lights get a single light shining from the camera.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Light {
+    pub kind: LightKind,
+    /// same layout as the node's color, the alpha is unused
+    pub color: [u8; 4],
+    pub intensity: f32,
+    /// point lights don't reach further than this
+    pub range: f32,
+}
+
+impl Default for Light {
+    fn default() -> Self {
+        Self {
+            kind: LightKind::Directional,
+            color: [255, 255, 255, 255],
+            intensity: 1.0,
+            range: 10.0,
+        }
+    }
+}
+
+inspect_fields!(Light {
+    kind,
+    color: inspect::color,
+    intensity: inspect::non_negative,
+    range: inspect::positive,
+});
+
+/// The lights of a scene as the mesh shader takes them, in view space
+#[derive(Clone, Debug, Default)]
+pub struct Lights {
+    /// direction towards a directional light with w = 0, location of a point light with w = 1
+    vectors: Vec<[f32; 4]>,
+    /// color times intensity, with the range in w
+    colors: Vec<[f32; 4]>,
+}
+
+impl Lights {
+    /// The first `MAX_LIGHTS` visible lights of `scene` as seen through `view`
+    pub fn of_scene(scene: &Scene, view: Mat4) -> Self {
+        let mut lights = Self::default();
+        for node in scene.nodes.iter().filter(|node| node.visible) {
+            let light = match &node.light {
+                Some(light) => light,
+                None => continue,
+            };
+            if lights.vectors.len() == MAX_LIGHTS {
+                break;
+            }
+            let transform = scene.world_transform(node);
+            lights.vectors.push(match light.kind {
+                LightKind::Directional => {
+                    let towards = view.transform_vector3(-(transform.rotation * Vec3::Z)).normalize_or_zero();
+                    towards.extend(0.0).to_array()
+                }
+                LightKind::Point => view.transform_point3(transform.location).extend(1.0).to_array(),
+            });
+            let intensity = light.intensity.max(0.0);
+            let [r, g, b, _] = light.color.map(|channel| channel as f32 / 255.0 * intensity);
+            lights.colors.push([r, g, b, light.range.max(MIN_RANGE)]);
+        }
+        lights
+    }
+
+    /// `uniforms` with the lights added to them
+    pub fn uniforms<U: Uniforms>(&self, uniforms: U) -> LightUniforms<'_, U> {
+        LightUniforms { uniforms, lights: self }
+    }
+}
+
+pub struct LightUniforms<'a, U> {
+    uniforms: U,
+    lights: &'a Lights,
+}
+
+impl<U: Uniforms> Uniforms for LightUniforms<'_, U> {
+    fn visit_values<'a, F: FnMut(&str, UniformValue<'a>)>(&'a self, mut output: F) {
+        self.uniforms.visit_values(&mut output);
+        output("u_light_count", UniformValue::SignedInt(self.lights.vectors.len() as i32));
+        for (index, (vector, color)) in self.lights.vectors.iter().zip(&self.lights.colors).enumerate() {
+            output(&format!("u_light_vectors[{}]", index), UniformValue::Vec4(*vector));
+            output(&format!("u_light_colors[{}]", index), UniformValue::Vec4(*color));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::Node;
+
+    #[test]
+    fn ranges_from_files_are_kept_positive() {
+        let mut scene = Scene {
+            nodes: Vec::new(),
+            ..Scene::default()
+        };
+        for range in [0.0, -1.0, f32::NAN] {
+            let mut lamp = Node::new("lamp");
+            lamp.light = Some(Light {
+                kind: LightKind::Point,
+                intensity: -2.0,
+                range,
+                ..Light::default()
+            });
+            scene.add(lamp);
+        }
+        let lights = Lights::of_scene(&scene, Mat4::IDENTITY);
+        assert_eq!(lights.colors.len(), 3);
+        for color in &lights.colors {
+            assert_eq!(*color, [0.0, 0.0, 0.0, MIN_RANGE]);
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use crate::math::{Sphere, Viewport};
+use crate::scene::NodeId;
+use std::collections::HashMap;
+use std::mem;
+
+/// A node switches to the next simpler level of detail once its bounding sphere is smaller than this
+/// share of the viewport's height
+const LOD_SCREEN_SIZES: [f32; 3] = [0.2, 0.08, 0.03];
+
+/// How far past a threshold a node has to get before it switches back and forth, as a share of the threshold.
+/// Without it a node right at a threshold would flicker between two levels as the camera moves.
+const HYSTERESIS: f32 = 0.15;
+
+/// Share of the viewport's height `sphere` covers, 1 or more if the camera is inside it
+pub fn screen_size(viewport: &Viewport, sphere: &Sphere) -> f32 {
+    let distance = (sphere.center - viewport.eye()).length();
+    if distance <= sphere.radius {
+        return f32::INFINITY;
+    }
+    sphere.radius * viewport.perspective.y_axis.y / distance
+}
+
+/// Which level of detail each node was drawn with, so they stick to it until they're clearly past a threshold
+#[derive(Debug, Default)]
+pub struct LodSelection {
+    previous: HashMap<NodeId, usize>,
+    current: HashMap<NodeId, usize>,
+}
+
+impl LodSelection {
+    /// Call before selecting the levels of a frame. Nodes that weren't drawn in the last one start over.
+    pub fn next_frame(&mut self) {
+        self.previous = mem::take(&mut self.current);
+    }
+
+    /// The level of detail to draw `node` with out of `levels`, 0 being the mesh itself
+    pub fn select(&mut self, node: NodeId, screen_size: f32, levels: usize) -> usize {
+        let level_at = |scale: f32| {
+            LOD_SCREEN_SIZES
+                .iter()
+                .take(levels.saturating_sub(1))
+                .filter(|&&threshold| screen_size < threshold * scale)
+                .count()
+        };
+        let level = match self.previous.get(&node) {
+            // simpler once it's clearly below a threshold, more detailed once clearly above
+            Some(&previous) => previous.clamp(level_at(1.0 - HYSTERESIS), level_at(1.0 + HYSTERESIS)),
+            None => level_at(1.0),
+        };
+        self.current.insert(node, level);
+        level
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NODE: NodeId = NodeId(1);
+
+    /// Selects the level of `NODE` once per frame at each of `sizes`
+    fn levels(selection: &mut LodSelection, sizes: &[f32]) -> Vec<usize> {
+        sizes
+            .iter()
+            .map(|&size| {
+                selection.next_frame();
+                selection.select(NODE, size, 4)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn levels_get_simpler_with_distance() {
+        let mut selection = LodSelection::default();
+        assert_eq!(levels(&mut selection, &[0.5]), [0]);
+        let mut fresh = LodSelection::default();
+        assert_eq!(levels(&mut fresh, &[0.1]), [1]);
+        let mut fresh = LodSelection::default();
+        assert_eq!(levels(&mut fresh, &[0.01]), [3]);
+        // only as many as the mesh has
+        let mut fresh = LodSelection::default();
+        assert_eq!(fresh.select(NODE, 0.01, 2), 1);
+        assert_eq!(fresh.select(NODE, 0.01, 1), 0);
+    }
+
+    #[test]
+    fn no_flicker_at_a_threshold() {
+        let threshold = LOD_SCREEN_SIZES[0];
+        let mut selection = LodSelection::default();
+        let wobble = [1.05, 0.95, 1.05, 0.95, 1.1, 0.9].map(|scale| threshold * scale);
+        assert_eq!(levels(&mut selection, &wobble), [0; 6]);
+
+        let mut selection = LodSelection::default();
+        levels(&mut selection, &[threshold * 0.5]);
+        assert_eq!(levels(&mut selection, &wobble), [1; 6]);
+    }
+
+    #[test]
+    fn switches_once_past_the_band() {
+        let threshold = LOD_SCREEN_SIZES[0];
+        let mut selection = LodSelection::default();
+        let shrinking = [1.0, 0.9, 1.0 - HYSTERESIS * 1.1, 0.9].map(|scale| threshold * scale);
+        assert_eq!(levels(&mut selection, &shrinking), [0, 0, 1, 1]);
+        let growing = [1.1, 1.0 + HYSTERESIS * 1.1, 1.1].map(|scale| threshold * scale);
+        assert_eq!(levels(&mut selection, &growing), [1, 0, 0]);
+    }
+
+    #[test]
+    fn nodes_not_drawn_last_frame_start_over() {
+        let threshold = LOD_SCREEN_SIZES[0];
+        let mut selection = LodSelection::default();
+        levels(&mut selection, &[threshold * 0.5]);
+        // a frame without the node
+        selection.next_frame();
+        assert_eq!(levels(&mut selection, &[threshold * 1.05]), [0]);
+    }
+}
@@ -0,0 +1,19 @@
+mod batch;
+mod camera;
+mod capture;
+mod headless;
+mod light;
+mod lod;
+mod renderer;
+mod stats;
+mod stress;
+
+pub use batch::*;
+pub use camera::*;
+pub use capture::*;
+pub use headless::*;
+pub use light::*;
+pub use lod::*;
+pub use renderer::*;
+pub use stats::*;
+pub use stress::*;
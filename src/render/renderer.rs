@@ -0,0 +1,213 @@
+use crate::color::BACKGROUND_COLOR;
+use crate::error::{Error, RenderContext};
+use crate::math::Viewport;
+use crate::mesh::GpuMeshes;
+use crate::render::{batches, image_from_gl, Lights, LodSelection, RenderStats, MAX_LIGHTS};
+use crate::scene::Scene;
+use glam::f32::{Mat4, Vec2};
+use glium::backend::Facade;
+use glium::framebuffer::{DepthRenderBuffer, SimpleFrameBuffer};
+use glium::texture::{DepthFormat, RawImage2d, SrgbTexture2d};
+use glium::{uniform, Surface};
+use image::RgbaImage;
+
+const VERTEX_SHADER: &str = r#"
+    #version 330
+
+    in vec3 position;
+    in vec3 normal;
+    // per instance
+    in mat4 model;
+    in vec4 rgba;
+
+    out vec3 v_normal;
+    out vec3 v_position;
+    out vec4 v_rgba;
+
+    uniform mat4 perspective;
+
+    uniform mat4 view;
+
+    void main() {
+        mat4 modelview = view * model;
+        v_normal = transpose(inverse(mat3(modelview))) * normal;
+        v_rgba = rgba;
+        vec4 view_position = modelview * vec4(position, 1.0);
+        v_position = view_position.xyz;
+        gl_Position = perspective * view_position;
+    }
+"#;
+
+const FRAGMENT_SHADER: &str = r#"
+    #version 330
+
+    #ifdef GL_ES
+    precision mediump float;
+    #endif
+
+    in vec3 v_normal;
+    in vec3 v_position;
+    in vec4 v_rgba;
+    out vec4 color;
+    uniform vec3 u_light;
+
+    // the scene's own lights, see `Lights`
+    uniform int u_light_count;
+    uniform vec4 u_light_vectors[MAX_LIGHTS];
+    uniform vec4 u_light_colors[MAX_LIGHTS];
+
+    const float AMBIENT = 0.15;
+
+    void main() {
+        vec3 normal = normalize(v_normal);
+        if (u_light_count == 0) {
+            float brightness = dot(normal, normalize(u_light));
+            vec3 dark_color = vec3(0.6 * v_rgba);
+            vec3 regular_color = vec3(v_rgba);
+            color = vec4(mix(dark_color, regular_color, brightness), v_rgba[3]);
+            return;
+        }
+
+        vec3 light = vec3(AMBIENT);
+        for (int i = 0; i < u_light_count; i++) {
+            vec3 towards = u_light_vectors[i].xyz;
+            float strength = 1.0;
+            if (u_light_vectors[i].w != 0.0) {
+                towards -= v_position;
+                float fade = clamp(1.0 - length(towards) / u_light_colors[i].w, 0.0, 1.0);
+                strength = fade * fade;
+            }
+            light += u_light_colors[i].rgb * max(dot(normal, normalize(towards)), 0.0) * strength;
+        }
+        color = vec4(v_rgba.rgb * light, v_rgba.a);
+    }
+"#;
+
+/// Direction the light comes from in scenes without lights, in view space
+const LIGHT: [f32; 3] = [-1.0, 0.4, 0.9];
+
+/// Draws the nodes of a scene with their meshes and colors, in the editor and without a window alike.
+/// Nodes sharing a mesh are drawn together with instancing, see `batches`.
+pub struct SceneRenderer {
+    program: glium::Program,
+    lods: LodSelection,
+}
+
+impl SceneRenderer {
+    pub fn new<F: Facade>(facade: &F) -> Result<Self, Error> {
+        let fragment_shader =
+            FRAGMENT_SHADER.replacen("#version 330", &format!("#version 330\n#define MAX_LIGHTS {}", MAX_LIGHTS), 1);
+        let program =
+            glium::Program::from_source(facade, VERTEX_SHADER, &fragment_shader, None).map_err(|err| Error::Shader {
+                name: "mesh",
+                error: err.to_string(),
+            })?;
+        Ok(Self {
+            program,
+            lods: LodSelection::default(),
+        })
+    }
+
+    /// The shader the meshes are drawn with, the thumbnails use it as well.
+    /// It takes the transform and color of each copy from a per-instance buffer of `Instance`s,
+    /// and the lights through `Lights::uniforms`.
+    pub fn program(&self) -> &glium::Program {
+        &self.program
+    }
+
+    /// Clears `target` to the background and draws the visible nodes of `scene` as seen from `viewport`,
+    /// skipping the ones that are out of view and drawing the small ones with their simpler levels of detail
+    pub fn draw<F: Facade, S: Surface>(
+        &mut self,
+        facade: &F,
+        target: &mut S,
+        scene: &Scene,
+        meshes: &GpuMeshes,
+        viewport: &Viewport,
+        wireframe: bool,
+    ) -> Result<RenderStats, Error> {
+        target.clear_color_srgb_and_depth(
+            (
+                BACKGROUND_COLOR.get_glfloat_red(),
+                BACKGROUND_COLOR.get_glfloat_green(),
+                BACKGROUND_COLOR.get_glfloat_blue(),
+                1.0,
+            ),
+            1.0,
+        );
+
+        let params = glium::DrawParameters {
+            depth: glium::Depth {
+                test: glium::draw_parameters::DepthTest::IfLess,
+                write: true,
+                ..Default::default()
+            },
+            blend: glium::draw_parameters::Blend::alpha_blending(),
+            backface_culling: glium::draw_parameters::BackfaceCullingMode::CullClockwise,
+            polygon_mode: if wireframe {
+                glium::draw_parameters::PolygonMode::Line
+            } else {
+                glium::draw_parameters::PolygonMode::Fill
+            },
+            ..Default::default()
+        };
+        let view = viewport.view.to_cols_array_2d();
+        let perspective = viewport.perspective.to_cols_array_2d();
+        let lights = Lights::of_scene(scene, viewport.view);
+
+        let (batches, culled) = batches(scene, meshes, viewport, &mut self.lods);
+        let mut stats = RenderStats {
+            culled,
+            ..Default::default()
+        };
+        for batch in batches {
+            let mesh = match meshes.get(&batch.mesh) {
+                Some(mesh) => mesh.level(batch.lod),
+                None => continue,
+            };
+            let instances = glium::VertexBuffer::dynamic(facade, &batch.instances).rendering("drawing the scene")?;
+            let per_instance = instances
+                .per_instance()
+                .map_err(|_| "the graphics card can't draw instances")
+                .rendering("drawing the scene")?;
+            target
+                .draw(
+                    (&mesh.positions, &mesh.normals, per_instance),
+                    &mesh.indices,
+                    &self.program,
+                    &lights.uniforms(uniform! { view: view, perspective: perspective, u_light: LIGHT }),
+                    &params,
+                )
+                .rendering("drawing the scene")?;
+            stats.draw_calls += 1;
+            stats.drawn += batch.instances.len();
+            stats.triangles += batch.instances.len() * mesh.indices.len() / 3;
+        }
+        Ok(stats)
+    }
+
+    /// Draws into a texture instead of a window and reads the picture back
+    pub fn draw_offscreen<F: Facade>(
+        &mut self,
+        facade: &F,
+        scene: &Scene,
+        meshes: &GpuMeshes,
+        view: Mat4,
+        width: u32,
+        height: u32,
+    ) -> Result<(RgbaImage, RenderStats), Error> {
+        let what = "rendering offscreen";
+        let texture = SrgbTexture2d::empty(facade, width, height).rendering(what)?;
+        let depth = DepthRenderBuffer::new(facade, DepthFormat::I24, width, height).rendering(what)?;
+        let mut target = SimpleFrameBuffer::with_depth_buffer(facade, &texture, &depth).rendering(what)?;
+
+        let viewport = Viewport::new(
+            crate::render::perspective(width, height),
+            view,
+            Vec2::new(width as f32, height as f32),
+        );
+        let stats = self.draw(facade, &mut target, scene, meshes, &viewport, false)?;
+        let pixels: RawImage2d<u8> = texture.read();
+        Ok((image_from_gl(pixels), stats))
+    }
+}
@@ -0,0 +1,33 @@
+use egui::{Align2, CtxRef, Order};
+use std::time::Duration;
+
+/// What the last `SceneRenderer::draw` did
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RenderStats {
+    pub draw_calls: usize,
+    pub drawn: usize,
+    /// of all the nodes drawn, at the level of detail they were drawn with
+    pub triangles: usize,
+    /// nodes that weren't drawn for being out of view
+    pub culled: usize,
+}
+
+impl RenderStats {
+    /// Overlay in the top left corner of the viewport, next to the panels
+    pub fn show(&self, ctx: &CtxRef, frame_time: Duration) {
+        let corner = ctx.available_rect().left_top() + egui::vec2(8.0, 8.0);
+        egui::Area::new("render_stats")
+            .order(Order::Foreground)
+            .anchor(Align2::LEFT_TOP, corner.to_vec2())
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.monospace(format!("frame  {:6.2} ms", frame_time.as_secs_f32() * 1000.0));
+                    ui.monospace(format!("calls  {:6}", self.draw_calls));
+                    ui.monospace(format!("drawn  {:6}", self.drawn));
+                    ui.monospace(format!("tris   {:6}", self.triangles));
+                    ui.monospace(format!("culled {:6}", self.culled));
+                });
+            });
+    }
+}
@@ -0,0 +1,43 @@
+use crate::console::Registry;
+use crate::mesh::MeshId;
+use crate::scene::{Node, NodeId};
+use egui::color::Hsva;
+use egui::Color32;
+use glam::f32::Vec3;
+
+/// Teapots the `stress` command adds unless told otherwise
+const STRESS_COUNT: usize = 10000;
+
+/// Distance between the teapots of the `stress` grid, they're about 1 across
+const STRESS_SPACING: f32 = 1.5;
+
+/// Console commands to try the renderer with
+pub fn register_commands(registry: &mut Registry) {
+    registry.command("stress", "stress [count]: adds a grid of teapots in all colors to try the renderer, 10000 by default", |context, args| {
+        let count: usize = match args {
+            [] => STRESS_COUNT,
+            [count] => count.parse().map_err(|_| format!("expected a number of teapots, got {}", count))?,
+            _ => return Err("usage: stress [count]".to_string()),
+        };
+        let side = (count as f32).sqrt().ceil() as usize;
+        let nodes = (0..count)
+            .map(|index| {
+                let (row, column) = (index / side, index % side);
+                let mut node = Node::new(&format!("Teapot {}", index + 1));
+                // only to tell them apart, they get fresh ids when they're added
+                node.id = NodeId(index as u64 + 1);
+                node.mesh = Some(MeshId::Teapot);
+                node.transform.size = Vec3::splat(0.01);
+                node.transform.location = Vec3::new(
+                    (column as f32 - side as f32 / 2.0) * STRESS_SPACING,
+                    0.0,
+                    2.0 + row as f32 * STRESS_SPACING,
+                );
+                node.color = Color32::from(Hsva::new(index as f32 / count as f32, 0.7, 0.9, 1.0)).to_array();
+                node
+            })
+            .collect();
+        context.history.insert_nodes(context.scene, "Stress test", nodes);
+        Ok(format!("added {} teapots", count))
+    });
+}
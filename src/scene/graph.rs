@@ -0,0 +1,93 @@
+use crate::mesh::MeshId;
+use crate::physics;
+use crate::scene::{Node, NodeId, Transform};
+use glam::f32::{Quat, Vec3};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Scene {
+    pub nodes: Vec<Node>,
+    #[serde(default = "default_gravity")]
+    pub gravity: Vec3,
+}
+
+fn default_gravity() -> Vec3 {
+    physics::DEFAULT_GRAVITY
+}
+
+impl Default for Scene {
+    /// The scene the editor starts with: a single red teapot
+    fn default() -> Self {
+        let mut teapot = Node::new("Teekanne");
+        teapot.transform = Transform {
+            location: Vec3::new(0.0, 0.0, 2.0),
+            rotation: Quat::IDENTITY,
+            size: Vec3::splat(0.01),
+        };
+        teapot.color = [255, 0, 0, 255];
+        teapot.mesh = Some(MeshId::Teapot);
+
+        let mut scene = Self {
+            nodes: Vec::new(),
+            gravity: default_gravity(),
+        };
+        scene.add(teapot);
+        scene
+    }
+}
+
+impl Scene {
+    /// Id the next node that is added gets
+    pub fn next_id(&self) -> NodeId {
+        let highest = self.nodes.iter().map(|node| node.id.0).max().unwrap_or(0);
+        NodeId(highest + 1)
+    }
+
+    /// Adds `node` with a fresh id and returns that id
+    pub fn add(&mut self, mut node: Node) -> NodeId {
+        let id = self.next_id();
+        node.id = id;
+        self.nodes.push(node);
+        id
+    }
+
+    pub fn index_of(&self, id: NodeId) -> Option<usize> {
+        self.nodes.iter().position(|node| node.id == id)
+    }
+
+    /// Gives nodes from scenes saved before there were ids one of their own
+    pub fn assign_missing_ids(&mut self) {
+        let mut highest = self.nodes.iter().map(|node| node.id.0).max().unwrap_or(0);
+        for node in &mut self.nodes {
+            if node.id == NodeId::default() {
+                highest += 1;
+                node.id = NodeId(highest);
+            }
+        }
+    }
+
+    pub fn node(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> Option<&mut Node> {
+        self.nodes.iter_mut().find(|node| node.id == id)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let text = fs::read_to_string(path)?;
+        let mut scene: Self = ron::from_str(&text)?;
+        scene.assign_missing_ids();
+        physics::warn_unsupported(&scene);
+        Ok(scene)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::new())?;
+        fs::write(path, text)?;
+        Ok(())
+    }
+}
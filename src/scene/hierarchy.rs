@@ -0,0 +1,81 @@
+use crate::scene::{Node, NodeId, Scene, Transform};
+use std::collections::{HashMap, HashSet};
+
+impl Scene {
+    /// Where `node` is in the world, after applying the transforms of all its parents
+    pub fn world_transform(&self, node: &Node) -> Transform {
+        let mut transform = node.transform;
+        let mut parent = node.parent;
+        // a broken file could have parents pointing in a circle
+        for _ in 0..self.nodes.len() {
+            let node = match parent.and_then(|id| self.node(id)) {
+                Some(node) => node,
+                None => break,
+            };
+            transform = node.transform.child_to_world(&transform);
+            parent = node.parent;
+        }
+        transform
+    }
+
+    /// World transform of the parent of `node`, what its own transform is relative to
+    pub fn parent_transform(&self, node: &Node) -> Transform {
+        match node.parent.and_then(|id| self.node(id)) {
+            Some(parent) => self.world_transform(parent),
+            None => Transform::default(),
+        }
+    }
+
+    /// Nodes directly below each node in scene order, the top level nodes are under `None`.
+    /// Nodes whose parent is gone count as top level.
+    pub fn children_by_parent(&self) -> HashMap<Option<NodeId>, Vec<NodeId>> {
+        let ids: HashSet<NodeId> = self.nodes.iter().map(|node| node.id).collect();
+        let mut children: HashMap<Option<NodeId>, Vec<NodeId>> = HashMap::new();
+        for node in &self.nodes {
+            let parent = node.parent.filter(|parent| ids.contains(parent));
+            children.entry(parent).or_default().push(node.id);
+        }
+        children
+    }
+
+    /// Whether `ancestor` is above `id` in the hierarchy
+    pub fn is_descendant(&self, id: NodeId, ancestor: NodeId) -> bool {
+        let mut parent = self.node(id).and_then(|node| node.parent);
+        for _ in 0..self.nodes.len() {
+            match parent {
+                Some(parent_id) if parent_id == ancestor => return true,
+                Some(parent_id) => parent = self.node(parent_id).and_then(|node| node.parent),
+                None => return false,
+            }
+        }
+        false
+    }
+
+    /// Whether `id` can be moved below `parent` without making a loop
+    pub fn can_reparent(&self, id: NodeId, parent: Option<NodeId>) -> bool {
+        match parent {
+            Some(parent) => parent != id && !self.is_descendant(parent, id),
+            None => true,
+        }
+    }
+
+    /// `id` and everything below it, in scene order
+    pub fn subtree(&self, id: NodeId) -> Vec<NodeId> {
+        let below = self.subtrees(&[id]);
+        self.nodes.iter().map(|node| node.id).filter(|id| below.contains(id)).collect()
+    }
+
+    /// `ids` and everything below them, going through the hierarchy once however many there are
+    pub fn subtrees(&self, ids: &[NodeId]) -> HashSet<NodeId> {
+        let children = self.children_by_parent();
+        let mut found = HashSet::new();
+        let mut pending = ids.to_vec();
+        while let Some(id) = pending.pop() {
+            // the set also stops parents that point in a circle
+            if found.insert(id) {
+                pending.extend(children.get(&Some(id)).into_iter().flatten());
+            }
+        }
+        found
+    }
+}
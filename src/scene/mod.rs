@@ -0,0 +1,9 @@
+mod graph;
+mod hierarchy;
+mod node;
+mod picking;
+mod transform;
+
+pub use graph::*;
+pub use node::*;
+pub use transform::*;
@@ -0,0 +1,97 @@
+use crate::controller::CharacterController;
+use crate::inspect::{self, inspect_fields};
+use crate::mesh::MeshId;
+use crate::physics::{Collider, RigidBody};
+use crate::prefab::PrefabLink;
+use crate::render::Light;
+use crate::scene::Transform;
+use crate::script::Script;
+use serde::{Deserialize, Serialize};
+
+/// Identifies a node for as long as it is in its scene, unlike its index
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct NodeId(pub u64);
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Node {
+    /// assigned by `Scene::add`, 0 until then
+    #[serde(default)]
+    pub id: NodeId,
+    pub name: String,
+    /// the node this one moves with, its transform is relative to the parent's
+    #[serde(default)]
+    pub parent: Option<NodeId>,
+    /// hidden nodes aren't drawn and can't be clicked, they still take part in physics
+    #[serde(default = "visible")]
+    pub visible: bool,
+    /// locked nodes can't be clicked in the viewport, so they aren't moved by accident
+    #[serde(default)]
+    pub locked: bool,
+    pub transform: Transform,
+    /// premultiplied srgba, same layout as `egui::Color32`
+    pub color: [u8; 4],
+    #[serde(default)]
+    pub mesh: Option<MeshId>,
+    #[serde(default)]
+    pub body: Option<RigidBody>,
+    #[serde(default)]
+    pub collider: Option<Collider>,
+    #[serde(default)]
+    pub controller: Option<CharacterController>,
+    #[serde(default)]
+    pub script: Option<Script>,
+    #[serde(default)]
+    pub light: Option<Light>,
+    /// the prefab this node was placed from, if any
+    #[serde(default)]
+    pub prefab: Option<PrefabLink>,
+}
+
+fn visible() -> bool {
+    true
+}
+
+inspect_fields!(Node {
+    name,
+    visible,
+    locked,
+    transform,
+    color: inspect::color,
+    mesh,
+    body,
+    collider,
+    controller,
+    script,
+    light,
+});
+
+impl Node {
+    pub fn new(name: &str) -> Self {
+        Self {
+            id: NodeId(0),
+            name: name.to_string(),
+            parent: None,
+            visible: true,
+            locked: false,
+            transform: Transform::default(),
+            color: [255, 255, 255, 255],
+            mesh: None,
+            body: None,
+            collider: None,
+            controller: None,
+            script: None,
+            light: None,
+            prefab: None,
+        }
+    }
+
+    /// Color as expected by the `rgba` shader uniform
+    pub fn rgba(&self) -> [f32; 4] {
+        [
+            self.color[0] as f32 / 255.0,
+            self.color[1] as f32 / 255.0,
+            self.color[2] as f32 / 255.0,
+            self.color[3] as f32 / 255.0,
+        ]
+    }
+}
@@ -0,0 +1,110 @@
+use crate::math::Ray;
+use crate::mesh::MeshLibrary;
+use crate::scene::{NodeId, Scene};
+
+impl Scene {
+    /// The closest node whose mesh is hit by `ray` and the distance along it.
+    /// Nodes without a mesh, hidden and locked ones can't be hit.
+    pub fn raycast(&self, ray: &Ray, meshes: &MeshLibrary) -> Option<(NodeId, f32)> {
+        let mut closest: Option<(NodeId, f32)> = None;
+        for node in &self.nodes {
+            let mesh = match &node.mesh {
+                Some(id) if node.visible && !node.locked => match meshes.get(id) {
+                    Some(mesh) => mesh,
+                    None => continue,
+                },
+                _ => continue,
+            };
+            let matrix = self.world_transform(node).matrix();
+            if matrix.determinant().abs() <= f32::EPSILON {
+                // squashed flat, nothing to click on
+                continue;
+            }
+            // testing in the mesh's own space saves transforming every triangle
+            let local = ray.transformed(matrix.inverse());
+
+            let bounds_hit = mesh.bounds().and_then(|bounds| local.intersect_aabb(&bounds));
+            match (bounds_hit, closest) {
+                (None, _) => continue,
+                (Some(entry), Some((_, best))) if entry >= best => continue,
+                _ => {}
+            }
+
+            for triangle in mesh.triangles() {
+                if let Some(distance) = local.intersect_triangle(triangle) {
+                    let closer = match closest {
+                        Some((_, best)) => distance < best,
+                        None => true,
+                    };
+                    if closer {
+                        closest = Some((node.id, distance));
+                    }
+                }
+            }
+        }
+        closest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::Ray;
+    use crate::mesh::{MeshId, MeshLibrary};
+    use crate::scene::{Node, Scene};
+    use glam::f32::{Quat, Vec3};
+
+    fn cube(scene: &mut Scene, z: f32) -> crate::scene::NodeId {
+        let mut node = Node::new("cube");
+        node.mesh = Some(MeshId::Cube);
+        node.transform.location = Vec3::new(0.0, 0.0, z);
+        scene.add(node)
+    }
+
+    #[test]
+    fn closest_mesh_is_hit() {
+        let meshes = MeshLibrary::new();
+        let half = meshes.get(&MeshId::Cube).unwrap().bounds().unwrap().max.z;
+        let mut scene = Scene {
+            nodes: Vec::new(),
+            ..Scene::default()
+        };
+        let far = cube(&mut scene, 10.0);
+        let near = cube(&mut scene, 5.0);
+        let ray = Ray::new(Vec3::ZERO, Vec3::Z);
+
+        let (id, distance) = scene.raycast(&ray, &meshes).unwrap();
+        assert_eq!(id, near);
+        assert!((distance - (5.0 - half)).abs() < 1e-4, "{}", distance);
+
+        // hidden and locked nodes are looked through
+        scene.node_mut(near).unwrap().visible = false;
+        assert_eq!(scene.raycast(&ray, &meshes).map(|(id, _)| id), Some(far));
+        scene.node_mut(far).unwrap().locked = true;
+        assert_eq!(scene.raycast(&ray, &meshes), None);
+        assert_eq!(scene.raycast(&Ray::new(Vec3::ZERO, -Vec3::Z), &meshes), None);
+    }
+
+    #[test]
+    fn transformed_meshes_are_hit_where_they_are_drawn() {
+        let meshes = MeshLibrary::new();
+        let half = meshes.get(&MeshId::Cube).unwrap().bounds().unwrap().max.z;
+        let mut scene = Scene {
+            nodes: Vec::new(),
+            ..Scene::default()
+        };
+        let mut parent = Node::new("parent");
+        parent.transform.location = Vec3::new(3.0, 0.0, 0.0);
+        parent.transform.size = Vec3::splat(2.0);
+        let parent = scene.add(parent);
+        let id = cube(&mut scene, 2.0);
+        let node = scene.node_mut(id).unwrap();
+        node.parent = Some(parent);
+        node.transform.rotation = Quat::from_rotation_y(std::f32::consts::FRAC_PI_2);
+
+        // the cube is at (3, 0, 4) and twice as big
+        let (hit, distance) = scene.raycast(&Ray::new(Vec3::new(3.0, 0.0, 0.0), Vec3::Z), &meshes).unwrap();
+        assert_eq!(hit, id);
+        assert!((distance - (4.0 - 2.0 * half)).abs() < 1e-4, "{}", distance);
+        assert_eq!(scene.raycast(&Ray::new(Vec3::new(0.0, 0.0, 0.0), Vec3::Z), &meshes), None);
+    }
+}
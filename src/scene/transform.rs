@@ -0,0 +1,218 @@
+use crate::inspect::{self, inspect_fields};
+use glam::f32::{Mat3, Mat4, Quat, Vec3};
+use glam::EulerRot;
+use serde::{Deserialize, Deserializer, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Transform {
+    pub location: Vec3,
+    #[serde(deserialize_with = "rotation")]
+    pub rotation: Quat,
+    pub size: Vec3,
+}
+
+/// Rotations are read as quaternions, or as the Euler angles of older files
+fn rotation<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Quat, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Stored {
+        Quat(Quat),
+        /// radians, applied in X, Y, Z order
+        Euler(Vec3),
+    }
+
+    Ok(match Stored::deserialize(deserializer)? {
+        Stored::Quat(rotation) => rotation.normalize(),
+        Stored::Euler(angles) => Quat::from_euler(EulerRot::XYZ, angles.x, angles.y, angles.z),
+    })
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            location: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            size: Vec3::ONE,
+        }
+    }
+}
+
+/// `rotation.to_euler(order)`, also right at gimbal lock, where glam's angles no longer make up the rotation
+pub fn euler_angles(rotation: Quat, order: EulerRot) -> (f32, f32, f32) {
+    let (a, b, c) = rotation.to_euler(order);
+    if Quat::from_euler(order, a, b, c).dot(rotation).abs() > 1.0 - 1e-5 {
+        return (a, b, c);
+    }
+    let (first, second) = match order {
+        EulerRot::XYZ => (Vec3::X, Vec3::Y),
+        EulerRot::XZY => (Vec3::X, Vec3::Z),
+        EulerRot::YXZ => (Vec3::Y, Vec3::X),
+        EulerRot::YZX => (Vec3::Y, Vec3::Z),
+        EulerRot::ZXY => (Vec3::Z, Vec3::X),
+        EulerRot::ZYX => (Vec3::Z, Vec3::Y),
+        // the deprecated orders that repeat an axis
+        _ => return (a, b, c),
+    };
+    // the first and the last axis line up, so the first angle does all of their turning
+    let rest = rotation * Quat::from_axis_angle(second, b).inverse();
+    let mut a = 2.0 * Vec3::new(rest.x, rest.y, rest.z).dot(first).atan2(rest.w);
+    if a > std::f32::consts::PI {
+        a -= std::f32::consts::TAU;
+    } else if a < -std::f32::consts::PI {
+        a += std::f32::consts::TAU;
+    }
+    (a, b, 0.0)
+}
+
+inspect_fields!(Transform {
+    location,
+    rotation: inspect::euler_degrees,
+    size,
+});
+
+impl Transform {
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::from_translation(self.location) * Mat4::from_scale(self.size) * Mat4::from_quat(self.rotation)
+    }
+
+    /// Turns the node so its Z axis points at `target` and its Y axis is as close to `up` as possible.
+    /// Does nothing if `target` is straight above or below, or at the location itself.
+    pub fn look_at(&mut self, target: Vec3, up: Vec3) {
+        let forward = (target - self.location).normalize_or_zero();
+        let right = up.cross(forward).normalize_or_zero();
+        if right == Vec3::ZERO {
+            return;
+        }
+        self.rotation = Quat::from_mat3(&Mat3::from_cols(right, forward.cross(right), forward));
+    }
+
+    /// The transform `t` of the way from `self` to `other`, turning along the shortest arc
+    pub fn slerp(&self, other: &Transform, t: f32) -> Transform {
+        // q and -q are the same rotation, but only the one on the same side as `self` is the short way there
+        let rotation = if self.rotation.dot(other.rotation) < 0.0 { -other.rotation } else { other.rotation };
+        Transform {
+            location: self.location.lerp(other.location, t),
+            rotation: self.rotation.slerp(rotation, t).normalize(),
+            size: self.size.lerp(other.size, t),
+        }
+    }
+
+    /// Where a child with the `local` transform ends up in the world, with `self` being its parent.
+    /// Exact as long as the parent is scaled the same along all axes.
+    pub fn child_to_world(&self, local: &Transform) -> Transform {
+        Transform {
+            location: self.matrix().transform_point3(local.location),
+            rotation: (self.rotation * local.rotation).normalize(),
+            size: self.size * local.size,
+        }
+    }
+
+    /// The reverse of `child_to_world`
+    pub fn world_to_child(&self, world: &Transform) -> Transform {
+        Transform {
+            location: self.matrix().inverse().transform_point3(world.location),
+            rotation: (self.rotation.inverse() * world.rotation).normalize(),
+            size: world.size / self.size,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    const ORDERS: [EulerRot; 6] = [EulerRot::XYZ, EulerRot::XZY, EulerRot::YXZ, EulerRot::YZX, EulerRot::ZXY, EulerRot::ZYX];
+
+    fn assert_same_rotation(a: Quat, b: Quat) {
+        // q and -q are the same rotation
+        assert!(a.dot(b).abs() > 1.0 - 1e-5, "{} and {} differ", a, b);
+    }
+
+    #[test]
+    fn euler_angles_round_trip_in_every_order() {
+        for &order in &ORDERS {
+            for &angles in &[(0.3, -0.5, 1.2), (-2.5, 1.0, 0.1), (0.0, 0.0, -3.0)] {
+                let rotation = Quat::from_euler(order, angles.0, angles.1, angles.2);
+                let (a, b, c) = euler_angles(rotation, order);
+                assert!(
+                    (a - angles.0).abs() < 1e-4 && (b - angles.1).abs() < 1e-4 && (c - angles.2).abs() < 1e-4,
+                    "{:?} turned {:?} into {:?}",
+                    order,
+                    angles,
+                    (a, b, c)
+                );
+                assert_same_rotation(Quat::from_euler(order, a, b, c), rotation);
+            }
+        }
+    }
+
+    #[test]
+    fn euler_angles_keep_the_rotation_at_gimbal_lock() {
+        for &order in &ORDERS {
+            for &middle in &[FRAC_PI_2, -FRAC_PI_2] {
+                let rotation = Quat::from_euler(order, 0.4, middle, -0.7);
+                let (a, b, c) = euler_angles(rotation, order);
+                assert_same_rotation(Quat::from_euler(order, a, b, c), rotation);
+            }
+        }
+    }
+
+    #[test]
+    fn old_euler_rotations_are_read() {
+        let transform: Transform =
+            ron::from_str("(location: (1.0, 2.0, 3.0), rotation: (0.5, -1.0, 2.0), size: (1.0, 1.0, 1.0))").unwrap();
+        // how the angles were applied before rotations were quaternions
+        let matrix = Mat4::from_rotation_x(0.5) * Mat4::from_rotation_y(-1.0) * Mat4::from_rotation_z(2.0);
+        assert!(Mat4::from_quat(transform.rotation).abs_diff_eq(matrix, 1e-5));
+        assert_eq!(transform.location, Vec3::new(1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn quaternions_are_read_normalized() {
+        let transform: Transform =
+            ron::from_str("(location: (0.0, 0.0, 0.0), rotation: (0.0, 2.0, 0.0, 0.0), size: (1.0, 1.0, 1.0))").unwrap();
+        assert_eq!(transform.rotation, Quat::from_xyzw(0.0, 1.0, 0.0, 0.0));
+        let text = ron::to_string(&transform).unwrap();
+        assert_eq!(ron::from_str::<Transform>(&text).unwrap(), transform);
+    }
+
+    #[test]
+    fn look_at_points_z_at_the_target() {
+        let mut transform = Transform {
+            location: Vec3::new(1.0, 1.0, 1.0),
+            ..Transform::default()
+        };
+        transform.look_at(Vec3::new(4.0, 5.0, 1.0), Vec3::Y);
+        assert!((transform.rotation * Vec3::Z).abs_diff_eq(Vec3::new(0.6, 0.8, 0.0), 1e-5));
+        // the Y axis stays on the side of `up`, without any roll
+        let up = transform.rotation * Vec3::Y;
+        assert!(up.y > 0.0 && up.z.abs() < 1e-5, "{}", up);
+
+        let before = transform.rotation;
+        transform.look_at(Vec3::new(1.0, 7.0, 1.0), Vec3::Y);
+        assert_eq!(transform.rotation, before);
+    }
+
+    #[test]
+    fn slerp_takes_the_shortest_arc() {
+        let from = Transform::default();
+        let to = Transform {
+            location: Vec3::new(2.0, 0.0, 0.0),
+            rotation: Quat::from_rotation_y(FRAC_PI_2),
+            size: Vec3::splat(3.0),
+        };
+        let halfway = from.slerp(&to, 0.5);
+        assert_eq!(halfway.location, Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(halfway.size, Vec3::splat(2.0));
+        assert_same_rotation(halfway.rotation, Quat::from_rotation_y(FRAC_PI_2 / 2.0));
+
+        // the same orientation stored as the negated quaternion doesn't make it turn the long way
+        let negated = Transform {
+            rotation: -to.rotation,
+            ..to
+        };
+        assert_same_rotation(from.slerp(&negated, 0.5).rotation, Quat::from_rotation_y(FRAC_PI_2 / 2.0));
+        assert_same_rotation(from.slerp(&to, 1.0).rotation, to.rotation);
+    }
+}
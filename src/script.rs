@@ -0,0 +1,126 @@
+use crate::inspect::{self, Inspect};
+use crate::physics::PhysicsEvent;
+use crate::scene::{NodeId, Scene};
+use glam::f32::Vec3;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// Gameplay behaviour attached to a node, driven by physics events and the simulation steps
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Script {
+    /// Disappears when a character walks into the node's trigger
+    Pickup { collected: bool },
+    /// Keeps turning to face the eyes of the closest character.
+    /// `turn_speed` is how quickly it catches up, higher is snappier.
+    Watch { turn_speed: f32 },
+}
+
+impl Default for Script {
+    fn default() -> Self {
+        Script::Pickup { collected: false }
+    }
+}
+
+impl Script {
+    /// One of each kind, for picking a new script
+    fn examples() -> [Script; 2] {
+        [Script::Pickup { collected: false }, Script::Watch { turn_speed: 4.0 }]
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Script::Pickup { .. } => "Pickup",
+            Script::Watch { .. } => "Watch",
+        }
+    }
+}
+
+impl Inspect for Script {
+    fn inspect(&mut self, ui: &mut egui::Ui, label: &str) -> bool {
+        let before = self.clone();
+        egui::ComboBox::from_label(label)
+            .selected_text(self.name())
+            .show_ui(ui, |ui| {
+                for example in Script::examples().iter() {
+                    if ui.selectable_label(self.name() == example.name(), example.name()).clicked()
+                        && self.name() != example.name()
+                    {
+                        *self = example.clone();
+                    }
+                }
+            });
+        match self {
+            Script::Pickup { collected } => {
+                collected.inspect(ui, "collected");
+            }
+            Script::Watch { turn_speed } => {
+                inspect::row(ui, "turn speed", |ui| inspect::positive(ui, turn_speed));
+            }
+        }
+        *self != before
+    }
+}
+
+/// Runs the scripts that act every step rather than on events
+pub fn update(scene: &mut Scene, dt: f32) {
+    let eyes: Vec<Vec3> = scene
+        .nodes
+        .iter()
+        .filter_map(|node| {
+            let controller = node.controller.as_ref()?;
+            Some(scene.world_transform(node).location + Vec3::Y * controller.eye_height)
+        })
+        .collect();
+
+    for index in 0..scene.nodes.len() {
+        let turn_speed = match scene.nodes[index].script {
+            Some(Script::Watch { turn_speed }) => turn_speed,
+            _ => continue,
+        };
+        let node = &scene.nodes[index];
+        let world = scene.world_transform(node);
+        let closest = eyes.iter().copied().min_by(|a, b| {
+            let distance = |eye: Vec3| eye.distance_squared(world.location);
+            distance(*a).partial_cmp(&distance(*b)).unwrap_or(Ordering::Equal)
+        });
+        let eye = match closest {
+            Some(eye) => eye,
+            None => continue,
+        };
+
+        let mut goal = world;
+        goal.look_at(eye, Vec3::Y);
+        // the same share of the remaining turn every second, independent of the timestep
+        let world = world.slerp(&goal, 1.0 - (-turn_speed * dt).exp());
+        let parent = scene.parent_transform(node);
+        scene.nodes[index].transform.rotation = parent.world_to_child(&world).rotation;
+    }
+}
+
+/// Lets the scripts of the nodes involved react to `events`
+pub fn dispatch(scene: &mut Scene, events: &[PhysicsEvent]) {
+    for &event in events {
+        match event {
+            PhysicsEvent::TriggerEnter { trigger, other } => on_trigger_enter(scene, trigger, other),
+            PhysicsEvent::TriggerExit { .. } | PhysicsEvent::CollisionBegin(..) | PhysicsEvent::CollisionEnd(..) => {}
+        }
+    }
+}
+
+fn on_trigger_enter(scene: &mut Scene, trigger: NodeId, other: NodeId) {
+    let is_character = matches!(scene.node(other), Some(node) if node.controller.is_some());
+    let node = match scene.node_mut(trigger) {
+        Some(node) => node,
+        None => return,
+    };
+
+    match &mut node.script {
+        Some(Script::Pickup { collected }) if !*collected && is_character => {
+            *collected = true;
+            node.mesh = None;
+            node.collider = None;
+            log::info!("{} collected", node.name);
+        }
+        _ => {}
+    }
+}
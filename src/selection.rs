@@ -0,0 +1,42 @@
+use crate::scene::{NodeId, Scene};
+
+/// The nodes picked in the viewport or the outliner. The one picked last is the
+/// primary one, it is shown in the inspector and gets the gizmo.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Selection {
+    nodes: Vec<NodeId>,
+}
+
+impl Selection {
+    pub fn primary(&self) -> Option<NodeId> {
+        self.nodes.last().copied()
+    }
+
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.nodes.contains(&id)
+    }
+
+    pub fn ids(&self) -> &[NodeId] {
+        &self.nodes
+    }
+
+    /// Selects only `id`, or nothing for `None`
+    pub fn set(&mut self, id: Option<NodeId>) {
+        self.nodes.clear();
+        self.nodes.extend(id);
+    }
+
+    /// Adds `id` as the primary node, or removes it if it was selected already
+    pub fn toggle(&mut self, id: NodeId) {
+        if self.contains(id) {
+            self.nodes.retain(|&selected| selected != id);
+        } else {
+            self.nodes.push(id);
+        }
+    }
+
+    /// Forgets nodes that were deleted, e.g. by an undo
+    pub fn retain_existing(&mut self, scene: &Scene) {
+        self.nodes.retain(|&id| scene.node(id).is_some());
+    }
+}
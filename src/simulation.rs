@@ -0,0 +1,200 @@
+use crate::console::{Registry, Value};
+use crate::controller;
+use crate::input::{ActionMap, InputEvent, InputState, Playback, Recording};
+use crate::mesh::MeshLibrary;
+use crate::physics::{self, ContactTracker, PhysicsEvent};
+use crate::scene::Scene;
+use crate::script;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Length of one simulation step in seconds
+pub const TIMESTEP: f32 = 1.0 / 60.0;
+
+/// Upper bound of steps per update so a long stall doesn't freeze the editor
+const MAX_STEPS_PER_UPDATE: u32 = 8;
+
+/// How many of the latest physics events are kept around for display
+const RECENT_EVENTS: usize = 20;
+
+/// Runs the game logic in fixed steps, independent of the frame rate.
+///
+/// All input goes through here so it can be recorded and replayed:
+/// given the same starting scene and the same events per step,
+/// the simulation always ends up in the same state.
+pub struct Simulation {
+    timestep: f32,
+    accumulator: Duration,
+    /// number of steps since the start (or since the playback started)
+    pub frame: u64,
+    pub input: InputState,
+    pub actions: ActionMap,
+    pub meshes: MeshLibrary,
+    pending: Vec<InputEvent>,
+    recording: Option<(u64, Recording)>,
+    playback: Option<Playback>,
+    contacts: ContactTracker,
+    /// the latest physics events with the frame they happened in, oldest first
+    pub recent_events: VecDeque<(u64, PhysicsEvent)>,
+}
+
+impl Simulation {
+    pub fn new(timestep: f32) -> Self {
+        Self {
+            timestep,
+            accumulator: Duration::default(),
+            frame: 0,
+            input: InputState::default(),
+            actions: ActionMap::default(),
+            meshes: MeshLibrary::new(),
+            pending: Vec::new(),
+            recording: None,
+            playback: None,
+            contacts: ContactTracker::default(),
+            recent_events: VecDeque::new(),
+        }
+    }
+
+    /// Queues live input for the next step. Ignored while a playback is running.
+    pub fn push_event(&mut self, event: InputEvent) {
+        if self.playback.is_none() {
+            self.pending.push(event);
+        }
+    }
+
+    /// Advances by `elapsed` wall time, running as many steps as are due
+    pub fn update(&mut self, scene: &mut Scene, elapsed: Duration) {
+        let timestep = Duration::from_secs_f32(self.timestep);
+        self.accumulator += elapsed;
+
+        let mut steps = 0;
+        while self.accumulator >= timestep {
+            self.accumulator -= timestep;
+            steps += 1;
+            if steps > MAX_STEPS_PER_UPDATE {
+                self.accumulator = Duration::default();
+                break;
+            }
+            self.step(scene);
+        }
+    }
+
+    pub fn step(&mut self, scene: &mut Scene) {
+        let events = match &mut self.playback {
+            Some(playback) => playback.events_for(self.frame),
+            None => std::mem::take(&mut self.pending),
+        };
+
+        for event in events {
+            if let Some((start, recording)) = &mut self.recording {
+                recording.record(self.frame - *start, event);
+            }
+            self.input.apply(&event);
+        }
+
+        self.meshes.track(scene);
+        if self.playback.is_some() || self.recording.is_some() {
+            // a recording and its replay have to collide with the same meshes, so neither goes on while those load
+            self.meshes.wait_for_colliders(scene);
+        }
+        self.meshes.update();
+        controller::update(scene, &self.actions, &self.input, &self.meshes, self.timestep);
+        script::update(scene, self.timestep);
+        let contacts = physics::step(scene, &self.meshes, self.timestep);
+        let events = self.contacts.update(scene, &contacts);
+        script::dispatch(scene, &events);
+        for event in events {
+            if self.recent_events.len() == RECENT_EVENTS {
+                self.recent_events.pop_front();
+            }
+            self.recent_events.push_back((self.frame, event));
+        }
+
+        self.frame += 1;
+        if let Some((start, recording)) = &mut self.recording {
+            recording.frames = self.frame - *start;
+        }
+        if let Some(playback) = &self.playback {
+            if playback.is_finished(self.frame) {
+                self.playback = None;
+                self.input = InputState::default();
+            }
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playback.is_some()
+    }
+
+    /// Starts recording from the current state of `scene`.
+    /// Keys that are already held are recorded as pressed in the first step.
+    pub fn start_recording(&mut self, scene: &Scene) {
+        let mut recording = Recording::new(self.timestep, scene.clone());
+        for event in self.input.to_events() {
+            recording.record(0, event);
+        }
+        self.recording = Some((self.frame, recording));
+    }
+
+    pub fn stop_recording(&mut self) -> Option<Recording> {
+        self.recording.take().map(|(_, recording)| recording)
+    }
+
+    /// Resets `scene` to the start of `recording` and plays it back
+    pub fn start_playback(&mut self, recording: Recording, scene: &mut Scene) {
+        *scene = recording.scene.clone();
+        self.timestep = recording.timestep;
+        self.accumulator = Duration::default();
+        self.frame = 0;
+        self.input = InputState::default();
+        self.pending.clear();
+        self.recording = None;
+        self.contacts = ContactTracker::default();
+        self.recent_events.clear();
+
+        let playback = Playback::new(recording);
+        if !playback.is_finished(0) {
+            self.playback = Some(playback);
+        }
+    }
+}
+
+/// Console commands and variables of the simulation
+pub fn register_commands(registry: &mut Registry) {
+    registry.var("timescale", "speed of the simulation, 1 is real time and 0 pauses it", Value::Float(1.0));
+    registry.command("step", "step [count]: runs simulation steps right away, e.g. while paused", |context, args| {
+        let count = match args {
+            [] => 1,
+            [count] => count.parse().map_err(|_| format!("expected a number of steps, got {}", count))?,
+            _ => return Err("usage: step [count]".to_string()),
+        };
+        for _ in 0..count {
+            context.simulation.step(context.scene);
+        }
+        Ok(format!("frame {}", context.simulation.frame))
+    });
+    registry.command("replay", "replay <recording.ron>: plays an input recording back", |context, args| match args {
+        [path] => {
+            let recording = Recording::load(path).map_err(|err| format!("Could not load recording {}: {}", path, err))?;
+            context.simulation.start_playback(recording, context.scene);
+            context.history.clear();
+            Ok(String::new())
+        }
+        _ => Err("usage: replay <recording.ron>".to_string()),
+    });
+}
+
+/// Plays `recording` back without a window and returns the scene it ends in
+pub fn replay(recording: Recording) -> Scene {
+    let mut scene = Scene::default();
+    let mut simulation = Simulation::new(recording.timestep);
+    simulation.start_playback(recording, &mut scene);
+    while simulation.is_playing() {
+        simulation.step(&mut scene);
+    }
+    scene
+}
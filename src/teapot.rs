@@ -2,7 +2,7 @@ use glium::implement_vertex;
 
 #[derive(Copy, Clone)]
 pub struct Vertex {
-    position: (f32, f32, f32)
+    pub position: (f32, f32, f32)
 }
 
 implement_vertex!(Vertex, position);
@@ -544,7 +544,7 @@ pub const VERTICES: [Vertex; 531] = [
 
 #[derive(Copy, Clone)]
 pub struct Normal {
-    normal: (f32, f32, f32)
+    pub normal: (f32, f32, f32)
 }
 
 implement_vertex!(Normal, normal);
@@ -0,0 +1,94 @@
+use crate::logging::level_color;
+use egui::{Align2, CtxRef};
+use log::Level;
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+/// How long a message stays up
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+
+/// Older messages go away early when more come in
+const MAX_TOASTS: usize = 5;
+
+struct Toast {
+    level: Level,
+    message: String,
+    shown: Instant,
+}
+
+/// Messages in the bottom right corner that go away by themselves,
+/// for errors the editor recovers from
+#[derive(Default)]
+pub struct Toasts {
+    toasts: Vec<Toast>,
+}
+
+impl Toasts {
+    /// Logs `error` and shows it for a while
+    pub fn error(&mut self, error: impl Display) {
+        let message = error.to_string();
+        if self.push(Level::Error, message.clone()) {
+            log::error!("{}", message);
+        }
+    }
+
+    pub fn warn(&mut self, warning: impl Display) {
+        let message = warning.to_string();
+        if self.push(Level::Warn, message.clone()) {
+            log::warn!("{}", message);
+        }
+    }
+
+    pub fn info(&mut self, info: impl Display) {
+        let message = info.to_string();
+        if self.push(Level::Info, message.clone()) {
+            log::info!("{}", message);
+        }
+    }
+
+    /// Shows a message that was already logged where it happened.
+    /// `false` if it's still up from before, the same failure every frame would fill the screen and the log.
+    pub fn push(&mut self, level: Level, message: String) -> bool {
+        if let Some(toast) = self.toasts.iter_mut().find(|toast| toast.message == message) {
+            toast.shown = Instant::now();
+            return false;
+        }
+        if self.toasts.len() == MAX_TOASTS {
+            self.toasts.remove(0);
+        }
+        self.toasts.push(Toast {
+            level,
+            message,
+            shown: Instant::now(),
+        });
+        true
+    }
+
+    pub fn show(&mut self, ctx: &CtxRef) {
+        self.toasts.retain(|toast| toast.shown.elapsed() < TOAST_DURATION);
+        if self.toasts.is_empty() {
+            return;
+        }
+        let mut dismissed = None;
+        egui::Area::new("toasts")
+            .order(egui::Order::Foreground)
+            .anchor(Align2::RIGHT_BOTTOM, egui::vec2(-8.0, -8.0))
+            .show(ctx, |ui| {
+                for (index, toast) in self.toasts.iter().enumerate() {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.set_max_width(400.0);
+                        ui.horizontal(|ui| {
+                            if ui.small_button("x").clicked() {
+                                dismissed = Some(index);
+                            }
+                            let color = level_color(ui, toast.level);
+                            ui.add(egui::Label::new(&toast.message).text_color(color).wrap(true));
+                        });
+                    });
+                }
+            });
+        if let Some(index) = dismissed {
+            self.toasts.remove(index);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Renders the scenes in `tests/scenes` with `game render` and compares them with the pictures in `tests/golden`.
+//! After a change to the renderer that is meant to change the pictures, store new ones with `UPDATE_GOLDEN=1 cargo test`.
+
+use image::{Rgba, RgbaImage};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+const SIZE: &str = "256x192";
+
+/// How far apart two pixels may be before they count as different, as a share of the largest possible difference
+const PIXEL_THRESHOLD: f64 = 0.1;
+
+/// Share of the pixels that may differ, rasterizers don't agree on every edge
+const MAX_DIFFERENT: f64 = 0.005;
+
+/// The difference of black and white in `yiq_difference`
+const MAX_YIQ_DIFFERENCE: f64 = 35215.0;
+
+fn check(name: &str) {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let scene = root.join("scenes").join(format!("{}.scene.ron", name));
+    let golden = root.join("golden").join(format!("{}.png", name));
+    let out = Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden");
+    fs::create_dir_all(&out).unwrap();
+    let actual = out.join(format!("{}.png", name));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_game"))
+        .arg("render")
+        .arg(&scene)
+        .args(["--camera", "Camera", "--size", SIZE, "--out"])
+        .arg(&actual)
+        .current_dir(&out)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "game render {} failed:\n{}",
+        scene.display(),
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::copy(&actual, &golden).unwrap();
+        return;
+    }
+    assert!(golden.exists(), "there's no {} yet, make it with UPDATE_GOLDEN=1", golden.display());
+
+    let expected = image::open(&golden).unwrap().to_rgba8();
+    let actual_image = image::open(&actual).unwrap().to_rgba8();
+    assert_eq!(expected.dimensions(), actual_image.dimensions(), "{} changed size", name);
+
+    let (diff, different) = compare(&expected, &actual_image);
+    let share = different as f64 / (expected.width() * expected.height()) as f64;
+    if share > MAX_DIFFERENT {
+        let diff_path = out.join(format!("{}.diff.png", name));
+        diff.save(&diff_path).unwrap();
+        panic!(
+            "{} differs from {} in {} pixels ({:.2}%), see {} and {}",
+            name,
+            golden.display(),
+            different,
+            share * 100.0,
+            actual.display(),
+            diff_path.display()
+        );
+    }
+}
+
+/// Marks the pixels that differ in red on a faded copy of `expected` and counts them
+fn compare(expected: &RgbaImage, actual: &RgbaImage) -> (RgbaImage, usize) {
+    let mut diff = RgbaImage::new(expected.width(), expected.height());
+    let mut different = 0;
+    for ((a, b), pixel) in expected.pixels().zip(actual.pixels()).zip(diff.pixels_mut()) {
+        if yiq_difference(a, b) > MAX_YIQ_DIFFERENCE * PIXEL_THRESHOLD * PIXEL_THRESHOLD {
+            different += 1;
+            *pixel = Rgba([255, 0, 0, 255]);
+        } else {
+            let luma = (yiq(a)[0] * 0.1 + 255.0 * 0.9) as u8;
+            *pixel = Rgba([luma, luma, luma, 255]);
+        }
+    }
+    (diff, different)
+}
+
+fn yiq(pixel: &Rgba<u8>) -> [f64; 3] {
+    let [r, g, b] = [pixel[0] as f64, pixel[1] as f64, pixel[2] as f64];
+    [
+        r * 0.298_895_31 + g * 0.586_622_47 + b * 0.114_482_23,
+        r * 0.595_977_99 - g * 0.274_176_10 - b * 0.321_801_89,
+        r * 0.211_470_17 - g * 0.522_617_11 + b * 0.311_146_94,
+    ]
+}
+
+/// Squared distance in YIQ, weighted the way the eye notices brightness more than hue
+fn yiq_difference(a: &Rgba<u8>, b: &Rgba<u8>) -> f64 {
+    let (a, b) = (yiq(a), yiq(b));
+    let (y, i, q) = (a[0] - b[0], a[1] - b[1], a[2] - b[2]);
+    0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q
+}
+
+/// The plain teapot as it's spawned, white
+#[test]
+fn teapot() {
+    check("teapot");
+}
+
+/// Teapots and cubes turned every way, lit by the light scenes without lights get from the camera
+#[test]
+fn shading() {
+    check("shading");
+}
+
+/// Two directional lights of different colors and a point light fading out over the floor
+#[test]
+fn lights() {
+    check("lights");
+}
+
+/// See-through nodes in front of opaque ones
+#[test]
+fn transparency() {
+    check("transparency");
+}
+
+/// Teapots further and further away, each drawn with a simpler level of detail than the one before
+#[test]
+fn lod() {
+    check("lod");
+}
@@ -0,0 +1,35 @@
+//! Plays the input recordings in `tests/recordings` back with `game replay` and checks that they end in the scenes
+//! stored next to them. After a change to the simulation that is meant to change the outcome,
+//! store new scenes with `UPDATE_GOLDEN=1 cargo test`.
+
+use std::path::Path;
+use std::process::Command;
+
+fn check(name: &str) {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("recordings");
+    let recording = root.join(format!("{}.recording.ron", name));
+    let expected = root.join(format!("{}.expected.ron", name));
+    let option = if std::env::var_os("UPDATE_GOLDEN").is_some() { "--out" } else { "--expect" };
+
+    let output = Command::new(env!("CARGO_BIN_EXE_game"))
+        .arg("replay")
+        .arg(&recording)
+        .arg(option)
+        .arg(&expected)
+        .current_dir(&root)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "game replay {} failed:\n{}",
+        recording.display(),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// The character jumps on its way onto a step, picking up a coin.
+/// A ball lands on a teapot, which has to be loaded before the first step for its collider.
+#[test]
+fn walk() {
+    check("walk");
+}